@@ -1,8 +1,8 @@
 use cgmath::vec3;
 use renderer::{
-    point3, shape, BindGroupDescriptor, BindGroupEntry, BindingType, Camera, IndexBuffer, Mesh,
-    PipelineDescriptor, RenderPipelineId, Renderer, ShaderStage, UniformProperty, VertexBuffer,
-    WindowHandler, WindowSettings,
+    point3, shape, AttributeMapping, BindGroupDescriptor, BindGroupEntry, BindingType, Camera,
+    IndexBuffer, Mesh, PipelineBuilder, RenderPipelineId, Renderer, ShaderReload, ShaderStage,
+    ShaderWatcher, UniformProperty, VertexBuffer, WindowHandler, WindowSettings,
 };
 
 pub struct Example {
@@ -14,6 +14,8 @@ pub struct Example {
     camera: Camera,
     /// Render Pipeline
     pipeline: RenderPipelineId,
+    /// Reloads the shaders & pipeline when the shader files change
+    shader_watcher: ShaderWatcher,
     /// Bind group descriptor
     bind_group_descriptor: BindGroupDescriptor,
 }
@@ -27,13 +29,23 @@ impl WindowHandler for Example {
     where
         Self: Sized,
     {
-        let vertex_shader =
-            renderer.create_shader(include_str!("shaders/vertex.glsl"), ShaderStage::Vertex);
-        let fragment_shader =
-            renderer.create_shader(include_str!("shaders/fragment.glsl"), ShaderStage::Fragment);
+        let shader_dir = utils::app_dir()
+            .expect("Failed to get app dir")
+            .join("examples/01-spinning-cube-wgpu/shaders");
+
+        let mut shader_watcher = ShaderWatcher::new().expect("Failed to watch shaders");
+        let vertex_shader = shader_watcher
+            .load_shader(renderer, shader_dir.join("vertex.glsl"), ShaderStage::Vertex)
+            .unwrap_or_else(|error| panic!("{}", error));
+        let fragment_shader = shader_watcher
+            .load_shader(renderer, shader_dir.join("fragment.glsl"), ShaderStage::Fragment)
+            .unwrap_or_else(|error| panic!("{}", error));
 
         let mesh: Mesh = shape::Cube::new(1.0).into();
-        let vertex_buffer = renderer.create_vertex_buffer(&mesh.vertex_data());
+        let vertices = mesh
+            .map_attributes(&vertex_shader.layout().inputs(), &AttributeMapping::default())
+            .unwrap_or_else(|error| panic!("{}", error));
+        let vertex_buffer = renderer.create_vertex_buffer(&vertices.data);
         let index_buffer = renderer.create_index_buffer(&mesh.indices.unwrap());
 
         let mut camera = Camera::default();
@@ -43,8 +55,13 @@ impl WindowHandler for Example {
             vec3(0.0, 1.0, 0.0),
         );
 
-        let pipeline_descriptor = PipelineDescriptor::new(vertex_shader, fragment_shader);
-        let pipeline = renderer.create_pipeline(&pipeline_descriptor);
+        let pipeline_descriptor = PipelineBuilder::new(vertex_shader, fragment_shader)
+            .vertex_buffers(vec![vertices.descriptor])
+            .build()
+            .unwrap_or_else(|error| panic!("{}", error));
+        let pipeline = shader_watcher
+            .create_pipeline(renderer, pipeline_descriptor)
+            .unwrap_or_else(|error| panic!("{}", error));
 
         let bind_group_descriptor = BindGroupDescriptor::new(
             0,
@@ -55,7 +72,7 @@ impl WindowHandler for Example {
                     dynamic: false,
                     property: UniformProperty::Vec3,
                 },
-                shader_stage: ShaderStage::Vertex,
+                shader_stages: ShaderStage::Vertex.into(),
             }],
         );
 
@@ -64,11 +81,19 @@ impl WindowHandler for Example {
             index_buffer,
             camera,
             pipeline,
+            shader_watcher,
             bind_group_descriptor,
         }
     }
 
     fn render(&mut self, window: &mut renderer::Window, renderer: &mut Renderer) {
+        for reload in self.shader_watcher.update(renderer) {
+            match reload {
+                ShaderReload::Reloaded(path) => println!("Reloaded shader {}", path.display()),
+                ShaderReload::Failed(path, error) => println!("Failed to reload {}: {}", path.display(), error),
+            }
+        }
+
         let mut pass_descriptor = window.into();
 
         renderer.begin_pass(&mut pass_descriptor, &mut |render_pass| {
@@ -37,8 +37,10 @@ pub struct Example {
 
 impl WindowHandler for Example {
     fn load(_window: &Window, _resources: &Resources, renderer: &mut Renderer) -> Self where Self: Sized {
-        let vertex_shader = renderer.create_shader(&VERTEX_SHADER, ShaderStage::Vertex);
-        let fragment_shader = renderer.create_shader(&FRAGMENT_SHADER, ShaderStage::Fragment);
+        let vertex_shader = renderer.create_shader(&VERTEX_SHADER, ShaderStage::Vertex)
+            .unwrap_or_else(|error| panic!("{}", error));
+        let fragment_shader = renderer.create_shader(&FRAGMENT_SHADER, ShaderStage::Fragment)
+            .unwrap_or_else(|error| panic!("{}", error));
 
         let mut shaders = HashMap::new();
         shaders.insert(ShaderStage::Vertex, vertex_shader);
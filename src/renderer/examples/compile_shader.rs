@@ -25,7 +25,10 @@ const VERTEX_SHADER: &str = r#"
 "#;
 
 fn main() {
-    let renderer = futures::executor::block_on(Renderer::new());
+    let renderer = futures::executor::block_on(Renderer::new())
+        .expect("Failed to create renderer");
+    let info = renderer.adapter_info();
+    println!("Adapter {} ({:?})", info.name, info.backend);
     let shader = Shader::compile(VERTEX_SHADER, renderer::ShaderStage::Vertex, &renderer.device)
         .expect("Failed to compile shader");
     println!("Shader {}", shader.stage);
@@ -0,0 +1,49 @@
+use renderer::{BindGroupBuilder, BufferUsage, ComputePipelineDescriptor, Renderer, ShaderStage};
+
+const COMPUTE_SHADER: &str = r#"
+    #version 450
+
+    layout(local_size_x = 64) in;
+
+    layout(set = 0, binding = 0) buffer Values {
+        float values[];
+    };
+
+    void main() {
+        uint index = gl_GlobalInvocationID.x;
+        values[index] = values[index] * values[index];
+    }
+"#;
+
+const NUM_VALUES: u32 = 256;
+
+fn main() {
+    let mut renderer = futures::executor::block_on(Renderer::new())
+        .expect("Failed to create renderer");
+
+    let compute_shader = renderer.create_shader(COMPUTE_SHADER, ShaderStage::Compute)
+        .unwrap_or_else(|error| panic!("{}", error));
+    let pipeline_descriptor = ComputePipelineDescriptor::new(compute_shader)
+        .unwrap_or_else(|error| panic!("{}", error));
+    let pipeline = renderer.create_compute_pipeline(&pipeline_descriptor);
+
+    let values = (0..NUM_VALUES).map(|i| i as f32).collect::<Vec<f32>>();
+    let buffer = renderer.create_buffer_with_data(
+        bytemuck::cast_slice(&values),
+        BufferUsage::STORAGE | BufferUsage::COPY_SRC,
+    );
+
+    let bind_group = BindGroupBuilder::new(&pipeline_descriptor.layout.bind_groups[0]).buffer("Values", &buffer);
+    let bind_group = renderer.create_bind_group(&bind_group).expect("Failed to create bind group");
+
+    let [x, y, z] = pipeline_descriptor.workgroups([NUM_VALUES, 1, 1]);
+    renderer.begin_compute_pass(&mut |compute_pass| {
+        compute_pass.set_pipeline(&pipeline);
+        compute_pass.set_bind_group(0, &bind_group);
+        compute_pass.dispatch(x, y, z);
+    });
+
+    let data = renderer.read_buffer(&buffer).expect("Failed to read buffer");
+    let squares: &[f32] = bytemuck::cast_slice(&data);
+    println!("Squares: {:?}", &squares[..8]);
+}
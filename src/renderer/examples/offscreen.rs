@@ -0,0 +1,55 @@
+use renderer::{PassDescriptor, PipelineBuilder, Renderer, ShaderStage, TextureFormat};
+
+const VERTEX_SHADER: &str = r#"
+    #version 450
+
+    const vec2 positions[3] = vec2[3](
+        vec2(-0.5, -0.5),
+        vec2( 0.5, -0.5),
+        vec2( 0.0,  0.5)
+    );
+
+    void main() {
+        gl_Position = vec4(positions[gl_VertexIndex], 0.0, 1.0);
+    }
+"#;
+
+const FRAGMENT_SHADER: &str = r#"
+    #version 450
+
+    layout (location = 0) out vec4 outColor;
+
+    void main() {
+        outColor = vec4(1.0, 0.5, 0.0, 1.0);
+    }
+"#;
+
+fn main() {
+    let mut renderer = futures::executor::block_on(Renderer::new())
+        .expect("Failed to create renderer");
+
+    let vertex_shader = renderer.create_shader(VERTEX_SHADER, ShaderStage::Vertex)
+        .unwrap_or_else(|error| panic!("{}", error));
+    let fragment_shader = renderer.create_shader(FRAGMENT_SHADER, ShaderStage::Fragment)
+        .unwrap_or_else(|error| panic!("{}", error));
+
+    let pipeline_descriptor = PipelineBuilder::new(vertex_shader, fragment_shader)
+        .color_format(TextureFormat::Rgba8UnormSrgb)
+        .no_depth()
+        .build()
+        .unwrap_or_else(|error| panic!("{}", error));
+    let pipeline = renderer.create_pipeline(&pipeline_descriptor)
+        .unwrap_or_else(|error| panic!("{}", error));
+
+    let target = renderer.create_texture_2d(512, 512, TextureFormat::Rgba8UnormSrgb);
+    let mut pass_descriptor = PassDescriptor::from(&target);
+
+    renderer.begin_pass(&mut pass_descriptor, &mut |render_pass| {
+        render_pass.set_pipeline(&pipeline);
+        render_pass.draw(0..3, 0..1);
+    });
+
+    let image = renderer.read_texture(&target).expect("Failed to read texture");
+    image.save("offscreen.png").expect("Failed to save image");
+    println!("Saved offscreen.png");
+}
@@ -1,3 +1,5 @@
+use uuid::Uuid;
+
 bitflags::bitflags! {
     #[repr(transparent)]
     pub struct BufferUsage: u32 {
@@ -29,3 +31,24 @@ pub struct BufferDescriptor {
     /// Usage of the Buffer
     pub usage: BufferUsage,
 }
+
+/// A handle to a generic GPU buffer, e.g. uniform or storage buffer
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Buffer {
+    /// Id of this buffer
+    pub uuid: Uuid,
+    /// Size of the buffer in bytes
+    pub size: u64,
+    /// Usage of the buffer
+    pub usage: BufferUsage,
+}
+
+impl Buffer {
+    pub fn new(size: u64, usage: BufferUsage) -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            size,
+            usage,
+        }
+    }
+}
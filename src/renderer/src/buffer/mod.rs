@@ -1,3 +1,5 @@
 pub mod buffer;
+pub mod uniform_buffer;
 
-pub use buffer::*;
\ No newline at end of file
+pub use buffer::*;
+pub use uniform_buffer::*;
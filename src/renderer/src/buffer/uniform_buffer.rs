@@ -0,0 +1,319 @@
+use std::{fmt, marker::PhantomData};
+
+use crate::{BindGroupEntry, BindingType, Buffer, LayoutRule, UniformField, UniformProperty};
+
+#[derive(Debug, PartialEq)]
+pub enum UniformError {
+    /// The binding is not a uniform buffer
+    NotAUniform(String),
+    /// The size of the Rust type does not match the size of the uniform block
+    SizeMismatch(String, u64, u64),
+    /// The members of the Rust type do not match the members of the uniform block
+    LayoutMismatch(String, UniformProperty, UniformProperty),
+    /// No member with the given path exists in the uniform block
+    FieldNotFound(String, String),
+    /// The uniform buffer was created for a different binding, contains buffer & binding name
+    BindingMismatch(String, String),
+    /// The member does not fit into the uniform buffer, contains path, end of the member & buffer size
+    OutOfBounds(String, u64, u64),
+}
+
+impl fmt::Display for UniformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            UniformError::NotAUniform(name) => format!("Binding '{}' is not a uniform buffer", name),
+            UniformError::SizeMismatch(name, expected, found) => format!(
+                "Uniform '{}' expects {} bytes, but type has {} bytes", name, expected, found,
+            ),
+            UniformError::LayoutMismatch(name, expected, found) => format!(
+                "Uniform '{}' expects layout {:?}, but type has layout {:?}", name, expected, found,
+            ),
+            UniformError::FieldNotFound(name, path) => format!("Uniform '{}' has no member '{}'", name, path),
+            UniformError::BindingMismatch(buffer, binding) => format!(
+                "Uniform buffer of '{}' cannot be updated with a member of binding '{}'", buffer, binding,
+            ),
+            UniformError::OutOfBounds(path, end, size) => format!(
+                "Member '{}' ends at byte {}, but the uniform buffer has {} bytes", path, end, size,
+            ),
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A type that can be uploaded into a uniform buffer.
+///
+/// The property describes the members of the type in the same order & with the same offsets
+/// as they are declared in the uniform block of the shader, e.g.
+///
+/// layout(binding = 0) uniform Locals {
+///     mat4 modelView;
+///     vec4 color;
+/// };
+///
+/// is described by
+/// `UniformProperty::structure(vec![("modelView", UniformProperty::Mat4), ("color", UniformProperty::Vec4)], LayoutRule::Std140)`.
+/// Member names are not compared, only types & offsets.
+///
+pub trait Uniform: bytemuck::Pod {
+    /// Returns the layout of the type
+    fn property() -> UniformProperty;
+}
+
+impl Uniform for f32 {
+    fn property() -> UniformProperty {
+        UniformProperty::Float
+    }
+}
+
+impl Uniform for i32 {
+    fn property() -> UniformProperty {
+        UniformProperty::Int
+    }
+}
+
+impl Uniform for u32 {
+    fn property() -> UniformProperty {
+        UniformProperty::UInt
+    }
+}
+
+impl Uniform for [i32; 2] {
+    fn property() -> UniformProperty {
+        UniformProperty::IVec2
+    }
+}
+
+impl Uniform for [i32; 4] {
+    fn property() -> UniformProperty {
+        UniformProperty::IVec4
+    }
+}
+
+impl Uniform for [u32; 2] {
+    fn property() -> UniformProperty {
+        UniformProperty::UVec2
+    }
+}
+
+impl Uniform for [u32; 4] {
+    fn property() -> UniformProperty {
+        UniformProperty::UVec4
+    }
+}
+
+impl Uniform for [f32; 2] {
+    fn property() -> UniformProperty {
+        UniformProperty::Vec2
+    }
+}
+
+impl Uniform for [f32; 3] {
+    fn property() -> UniformProperty {
+        UniformProperty::Vec3
+    }
+}
+
+impl Uniform for [f32; 4] {
+    fn property() -> UniformProperty {
+        UniformProperty::Vec4
+    }
+}
+
+impl Uniform for [[f32; 4]; 4] {
+    fn property() -> UniformProperty {
+        UniformProperty::Mat4
+    }
+}
+
+/// A uniform buffer that holds a single value of type T
+#[derive(Debug)]
+pub struct UniformBuffer<T: Uniform> {
+    /// The underlying buffer
+    pub buffer: Buffer,
+    /// The name of the binding the buffer was validated against
+    pub name: String,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Uniform> UniformBuffer<T> {
+    pub fn new(buffer: Buffer, name: &str) -> Self {
+        Self {
+            buffer,
+            name: name.to_string(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Finds the member of the uniform block by path, e.g. `ubo.lights[2].color`.
+    ///
+    /// Validates that the binding is the one the buffer was created for, that the type U matches
+    /// the member and that the member fits into the buffer.
+    pub fn field<U: Uniform>(&self, binding: &BindGroupEntry, path: &str) -> Result<UniformField, UniformError> {
+        if binding.name != self.name {
+            return Err(UniformError::BindingMismatch(self.name.clone(), binding.name.clone()));
+        }
+
+        let field = find_uniform_field::<U>(binding, path)?;
+        let end = field.offset + std::mem::size_of::<U>() as u64;
+        if end > self.buffer.size {
+            return Err(UniformError::OutOfBounds(path.to_string(), end, self.buffer.size));
+        }
+
+        Ok(field)
+    }
+}
+
+/// Validates that the type T matches the reflected uniform block of the binding
+pub fn validate_uniform<T: Uniform>(entry: &BindGroupEntry) -> Result<(), UniformError> {
+    let property = match &entry.binding_type {
+        BindingType::Uniform { property, .. } => property,
+        _ => return Err(UniformError::NotAUniform(entry.name.clone())),
+    };
+
+    // a uniform block with a single member can be described by the member itself
+    let expected = match property {
+        UniformProperty::Struct(members) if members.len() == 1 && !T::property().same_layout(property) => {
+            &members[0].property
+        }
+        property => property,
+    };
+
+    if !T::property().same_layout(expected) {
+        return Err(UniformError::LayoutMismatch(entry.name.clone(), property.clone(), T::property()));
+    }
+
+    let size = std::mem::size_of::<T>() as u64;
+    if size != property.get_size() {
+        return Err(UniformError::SizeMismatch(entry.name.clone(), property.get_size(), size));
+    }
+
+    Ok(())
+}
+
+/// Finds the member of the uniform block by path, e.g. `ubo.lights[2].color`, and validates
+/// that the type T matches the member.
+pub fn find_uniform_field<T: Uniform>(entry: &BindGroupEntry, path: &str) -> Result<UniformField, UniformError> {
+    let property = match &entry.binding_type {
+        BindingType::Uniform { property, .. } => property,
+        _ => return Err(UniformError::NotAUniform(entry.name.clone())),
+    };
+
+    let field = property
+        .find(path, LayoutRule::Std140)
+        .ok_or_else(|| UniformError::FieldNotFound(entry.name.clone(), path.to_string()))?;
+
+    if !T::property().same_layout(&field.property) {
+        return Err(UniformError::LayoutMismatch(path.to_string(), field.property, T::property()));
+    }
+
+    Ok(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BindGroupEntry, BindingType, Buffer, BufferUsage, LayoutRule, ShaderStage, Uniform, UniformBuffer, UniformError, UniformField, UniformProperty, find_uniform_field, validate_uniform};
+
+    #[repr(C)]
+    #[derive(Debug, Copy, Clone)]
+    struct Locals {
+        model_view: [[f32; 4]; 4],
+        color: [f32; 4],
+    }
+
+    unsafe impl bytemuck::Zeroable for Locals {}
+    unsafe impl bytemuck::Pod for Locals {}
+
+    impl Uniform for Locals {
+        fn property() -> UniformProperty {
+            UniformProperty::structure(
+                vec![("model_view", UniformProperty::Mat4), ("color", UniformProperty::Vec4)],
+                LayoutRule::Std140,
+            )
+        }
+    }
+
+    fn block(members: Vec<(&str, UniformProperty)>) -> UniformProperty {
+        UniformProperty::structure(members, LayoutRule::Std140)
+    }
+
+    fn uniform_entry(property: UniformProperty) -> BindGroupEntry {
+        BindGroupEntry {
+            name: "Locals".into(),
+            index: 0,
+            binding_type: BindingType::Uniform {
+                dynamic: false,
+                property,
+            },
+            shader_stages: ShaderStage::Vertex.into(),
+        }
+    }
+
+    #[test]
+    fn it_accepts_matching_uniform_types() {
+        let entry = uniform_entry(block(vec![("modelView", UniformProperty::Mat4), ("color", UniformProperty::Vec4)]));
+        assert_eq!(Ok(()), validate_uniform::<Locals>(&entry));
+
+        let entry = uniform_entry(block(vec![("color", UniformProperty::Vec4)]));
+        assert_eq!(Ok(()), validate_uniform::<[f32; 4]>(&entry));
+    }
+
+    #[test]
+    fn it_rejects_mismatching_uniform_types() {
+        let property = block(vec![("color", UniformProperty::Vec4), ("modelView", UniformProperty::Mat4)]);
+        let entry = uniform_entry(property.clone());
+        assert_eq!(
+            Err(UniformError::LayoutMismatch("Locals".into(), property, Locals::property())),
+            validate_uniform::<Locals>(&entry),
+        );
+
+        let entry = BindGroupEntry {
+            binding_type: BindingType::Sampler { comparison: false },
+            ..uniform_entry(UniformProperty::Float)
+        };
+        assert_eq!(Err(UniformError::NotAUniform("Locals".into())), validate_uniform::<f32>(&entry));
+    }
+
+    #[test]
+    fn it_finds_uniform_fields_by_path() {
+        let entry = uniform_entry(block(vec![
+            ("time", UniformProperty::Float),
+            ("colors", UniformProperty::Array(Box::new(UniformProperty::Vec4), 4)),
+        ]));
+
+        assert_eq!(
+            Ok(UniformField { offset: 48, property: UniformProperty::Vec4 }),
+            find_uniform_field::<[f32; 4]>(&entry, "ubo.colors[2]"),
+        );
+        assert_eq!(
+            Err(UniformError::FieldNotFound("Locals".into(), "ubo.color".into())),
+            find_uniform_field::<[f32; 4]>(&entry, "ubo.color"),
+        );
+        assert_eq!(
+            Err(UniformError::LayoutMismatch("time".into(), UniformProperty::Float, UniformProperty::UInt)),
+            find_uniform_field::<u32>(&entry, "time"),
+        );
+    }
+
+    #[test]
+    fn it_checks_fields_against_the_uniform_buffer() {
+        let entry = uniform_entry(block(vec![("modelView", UniformProperty::Mat4), ("color", UniformProperty::Vec4)]));
+        let uniform_buffer = UniformBuffer::<Locals>::new(Buffer::new(80, BufferUsage::UNIFORM), "Locals");
+
+        assert_eq!(
+            Ok(UniformField { offset: 64, property: UniformProperty::Vec4 }),
+            uniform_buffer.field::<[f32; 4]>(&entry, "color"),
+        );
+
+        let other = BindGroupEntry { name: "Other".into(), ..entry.clone() };
+        assert_eq!(
+            Err(UniformError::BindingMismatch("Locals".into(), "Other".into())),
+            uniform_buffer.field::<[f32; 4]>(&other, "color"),
+        );
+
+        let small_buffer = UniformBuffer::<[f32; 4]>::new(Buffer::new(16, BufferUsage::UNIFORM), "Locals");
+        assert_eq!(
+            Err(UniformError::OutOfBounds("color".into(), 80, 16)),
+            small_buffer.field::<[f32; 4]>(&entry, "color"),
+        );
+    }
+}
@@ -1,4 +1,5 @@
 extern crate bitflags;
+#[cfg(feature = "glsl")]
 extern crate shaderc;
 
 mod buffer;
@@ -8,6 +9,7 @@ mod mesh;
 mod pipeline;
 mod render;
 mod renderer;
+mod renderer_options;
 mod shader;
 mod surface;
 mod texture;
@@ -23,6 +25,7 @@ pub use mesh::*;
 pub use pipeline::*;
 pub use render::*;
 pub use renderer::*;
+pub use renderer_options::*;
 pub use shader::*;
 pub use surface::*;
 pub use texture::*;
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use crate::VertexAttributeDescriptor;
+
+/// Prefixes commonly used to mark shader inputs, e.g. `i_position` or `a_normal`
+const INPUT_PREFIXES: [&str; 5] = ["attr_", "in_", "i_", "a_", "v_"];
+
+/// Matches the attributes of a mesh to the reflected inputs of a vertex shader.
+///
+/// By default shader inputs are matched by naming conventions, e.g. `i_position`, `a_pos` or
+/// `inPosition` map to the `positions` attribute. Prefixes & underscores are ignored.
+/// Explicit locations take precedence over names.
+///
+/// Example:
+/// let mapping = AttributeMapping::default()
+///     .with_alias("texcoords", "st")
+///     .with_location(3, "colors");
+/// let vertices = mesh.map_attributes(&vertex_shader.layout().inputs(), &mapping)?;
+///
+#[derive(Debug, Clone)]
+pub struct AttributeMapping {
+    /// Shader input names of each mesh attribute, without prefixes
+    aliases: HashMap<String, Vec<String>>,
+    /// Mesh attribute names of explicitly mapped shader locations
+    locations: HashMap<u32, String>,
+}
+
+impl Default for AttributeMapping {
+    fn default() -> Self {
+        Self::new()
+            .with_aliases("positions", &["position", "pos", "vertex"])
+            .with_aliases("normals", &["normal", "norm"])
+            .with_aliases("texcoords", &["texcoord", "texcoord0", "uv", "uv0", "texture"])
+            .with_aliases("tangents", &["tangent"])
+            .with_aliases("colors", &["color", "colour"])
+    }
+}
+
+impl AttributeMapping {
+    /// Creates a mapping without naming conventions, shader inputs have to match the attribute names
+    pub fn new() -> Self {
+        Self {
+            aliases: HashMap::new(),
+            locations: HashMap::new(),
+        }
+    }
+
+    /// Matches shader inputs with the given name to the mesh attribute
+    pub fn with_alias(mut self, attribute: &str, input_name: &str) -> Self {
+        self.aliases
+            .entry(attribute.to_string())
+            .or_default()
+            .push(semantic_name(input_name));
+        self
+    }
+
+    /// Matches shader inputs with any of the given names to the mesh attribute
+    pub fn with_aliases(self, attribute: &str, input_names: &[&str]) -> Self {
+        input_names
+            .iter()
+            .fold(self, |mapping, input_name| mapping.with_alias(attribute, input_name))
+    }
+
+    /// Maps the shader input at the given location to the mesh attribute, regardless of its name
+    pub fn with_location(mut self, location: u32, attribute: &str) -> Self {
+        self.locations.insert(location, attribute.to_string());
+        self
+    }
+
+    /// Returns true if the mesh attribute should be bound to the given shader input
+    pub fn matches(&self, attribute: &str, input: &VertexAttributeDescriptor) -> bool {
+        if let Some(mapped) = self.locations.get(&input.location) {
+            return mapped == attribute;
+        }
+
+        let input_name = semantic_name(&input.name);
+        input_name == semantic_name(attribute)
+            || input_name == semantic_name(attribute.trim_end_matches('s'))
+            || matches!(self.aliases.get(attribute), Some(aliases) if aliases.contains(&input_name))
+    }
+}
+
+/// Returns the name without common prefixes, underscores & case, e.g. `i_tex_coord` becomes `texcoord`
+fn semantic_name(name: &str) -> String {
+    let lower = name.to_lowercase();
+    let stripped = INPUT_PREFIXES
+        .iter()
+        .find_map(|prefix| lower.strip_prefix(prefix))
+        .unwrap_or_else(|| strip_camel_case_prefix(name, &lower));
+
+    stripped.replace('_', "")
+}
+
+/// Strips camel case prefixes like `inPosition` or `aNormal`
+fn strip_camel_case_prefix<'a>(name: &str, lower: &'a str) -> &'a str {
+    for prefix in ["in", "a", "i", "v"].iter() {
+        let rest = &name[prefix.len().min(name.len())..];
+        if name.starts_with(prefix) && rest.starts_with(|c: char| c.is_ascii_uppercase()) {
+            return &lower[prefix.len()..];
+        }
+    }
+    lower
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AttributeMapping, VertexAttributeDescriptor, VertexFormat};
+
+    fn input(name: &str, location: u32) -> VertexAttributeDescriptor {
+        VertexAttributeDescriptor {
+            name: name.to_string(),
+            offset: 0,
+            location,
+            format: VertexFormat::Float3,
+        }
+    }
+
+    #[test]
+    fn it_matches_attributes_by_naming_conventions() {
+        let mapping = AttributeMapping::default();
+
+        assert!(mapping.matches("positions", &input("i_position", 0)));
+        assert!(mapping.matches("positions", &input("inPosition", 0)));
+        assert!(mapping.matches("positions", &input("a_pos", 0)));
+        assert!(mapping.matches("normals", &input("in_normal", 1)));
+        assert!(mapping.matches("texcoords", &input("i_tex_coord", 2)));
+        assert!(mapping.matches("texcoords", &input("vUv", 2)));
+        assert!(!mapping.matches("normals", &input("i_position", 0)));
+        assert!(!mapping.matches("texcoords", &input("index", 0)));
+
+        let mapping = AttributeMapping::new().with_location(2, "texcoords");
+        assert!(mapping.matches("texcoords", &input("i_st", 2)));
+        assert!(!mapping.matches("texcoords", &input("i_uv", 3)));
+        assert!(mapping.matches("positions", &input("position", 0)));
+        assert!(!mapping.matches("positions", &input("pos", 0)));
+    }
+}
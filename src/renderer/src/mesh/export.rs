@@ -0,0 +1,379 @@
+use std::{fs::File, io::{BufWriter, Write}, path::Path};
+
+use crate::{Mesh, MeshError, VertexAttribute, VertexAttributeValues, VertexFormat};
+
+use super::normals::{face_normal, unit_normal};
+
+/// The encoding of an exported PLY file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+}
+
+/// The vertex data & triangles of a mesh in the layout of the export formats
+struct ExportMesh<'a> {
+    positions: &'a [[f32; 3]],
+    normals: Option<&'a [[f32; 3]]>,
+    /// Texcoords with their origin at the bottom left
+    texcoords: Option<Vec<[f32; 2]>>,
+    colors: Option<Vec<[f32; 4]>>,
+    triangles: Vec<[u32; 3]>,
+}
+
+impl<'a> ExportMesh<'a> {
+    /// Returns positions, normals & texcoords of the vertex
+    fn vertex_floats(&self, vertex: usize) -> Vec<f32> {
+        let mut floats = self.positions[vertex].to_vec();
+        if let Some(normals) = self.normals {
+            floats.extend_from_slice(&normals[vertex]);
+        }
+        if let Some(texcoords) = &self.texcoords {
+            floats.extend_from_slice(&texcoords[vertex]);
+        }
+        floats
+    }
+
+    /// Returns the color of the vertex with 8 bits per channel
+    fn vertex_color(&self, vertex: usize) -> Option<[u8; 4]> {
+        let to_u8 = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        self.colors
+            .as_ref()
+            .map(|colors| colors[vertex])
+            .map(|[r, g, b, a]| [to_u8(r), to_u8(g), to_u8(b), to_u8(a)])
+    }
+}
+
+impl Mesh {
+    /// Writes the mesh into a file, the format is chosen by the extension `obj`, `ply` or `stl`.
+    ///
+    /// PLY files are written in binary format.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MeshError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str()).map(str::to_lowercase);
+
+        match extension.as_deref() {
+            Some("obj") => self.write_obj(BufWriter::new(File::create(path)?)),
+            Some("ply") => self.write_ply(BufWriter::new(File::create(path)?), PlyFormat::BinaryLittleEndian),
+            Some("stl") => self.write_stl(BufWriter::new(File::create(path)?)),
+            _ => Err(MeshError::UnsupportedFileFormat(path.display().to_string())),
+        }
+    }
+
+    /// Writes positions, normals, texcoords & vertex colors as Wavefront OBJ.
+    ///
+    /// Vertex colors are written after the positions, their alpha channel is dropped.
+    pub fn write_obj<W: Write>(&self, mut writer: W) -> Result<(), MeshError> {
+        let export = self.export_mesh()?;
+
+        for (vertex, [x, y, z]) in export.positions.iter().enumerate() {
+            match &export.colors {
+                Some(colors) => {
+                    let [r, g, b, _] = colors[vertex];
+                    writeln!(writer, "v {} {} {} {} {} {}", x, y, z, r, g, b)?;
+                }
+                None => writeln!(writer, "v {} {} {}", x, y, z)?,
+            }
+        }
+        for [u, v] in export.texcoords.iter().flatten() {
+            writeln!(writer, "vt {} {}", u, v)?;
+        }
+        for [x, y, z] in export.normals.iter().flat_map(|normals| normals.iter()) {
+            writeln!(writer, "vn {} {} {}", x, y, z)?;
+        }
+
+        // all attributes share the index of the vertex
+        let corner = |index: u32| {
+            let index = index + 1;
+            match (export.texcoords.is_some(), export.normals.is_some()) {
+                (false, false) => format!("{}", index),
+                (true, false) => format!("{}/{}", index, index),
+                (false, true) => format!("{}//{}", index, index),
+                (true, true) => format!("{}/{}/{}", index, index, index),
+            }
+        };
+        for [a, b, c] in export.triangles.iter() {
+            writeln!(writer, "f {} {} {}", corner(*a), corner(*b), corner(*c))?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes positions, normals, texcoords & vertex colors as PLY, colors are stored as 8 bit RGBA
+    pub fn write_ply<W: Write>(&self, mut writer: W, format: PlyFormat) -> Result<(), MeshError> {
+        let export = self.export_mesh()?;
+
+        let mut header = vec![
+            String::from("ply"),
+            match format {
+                PlyFormat::Ascii => String::from("format ascii 1.0"),
+                PlyFormat::BinaryLittleEndian => String::from("format binary_little_endian 1.0"),
+            },
+            format!("element vertex {}", export.positions.len()),
+        ];
+        let mut properties = vec!["x", "y", "z"];
+        if export.normals.is_some() {
+            properties.extend_from_slice(&["nx", "ny", "nz"]);
+        }
+        if export.texcoords.is_some() {
+            properties.extend_from_slice(&["s", "t"]);
+        }
+        header.extend(properties.iter().map(|property| format!("property float {}", property)));
+        if export.colors.is_some() {
+            header.extend(["red", "green", "blue", "alpha"].iter().map(|property| format!("property uchar {}", property)));
+        }
+        header.push(format!("element face {}", export.triangles.len()));
+        header.push(String::from("property list uchar uint vertex_indices"));
+        header.push(String::from("end_header"));
+        writeln!(writer, "{}", header.join("\n"))?;
+
+        for vertex in 0..export.positions.len() {
+            let floats = export.vertex_floats(vertex);
+            let color = export.vertex_color(vertex);
+
+            match format {
+                PlyFormat::Ascii => {
+                    let values = floats
+                        .iter()
+                        .map(|value| value.to_string())
+                        .chain(color.iter().flatten().map(|value| value.to_string()))
+                        .collect::<Vec<String>>();
+                    writeln!(writer, "{}", values.join(" "))?;
+                }
+                PlyFormat::BinaryLittleEndian => {
+                    for value in floats {
+                        writer.write_all(&value.to_le_bytes())?;
+                    }
+                    if let Some(color) = color {
+                        writer.write_all(&color)?;
+                    }
+                }
+            }
+        }
+
+        for [a, b, c] in export.triangles.iter() {
+            match format {
+                PlyFormat::Ascii => writeln!(writer, "3 {} {} {}", a, b, c)?,
+                PlyFormat::BinaryLittleEndian => {
+                    writer.write_all(&[3])?;
+                    for index in [a, b, c].iter() {
+                        writer.write_all(&index.to_le_bytes())?;
+                    }
+                }
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the triangles as binary STL, the normals are computed from the triangles
+    pub fn write_stl<W: Write>(&self, mut writer: W) -> Result<(), MeshError> {
+        let export = self.export_mesh()?;
+
+        // the header must not start with `solid`, which marks ASCII files
+        let mut header = [0u8; 80];
+        let title = b"Binary STL exported by noire";
+        header[..title.len()].copy_from_slice(title);
+        writer.write_all(&header)?;
+        writer.write_all(&(export.triangles.len() as u32).to_le_bytes())?;
+
+        for triangle in export.triangles.iter() {
+            let normal = unit_normal(face_normal(export.positions, triangle)).map_or([0.0; 3], |normal| normal.into());
+            let vertices = triangle.iter().map(|&index| export.positions[index as usize]);
+
+            for value in normal.iter().chain(vertices.flatten().collect::<Vec<f32>>().iter()) {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+            // attribute byte count
+            writer.write_all(&[0, 0])?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Collects the attributes supported by the export formats, fails for other topologies than triangle lists
+    fn export_mesh(&self) -> Result<ExportMesh<'_>, MeshError> {
+        let triangles = self
+            .triangle_indices()?
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect();
+
+        let normals = match self.attribute("normals") {
+            Some(_) => Some(self.float3_values("normals")?),
+            None => None,
+        };
+        let texcoords = match self.attribute("texcoords") {
+            Some(_) => Some(self.float2_values("texcoords")?.iter().map(|[u, v]| [*u, 1.0 - v]).collect()),
+            None => None,
+        };
+        let colors = match self.attribute("colors") {
+            Some(colors) => Some(float_colors(colors)?),
+            None => None,
+        };
+
+        Ok(ExportMesh {
+            positions: self.float3_values("positions")?,
+            normals,
+            texcoords,
+            colors,
+            triangles,
+        })
+    }
+}
+
+/// Converts colors to RGBA floats, RGB colors are opaque
+fn float_colors(attribute: &VertexAttribute) -> Result<Vec<[f32; 4]>, MeshError> {
+    let unorm8 = |value: u8| value as f32 / u8::MAX as f32;
+    let unorm16 = |value: u16| value as f32 / u16::MAX as f32;
+
+    match &attribute.values {
+        VertexAttributeValues::Float3(colors) => Ok(colors.iter().map(|[r, g, b]| [*r, *g, *b, 1.0]).collect()),
+        VertexAttributeValues::Float4(colors) => Ok(colors.clone()),
+        VertexAttributeValues::Uchar4Norm(colors) => {
+            Ok(colors.iter().map(|[r, g, b, a]| [unorm8(*r), unorm8(*g), unorm8(*b), unorm8(*a)]).collect())
+        }
+        VertexAttributeValues::Ushort4Norm(colors) => {
+            Ok(colors.iter().map(|[r, g, b, a]| [unorm16(*r), unorm16(*g), unorm16(*b), unorm16(*a)]).collect())
+        }
+        values => Err(MeshError::FormatMismatch(attribute.name.to_string(), values.into(), VertexFormat::Float4)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Indices, Mesh, MeshError, ObjModel, PlyFormat, PrimitiveTopology, VertexAttribute, VertexAttributeValues, shape};
+
+    /// A single triangle with vertex colors
+    fn triangle() -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.attributes.push(VertexAttribute::positions(vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]));
+        mesh.attributes.push(VertexAttribute::new("colors", vec![[255u8, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 51]]));
+        mesh.indices = Some(Indices::U16(vec![0, 1, 2]));
+        mesh
+    }
+
+    /// Returns the values of all triangle corners, independent of the vertex order
+    fn corners(mesh: &Mesh, name: &str) -> Vec<Vec<f32>> {
+        let indices = match &mesh.indices {
+            Some(indices) => indices.to_u32(),
+            None => panic!("Mesh has no indices"),
+        };
+        let values = match &mesh.attribute(name).unwrap().values {
+            VertexAttributeValues::Float2(values) => values.iter().map(|value| value.to_vec()).collect::<Vec<Vec<f32>>>(),
+            VertexAttributeValues::Float3(values) => values.iter().map(|value| value.to_vec()).collect(),
+            VertexAttributeValues::Float4(values) => values.iter().map(|value| value.to_vec()).collect(),
+            values => panic!("Unexpected values {:?}", values),
+        };
+        indices.iter().map(|&index| values[index as usize].clone()).collect()
+    }
+
+    fn assert_near(expected: &[Vec<f32>], actual: &[Vec<f32>]) {
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual.iter()) {
+            for (expected, actual) in expected.iter().zip(actual.iter()) {
+                assert!((expected - actual).abs() < 1e-6, "Expected {:?}, got {:?}", expected, actual);
+            }
+        }
+    }
+
+    #[test]
+    fn it_round_trips_obj() {
+        let sphere: Mesh = shape::UvSphere::new(1.0).with_resolution(8, 4).into();
+        let mut data = Vec::new();
+        sphere.write_obj(&mut data).unwrap();
+
+        let model = ObjModel::parse(&String::from_utf8(data).unwrap(), "sphere.obj", |_| Ok(Vec::new())).unwrap();
+        assert_eq!(1, model.meshes.len());
+
+        let mesh = &model.meshes[0].mesh;
+        assert_eq!(sphere.indices.as_ref().unwrap().len(), mesh.indices.as_ref().unwrap().len());
+        for name in ["positions", "normals", "texcoords"].iter() {
+            assert_near(&corners(&sphere, name), &corners(mesh, name));
+        }
+
+        // vertex colors lose their alpha channel
+        let mut data = Vec::new();
+        triangle().write_obj(&mut data).unwrap();
+        let source = String::from_utf8(data).unwrap();
+        assert_eq!("v 0 0 0 1 0 0\nv 1 0 0 0 1 0\nv 0 1 0 0 0 1\nf 1 2 3\n", source);
+
+        let model = ObjModel::parse(&source, "triangle.obj", |_| Ok(Vec::new())).unwrap();
+        assert_eq!(
+            VertexAttributeValues::Float4(vec![[1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]]),
+            model.meshes[0].mesh.attribute("colors").unwrap().values,
+        );
+    }
+
+    #[test]
+    fn it_writes_ascii_and_binary_ply() {
+        let mut data = Vec::new();
+        triangle().write_ply(&mut data, PlyFormat::Ascii).unwrap();
+
+        let expected = [
+            "ply",
+            "format ascii 1.0",
+            "element vertex 3",
+            "property float x",
+            "property float y",
+            "property float z",
+            "property uchar red",
+            "property uchar green",
+            "property uchar blue",
+            "property uchar alpha",
+            "element face 1",
+            "property list uchar uint vertex_indices",
+            "end_header",
+            "0 0 0 255 0 0 255",
+            "1 0 0 0 255 0 255",
+            "0 1 0 0 0 255 51",
+            "3 0 1 2",
+            "",
+        ];
+        assert_eq!(expected.join("\n"), String::from_utf8(data).unwrap());
+
+        let cube: Mesh = shape::Cube::new(1.0).into();
+        let mut data = Vec::new();
+        cube.write_ply(&mut data, PlyFormat::BinaryLittleEndian).unwrap();
+
+        let header = b"end_header\n";
+        let body = data.windows(header.len()).position(|window| window == header).unwrap() + header.len();
+        let source = String::from_utf8(data[..body].to_vec()).unwrap();
+        assert!(source.starts_with("ply\nformat binary_little_endian 1.0\nelement vertex 24\n"));
+        assert!(source.contains("property float nx\n"));
+        assert!(source.contains("property float t\nelement face 12\n"));
+
+        // 8 floats per vertex, the vertex count & 3 indices per face
+        assert_eq!(24 * 8 * 4 + 12 * (1 + 3 * 4), data.len() - body);
+        assert_eq!(&(-0.5f32).to_le_bytes(), &data[body..body + 4]);
+        assert_eq!(3, data[body + 24 * 8 * 4]);
+    }
+
+    #[test]
+    fn it_writes_binary_stl() {
+        let mut data = Vec::new();
+        triangle().write_stl(&mut data).unwrap();
+
+        assert_eq!(80 + 4 + 50, data.len());
+        assert!(!data.starts_with(b"solid"));
+        assert_eq!(&1u32.to_le_bytes(), &data[80..84]);
+
+        let floats = data[84..132]
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect::<Vec<f32>>();
+        assert_eq!(vec![0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0], floats);
+        assert_eq!(&[0, 0], &data[132..134]);
+
+        let mut points = triangle();
+        points.topology = PrimitiveTopology::PointList;
+        assert_eq!(Err(MeshError::UnsupportedTopology(PrimitiveTopology::PointList)), points.write_stl(&mut Vec::new()));
+        assert_eq!(
+            Err(MeshError::UnsupportedFileFormat("mesh.fbx".into())),
+            triangle().save("mesh.fbx"),
+        );
+    }
+}
@@ -0,0 +1,554 @@
+use std::{borrow::Cow, fmt::{self, Display}, path::Path};
+
+use cgmath::Matrix4;
+use gltf::{buffer, image::Format, mesh::{Mode, util::{ReadColors, ReadIndices, ReadJoints, ReadTexCoords, ReadWeights}}, Document};
+use image::RgbaImage;
+
+use crate::{Indices, Mesh, PrimitiveTopology, VertexAttribute, VertexAttributeValues};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GltfError {
+    /// Failed to read the document, its buffers or images, contains the reason
+    ImportFailed(String),
+    /// The primitive mode has no matching topology, contains the mesh index & mode
+    UnsupportedMode(usize, String),
+    /// A primitive has no positions, contains the mesh index
+    MissingPositions(usize),
+}
+
+impl Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            GltfError::ImportFailed(error) => format!("Failed to import glTF: {}", error),
+            GltfError::UnsupportedMode(mesh, mode) => format!("Mesh {} has unsupported primitive mode {}", mesh, mode),
+            GltfError::MissingPositions(mesh) => format!("Mesh {} has a primitive without positions", mesh),
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl From<gltf::Error> for GltfError {
+    fn from(error: gltf::Error) -> Self {
+        GltfError::ImportFailed(error.to_string())
+    }
+}
+
+/// A texture referenced by a material
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GltfTexture {
+    /// Index of the image in `GltfScene::images`
+    pub image: usize,
+    /// The texcoord set, `0` refers to the `texcoords` attribute, `1` to `texcoords1`
+    pub texcoord: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GltfAlphaMode {
+    /// The alpha value is ignored
+    Opaque,
+    /// Fragments with alpha below the cutoff are discarded
+    Mask,
+    /// Alpha blended
+    Blend,
+}
+
+/// The parameters of a metallic roughness PBR material
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    pub base_color_factor: [f32; 4],
+    pub base_color_texture: Option<GltfTexture>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    /// Metalness in the blue, roughness in the green channel
+    pub metallic_roughness_texture: Option<GltfTexture>,
+    pub normal_texture: Option<GltfTexture>,
+    pub normal_scale: f32,
+    pub occlusion_texture: Option<GltfTexture>,
+    pub occlusion_strength: f32,
+    pub emissive_factor: [f32; 3],
+    pub emissive_texture: Option<GltfTexture>,
+    pub alpha_mode: GltfAlphaMode,
+    pub alpha_cutoff: f32,
+    pub double_sided: bool,
+}
+
+/// A decoded image, all pixel formats are converted to 8 bit RGBA
+#[derive(Debug, Clone)]
+pub struct GltfImage {
+    pub name: Option<String>,
+    pub image: RgbaImage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GltfProjection {
+    /// A perspective projection, an infinite projection if `zfar` is not set
+    Perspective {
+        aspect_ratio: Option<f32>,
+        /// Vertical field of view in radians
+        yfov: f32,
+        znear: f32,
+        zfar: Option<f32>,
+    },
+    /// An orthographic projection, `xmag` & `ymag` are half the width & height of the view
+    Orthographic {
+        xmag: f32,
+        ymag: f32,
+        znear: f32,
+        zfar: f32,
+    },
+}
+
+/// A camera looking along the negative Z axis of its node
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfCamera {
+    pub name: Option<String>,
+    pub projection: GltfProjection,
+}
+
+/// A primitive of a glTF mesh, each primitive has its own material
+#[derive(Debug)]
+pub struct GltfPrimitive {
+    pub mesh: Mesh,
+    /// Index of the material in `GltfScene::materials`, `None` uses the default material
+    pub material: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+/// A node of the scene hierarchy
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    /// The transform relative to the parent node
+    pub transform: Matrix4<f32>,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    /// Index of the mesh in `GltfScene::meshes`
+    pub mesh: Option<usize>,
+    /// Index of the camera in `GltfScene::cameras`
+    pub camera: Option<usize>,
+}
+
+/// The content of a glTF 2.0 file, `.gltf` with external or embedded resources, or binary `.glb`.
+///
+/// Meshes keep the component types of their accessors, e.g. normalized `u8` colors become
+/// `Uchar4Norm`. Attributes are named `positions`, `normals`, `tangents`, `texcoords`, `colors`,
+/// `joints` & `weights`, further sets get their index appended, e.g. `texcoords1`.
+/// RGB colors get an opaque alpha channel.
+///
+/// Example:
+/// let scene = GltfScene::load("assets/helmet.glb")?;
+/// for &root in scene.roots.iter() {
+///     let transform = scene.world_transform(root);
+///     ...
+/// }
+///
+#[derive(Debug)]
+pub struct GltfScene {
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    pub images: Vec<GltfImage>,
+    pub cameras: Vec<GltfCamera>,
+    pub nodes: Vec<GltfNode>,
+    /// The root nodes of the default scene
+    pub roots: Vec<usize>,
+}
+
+impl GltfScene {
+    /// Loads the file, external buffers & images are loaded relative to it
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GltfError> {
+        let (document, buffers, images) = gltf::import(path)?;
+        GltfScene::from_document(&document, &buffers, images)
+    }
+
+    /// Imports a binary glTF or a glTF with embedded resources from memory
+    pub fn from_slice(data: &[u8]) -> Result<Self, GltfError> {
+        let (document, buffers, images) = gltf::import_slice(data)?;
+        GltfScene::from_document(&document, &buffers, images)
+    }
+
+    /// Returns the transform of the node relative to the scene
+    pub fn world_transform(&self, node: usize) -> Matrix4<f32> {
+        let node = &self.nodes[node];
+        match node.parent {
+            Some(parent) => self.world_transform(parent) * node.transform,
+            None => node.transform,
+        }
+    }
+
+    fn from_document(
+        document: &Document,
+        buffers: &[buffer::Data],
+        images: Vec<gltf::image::Data>,
+    ) -> Result<Self, GltfError> {
+        let meshes = document
+            .meshes()
+            .map(|mesh| {
+                let primitives = mesh
+                    .primitives()
+                    .map(|primitive| {
+                        Ok(GltfPrimitive {
+                            mesh: primitive_mesh(mesh.index(), &primitive, buffers)?,
+                            material: primitive.material().index(),
+                        })
+                    })
+                    .collect::<Result<Vec<GltfPrimitive>, GltfError>>()?;
+
+                Ok(GltfMesh {
+                    name: mesh.name().map(String::from),
+                    primitives,
+                })
+            })
+            .collect::<Result<Vec<GltfMesh>, GltfError>>()?;
+
+        let images = document
+            .images()
+            .zip(images)
+            .map(|(image, data)| GltfImage {
+                name: image.name().map(String::from),
+                image: rgba_image(data),
+            })
+            .collect();
+
+        let mut nodes = document
+            .nodes()
+            .map(|node| GltfNode {
+                name: node.name().map(String::from),
+                transform: Matrix4::from(node.transform().matrix()),
+                parent: None,
+                children: node.children().map(|child| child.index()).collect(),
+                mesh: node.mesh().map(|mesh| mesh.index()),
+                camera: node.camera().map(|camera| camera.index()),
+            })
+            .collect::<Vec<GltfNode>>();
+
+        for parent in 0..nodes.len() {
+            for child in nodes[parent].children.clone() {
+                nodes[child].parent = Some(parent);
+            }
+        }
+
+        // files without scenes show all nodes
+        let roots = match document.default_scene().or_else(|| document.scenes().next()) {
+            Some(scene) => scene.nodes().map(|node| node.index()).collect(),
+            None => (0..nodes.len()).filter(|&node| nodes[node].parent.is_none()).collect(),
+        };
+
+        Ok(GltfScene {
+            meshes,
+            materials: document.materials().map(material).collect(),
+            images,
+            cameras: document.cameras().map(camera).collect(),
+            nodes,
+            roots,
+        })
+    }
+}
+
+/// Reads all attributes & indices of the primitive
+fn primitive_mesh(
+    mesh_index: usize,
+    primitive: &gltf::Primitive,
+    buffers: &[buffer::Data],
+) -> Result<Mesh, GltfError> {
+    let topology = match primitive.mode() {
+        Mode::Points => PrimitiveTopology::PointList,
+        Mode::Lines => PrimitiveTopology::LineList,
+        Mode::LineStrip => PrimitiveTopology::LineStrip,
+        Mode::Triangles => PrimitiveTopology::TriangleList,
+        Mode::TriangleStrip => PrimitiveTopology::TriangleStrip,
+        mode => return Err(GltfError::UnsupportedMode(mesh_index, format!("{:?}", mode))),
+    };
+
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    let positions = reader.read_positions().ok_or(GltfError::MissingPositions(mesh_index))?;
+
+    let mut mesh = Mesh::new(topology);
+    mesh.attributes.push(VertexAttribute::positions(positions.collect()));
+    if let Some(normals) = reader.read_normals() {
+        mesh.attributes.push(VertexAttribute::normals(normals.collect()));
+    }
+    if let Some(tangents) = reader.read_tangents() {
+        mesh.attributes.push(VertexAttribute::tangents(tangents.collect()));
+    }
+
+    let mut set = 0;
+    while let Some(texcoords) = reader.read_tex_coords(set) {
+        let values = match texcoords {
+            ReadTexCoords::U8(values) => VertexAttributeValues::Uchar2Norm(values.collect()),
+            ReadTexCoords::U16(values) => VertexAttributeValues::Ushort2Norm(values.collect()),
+            ReadTexCoords::F32(values) => VertexAttributeValues::Float2(values.collect()),
+        };
+        mesh.attributes.push(indexed_attribute("texcoords", set, values));
+        set += 1;
+    }
+
+    let mut set = 0;
+    while let Some(colors) = reader.read_colors(set) {
+        let values = match colors {
+            ReadColors::RgbU8(values) => VertexAttributeValues::Uchar4Norm(values.map(|[r, g, b]| [r, g, b, u8::MAX]).collect()),
+            ReadColors::RgbU16(values) => VertexAttributeValues::Ushort4Norm(values.map(|[r, g, b]| [r, g, b, u16::MAX]).collect()),
+            ReadColors::RgbF32(values) => VertexAttributeValues::Float4(values.map(|[r, g, b]| [r, g, b, 1.0]).collect()),
+            ReadColors::RgbaU8(values) => VertexAttributeValues::Uchar4Norm(values.collect()),
+            ReadColors::RgbaU16(values) => VertexAttributeValues::Ushort4Norm(values.collect()),
+            ReadColors::RgbaF32(values) => VertexAttributeValues::Float4(values.collect()),
+        };
+        mesh.attributes.push(indexed_attribute("colors", set, values));
+        set += 1;
+    }
+
+    let mut set = 0;
+    while let Some(joints) = reader.read_joints(set) {
+        let values = match joints {
+            ReadJoints::U8(values) => VertexAttributeValues::Uchar4(values.collect()),
+            ReadJoints::U16(values) => VertexAttributeValues::Ushort4(values.collect()),
+        };
+        mesh.attributes.push(indexed_attribute("joints", set, values));
+        set += 1;
+    }
+
+    let mut set = 0;
+    while let Some(weights) = reader.read_weights(set) {
+        let values = match weights {
+            ReadWeights::U8(values) => VertexAttributeValues::Uchar4Norm(values.collect()),
+            ReadWeights::U16(values) => VertexAttributeValues::Ushort4Norm(values.collect()),
+            ReadWeights::F32(values) => VertexAttributeValues::Float4(values.collect()),
+        };
+        mesh.attributes.push(indexed_attribute("weights", set, values));
+        set += 1;
+    }
+
+    mesh.indices = reader.read_indices().map(|indices| match indices {
+        ReadIndices::U8(indices) => Indices::U16(indices.map(u16::from).collect()),
+        ReadIndices::U16(indices) => Indices::U16(indices.collect()),
+        ReadIndices::U32(indices) => Indices::U32(indices.collect()),
+    });
+
+    Ok(mesh)
+}
+
+/// Creates an attribute named after its set, the first set keeps the plain name
+fn indexed_attribute(name: &'static str, set: u32, values: VertexAttributeValues) -> VertexAttribute {
+    let name: Cow<'static, str> = match set {
+        0 => name.into(),
+        set => format!("{}{}", name, set).into(),
+    };
+    VertexAttribute { name, values }
+}
+
+fn texture(texture: gltf::Texture, texcoord: u32) -> GltfTexture {
+    GltfTexture {
+        image: texture.source().index(),
+        texcoord,
+    }
+}
+
+fn material(material: gltf::Material) -> GltfMaterial {
+    let pbr = material.pbr_metallic_roughness();
+    let normal_texture = material.normal_texture();
+    let occlusion_texture = material.occlusion_texture();
+
+    GltfMaterial {
+        name: material.name().map(String::from),
+        base_color_factor: pbr.base_color_factor(),
+        base_color_texture: pbr.base_color_texture().map(|info| texture(info.texture(), info.tex_coord())),
+        metallic_factor: pbr.metallic_factor(),
+        roughness_factor: pbr.roughness_factor(),
+        metallic_roughness_texture: pbr.metallic_roughness_texture().map(|info| texture(info.texture(), info.tex_coord())),
+        normal_texture: normal_texture.as_ref().map(|normal| texture(normal.texture(), normal.tex_coord())),
+        normal_scale: normal_texture.as_ref().map_or(1.0, |normal| normal.scale()),
+        occlusion_texture: occlusion_texture.as_ref().map(|occlusion| texture(occlusion.texture(), occlusion.tex_coord())),
+        occlusion_strength: occlusion_texture.as_ref().map_or(1.0, |occlusion| occlusion.strength()),
+        emissive_factor: material.emissive_factor(),
+        emissive_texture: material.emissive_texture().map(|info| texture(info.texture(), info.tex_coord())),
+        alpha_mode: match material.alpha_mode() {
+            gltf::material::AlphaMode::Opaque => GltfAlphaMode::Opaque,
+            gltf::material::AlphaMode::Mask => GltfAlphaMode::Mask,
+            gltf::material::AlphaMode::Blend => GltfAlphaMode::Blend,
+        },
+        alpha_cutoff: material.alpha_cutoff(),
+        double_sided: material.double_sided(),
+    }
+}
+
+fn camera(camera: gltf::Camera) -> GltfCamera {
+    let projection = match camera.projection() {
+        gltf::camera::Projection::Perspective(perspective) => GltfProjection::Perspective {
+            aspect_ratio: perspective.aspect_ratio(),
+            yfov: perspective.yfov(),
+            znear: perspective.znear(),
+            zfar: perspective.zfar(),
+        },
+        gltf::camera::Projection::Orthographic(orthographic) => GltfProjection::Orthographic {
+            xmag: orthographic.xmag(),
+            ymag: orthographic.ymag(),
+            znear: orthographic.znear(),
+            zfar: orthographic.zfar(),
+        },
+    };
+
+    GltfCamera {
+        name: camera.name().map(String::from),
+        projection,
+    }
+}
+
+/// Converts the decoded pixels to 8 bit RGBA, 16 bit channels keep their most significant byte
+fn rgba_image(data: gltf::image::Data) -> RgbaImage {
+    let (channels, channel_size) = match data.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 | Format::B8G8R8 => (3, 1),
+        Format::R8G8B8A8 | Format::B8G8R8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+    };
+    let bgr = matches!(data.format, Format::B8G8R8 | Format::B8G8R8A8);
+
+    let mut pixels = Vec::with_capacity((data.width * data.height * 4) as usize);
+    for pixel in data.pixels.chunks_exact(channels * channel_size) {
+        let channel = |index: usize| pixel[index * channel_size + channel_size - 1];
+        let rgba = match channels {
+            1 => [channel(0), channel(0), channel(0), u8::MAX],
+            2 => [channel(0), channel(0), channel(0), channel(1)],
+            3 => [channel(0), channel(1), channel(2), u8::MAX],
+            _ => [channel(0), channel(1), channel(2), channel(3)],
+        };
+        let rgba = if bgr { [rgba[2], rgba[1], rgba[0], rgba[3]] } else { rgba };
+        pixels.extend_from_slice(&rgba);
+    }
+
+    RgbaImage::from_raw(data.width, data.height, pixels).expect("Image size does not match its pixels")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use cgmath::{InnerSpace, Matrix4, Vector4};
+
+    use crate::{GltfAlphaMode, GltfProjection, GltfScene, GltfTexture, Indices, PrimitiveTopology, VertexAttributeValues, VertexFormat};
+
+    fn asset(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/gltf").join(name)
+    }
+
+    fn assert_near(expected: Vector4<f32>, actual: Vector4<f32>) {
+        assert!((expected - actual).magnitude() < 1e-5, "Expected {:?}, got {:?}", expected, actual);
+    }
+
+    #[test]
+    fn it_imports_gltf_with_external_resources() {
+        let scene = GltfScene::load(asset("triangle.gltf")).unwrap();
+
+        assert_eq!(1, scene.meshes.len());
+        let primitive = &scene.meshes[0].primitives[0];
+        assert_eq!(Some(0), primitive.material);
+        assert_eq!(PrimitiveTopology::TriangleList, primitive.mesh.topology);
+        assert_eq!(3, primitive.mesh.vertex_count());
+        assert!(matches!(&primitive.mesh.indices, Some(Indices::U16(indices)) if indices == &vec![0, 1, 2]));
+
+        let formats = primitive.mesh.attributes
+            .iter()
+            .map(|attribute| (attribute.name.to_string(), VertexFormat::from(attribute)))
+            .collect::<Vec<(String, VertexFormat)>>();
+        assert_eq!(
+            vec![
+                ("positions".to_string(), VertexFormat::Float3),
+                ("normals".to_string(), VertexFormat::Float3),
+                ("texcoords".to_string(), VertexFormat::Float2),
+                ("colors".to_string(), VertexFormat::Uchar4Norm),
+            ],
+            formats,
+        );
+        assert_eq!(
+            VertexAttributeValues::Uchar4Norm(vec![[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 128]]),
+            primitive.mesh.attribute("colors").unwrap().values,
+        );
+
+        let material = &scene.materials[0];
+        assert_eq!(Some("Checker".to_string()), material.name);
+        assert_eq!([1.0, 0.5, 0.25, 1.0], material.base_color_factor);
+        assert_eq!(Some(GltfTexture { image: 0, texcoord: 0 }), material.base_color_texture);
+        assert_eq!((0.25, 0.75), (material.metallic_factor, material.roughness_factor));
+        assert_eq!(0.5, material.normal_scale);
+        assert_eq!(None, material.occlusion_texture);
+        assert_eq!([0.1, 0.2, 0.3], material.emissive_factor);
+        assert_eq!((GltfAlphaMode::Mask, 0.25), (material.alpha_mode, material.alpha_cutoff));
+        assert!(material.double_sided);
+
+        let image = &scene.images[0].image;
+        assert_eq!((2, 2), image.dimensions());
+        assert_eq!(&[255, 255, 255, 255], &image.get_pixel(0, 0).0);
+        assert_eq!(&[0, 0, 0, 255], &image.get_pixel(1, 0).0);
+
+        assert_eq!(
+            GltfProjection::Perspective { aspect_ratio: Some(1.5), yfov: 0.8, znear: 0.1, zfar: None },
+            scene.cameras[0].projection,
+        );
+        assert_eq!(
+            GltfProjection::Orthographic { xmag: 2.0, ymag: 1.0, znear: 0.5, zfar: 10.0 },
+            scene.cameras[1].projection,
+        );
+    }
+
+    #[test]
+    fn it_resolves_the_node_hierarchy() {
+        let scene = GltfScene::load(asset("triangle.gltf")).unwrap();
+
+        assert_eq!(vec![0], scene.roots);
+        assert_eq!(vec![1, 2], scene.nodes[0].children);
+        assert_eq!(Some(0), scene.nodes[1].parent);
+        assert_eq!(Some(0), scene.nodes[1].mesh);
+        assert_eq!(Some(0), scene.nodes[2].camera);
+        assert_eq!(Matrix4::from_translation([1.0, 2.0, 3.0].into()), scene.world_transform(0));
+
+        // the triangle is scaled by 2 & rotated by 90 degrees around Y, then moved by its parent
+        let transform = scene.world_transform(1);
+        assert_near(Vector4::new(1.0, 2.0, 1.0, 1.0), transform * Vector4::new(1.0, 0.0, 0.0, 1.0));
+        assert_near(Vector4::new(1.0, 4.0, 3.0, 1.0), transform * Vector4::new(0.0, 1.0, 0.0, 1.0));
+        assert_near(Vector4::new(1.0, 2.0, 8.0, 1.0), scene.world_transform(2) * Vector4::new(0.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn it_imports_binary_gltf() {
+        let data = std::fs::read(asset("quad.glb")).unwrap();
+        let scene = GltfScene::from_slice(&data).unwrap();
+
+        assert_eq!(vec![0], scene.roots);
+        assert_near(Vector4::new(4.0, 5.0, 6.0, 1.0), scene.world_transform(0) * Vector4::new(0.0, 0.0, 0.0, 1.0));
+
+        let primitives = &scene.meshes[0].primitives;
+        assert_eq!(2, primitives.len());
+        assert_eq!(None, primitives[0].material);
+
+        let mesh = &primitives[0].mesh;
+        assert!(matches!(&mesh.indices, Some(Indices::U32(indices)) if indices.len() == 6));
+        assert_eq!(
+            VertexAttributeValues::Ushort2Norm(vec![[0, 65535], [65535, 65535], [65535, 0], [0, 0]]),
+            mesh.attribute("texcoords").unwrap().values,
+        );
+        // RGB colors get an opaque alpha channel
+        assert_eq!(
+            VertexAttributeValues::Float4(vec![[1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0], [1.0, 1.0, 1.0, 1.0]]),
+            mesh.attribute("colors").unwrap().values,
+        );
+        assert_eq!(VertexFormat::Float4, mesh.attribute("tangents").unwrap().into());
+        assert_eq!(VertexFormat::Uchar4, mesh.attribute("joints").unwrap().into());
+        assert_eq!(VertexFormat::Uchar4Norm, mesh.attribute("weights").unwrap().into());
+
+        // u8 indices are widened to u16
+        let lines = &primitives[1].mesh;
+        assert_eq!(PrimitiveTopology::LineList, lines.topology);
+        assert!(matches!(&lines.indices, Some(Indices::U16(indices)) if indices == &vec![0, 1, 1, 2, 2, 3, 3, 0]));
+
+        assert_eq!(&[0, 128, 255, 255], &scene.images[0].image.get_pixel(0, 0).0);
+    }
+}
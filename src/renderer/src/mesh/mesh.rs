@@ -1,13 +1,86 @@
-use std::{borrow::Cow, fmt::Display};
+use std::{borrow::Cow, fmt::{self, Display}};
+
+use crate::{AttributeMapping, InputStepMode, PrimitiveTopology, VertexAttributeDescriptor, VertexBufferDescriptor, VertexFormat};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeshError {
+    /// The shader requires an input that no attribute of the mesh provides, contains name & location
+    MissingAttribute(String, u32),
+    /// The mesh attribute does not have the format of the shader input, contains attribute, mesh & shader format
+    FormatMismatch(String, VertexFormat, VertexFormat),
+    /// The mesh has no attribute with the given name
+    AttributeNotFound(String),
+    /// The operation does not support the topology of the mesh
+    UnsupportedTopology(PrimitiveTopology),
+    /// The tangent space could not be generated, e.g. for meshes without triangles
+    TangentGenerationFailed,
+    /// The file extension does not match a supported export format, contains the path
+    UnsupportedFileFormat(String),
+    /// Failed to write the exported mesh, contains the reason
+    WriteFailed(String),
+}
 
-use crate::{PrimitiveTopology, VertexBufferDescriptor, VertexFormat};
+impl Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            MeshError::MissingAttribute(name, location) => format!(
+                "Mesh has no attribute for shader input '{}' at location {}", name, location,
+            ),
+            MeshError::FormatMismatch(name, format, expected) => format!(
+                "Mesh attribute '{}' has format {:?}, but the shader expects {:?}", name, format, expected,
+            ),
+            MeshError::AttributeNotFound(name) => format!("Mesh has no attribute '{}'", name),
+            MeshError::UnsupportedTopology(topology) => format!("Mesh topology {:?} is not supported", topology),
+            MeshError::TangentGenerationFailed => String::from("Failed to generate tangents"),
+            MeshError::UnsupportedFileFormat(path) => format!("Unsupported mesh file format: {}", path),
+            MeshError::WriteFailed(error) => format!("Failed to write mesh: {}", error),
+        };
+        write!(f, "{}", s)
+    }
+}
 
-#[derive(Debug)]
+impl From<std::io::Error> for MeshError {
+    fn from(error: std::io::Error) -> Self {
+        MeshError::WriteFailed(error.to_string())
+    }
+}
+
+/// The values of a single vertex attribute.
+///
+/// Half floats are stored as their bits, see `VertexAttributeValues::half2`. `Norm` variants are
+/// converted to floats in range `[0, 1]` (unsigned) or `[-1, 1]` (signed) by the GPU.
+#[derive(Debug, Clone, PartialEq)]
 pub enum VertexAttributeValues {
     Float(Vec<f32>),
     Float2(Vec<[f32; 2]>),
     Float3(Vec<[f32; 3]>),
     Float4(Vec<[f32; 4]>),
+    Int(Vec<i32>),
+    Int2(Vec<[i32; 2]>),
+    Int3(Vec<[i32; 3]>),
+    Int4(Vec<[i32; 4]>),
+    Uint(Vec<u32>),
+    Uint2(Vec<[u32; 2]>),
+    Uint3(Vec<[u32; 3]>),
+    Uint4(Vec<[u32; 4]>),
+    Half2(Vec<[u16; 2]>),
+    Half4(Vec<[u16; 4]>),
+    Short2(Vec<[i16; 2]>),
+    Short4(Vec<[i16; 4]>),
+    Short2Norm(Vec<[i16; 2]>),
+    Short4Norm(Vec<[i16; 4]>),
+    Ushort2(Vec<[u16; 2]>),
+    Ushort4(Vec<[u16; 4]>),
+    Ushort2Norm(Vec<[u16; 2]>),
+    Ushort4Norm(Vec<[u16; 4]>),
+    Char2(Vec<[i8; 2]>),
+    Char4(Vec<[i8; 4]>),
+    Char2Norm(Vec<[i8; 2]>),
+    Char4Norm(Vec<[i8; 4]>),
+    Uchar2(Vec<[u8; 2]>),
+    Uchar4(Vec<[u8; 4]>),
+    Uchar2Norm(Vec<[u8; 2]>),
+    Uchar4Norm(Vec<[u8; 4]>),
 }
 
 impl VertexAttributeValues {
@@ -17,6 +90,32 @@ impl VertexAttributeValues {
             VertexAttributeValues::Float2(ref values) => values.len(),
             VertexAttributeValues::Float3(ref values) => values.len(),
             VertexAttributeValues::Float4(ref values) => values.len(),
+            VertexAttributeValues::Int(ref values) => values.len(),
+            VertexAttributeValues::Int2(ref values) => values.len(),
+            VertexAttributeValues::Int3(ref values) => values.len(),
+            VertexAttributeValues::Int4(ref values) => values.len(),
+            VertexAttributeValues::Uint(ref values) => values.len(),
+            VertexAttributeValues::Uint2(ref values) => values.len(),
+            VertexAttributeValues::Uint3(ref values) => values.len(),
+            VertexAttributeValues::Uint4(ref values) => values.len(),
+            VertexAttributeValues::Half2(ref values) => values.len(),
+            VertexAttributeValues::Half4(ref values) => values.len(),
+            VertexAttributeValues::Short2(ref values) => values.len(),
+            VertexAttributeValues::Short4(ref values) => values.len(),
+            VertexAttributeValues::Short2Norm(ref values) => values.len(),
+            VertexAttributeValues::Short4Norm(ref values) => values.len(),
+            VertexAttributeValues::Ushort2(ref values) => values.len(),
+            VertexAttributeValues::Ushort4(ref values) => values.len(),
+            VertexAttributeValues::Ushort2Norm(ref values) => values.len(),
+            VertexAttributeValues::Ushort4Norm(ref values) => values.len(),
+            VertexAttributeValues::Char2(ref values) => values.len(),
+            VertexAttributeValues::Char4(ref values) => values.len(),
+            VertexAttributeValues::Char2Norm(ref values) => values.len(),
+            VertexAttributeValues::Char4Norm(ref values) => values.len(),
+            VertexAttributeValues::Uchar2(ref values) => values.len(),
+            VertexAttributeValues::Uchar4(ref values) => values.len(),
+            VertexAttributeValues::Uchar2Norm(ref values) => values.len(),
+            VertexAttributeValues::Uchar4Norm(ref values) => values.len(),
         }
     }
 
@@ -32,8 +131,123 @@ impl VertexAttributeValues {
             VertexAttributeValues::Float2(values) => bytemuck::cast_slice(values.as_slice()),
             VertexAttributeValues::Float3(values) => bytemuck::cast_slice(values.as_slice()),
             VertexAttributeValues::Float4(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Int(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Int2(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Int3(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Int4(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Uint(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Uint2(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Uint3(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Uint4(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Half2(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Half4(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Short2(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Short4(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Short2Norm(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Short4Norm(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Ushort2(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Ushort4(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Ushort2Norm(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Ushort4Norm(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Char2(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Char4(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Char2Norm(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Char4Norm(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Uchar2(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Uchar4(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Uchar2Norm(values) => bytemuck::cast_slice(values.as_slice()),
+            VertexAttributeValues::Uchar4Norm(values) => bytemuck::cast_slice(values.as_slice()),
         }
     }
+
+    /// Returns the values at the given indices, values may be repeated
+    pub fn gather(&self, indices: &[u32]) -> Self {
+        fn gather<T: Copy>(values: &[T], indices: &[u32]) -> Vec<T> {
+            indices.iter().map(|&index| values[index as usize]).collect()
+        }
+
+        match self {
+            VertexAttributeValues::Float(values) => VertexAttributeValues::Float(gather(values, indices)),
+            VertexAttributeValues::Float2(values) => VertexAttributeValues::Float2(gather(values, indices)),
+            VertexAttributeValues::Float3(values) => VertexAttributeValues::Float3(gather(values, indices)),
+            VertexAttributeValues::Float4(values) => VertexAttributeValues::Float4(gather(values, indices)),
+            VertexAttributeValues::Int(values) => VertexAttributeValues::Int(gather(values, indices)),
+            VertexAttributeValues::Int2(values) => VertexAttributeValues::Int2(gather(values, indices)),
+            VertexAttributeValues::Int3(values) => VertexAttributeValues::Int3(gather(values, indices)),
+            VertexAttributeValues::Int4(values) => VertexAttributeValues::Int4(gather(values, indices)),
+            VertexAttributeValues::Uint(values) => VertexAttributeValues::Uint(gather(values, indices)),
+            VertexAttributeValues::Uint2(values) => VertexAttributeValues::Uint2(gather(values, indices)),
+            VertexAttributeValues::Uint3(values) => VertexAttributeValues::Uint3(gather(values, indices)),
+            VertexAttributeValues::Uint4(values) => VertexAttributeValues::Uint4(gather(values, indices)),
+            VertexAttributeValues::Half2(values) => VertexAttributeValues::Half2(gather(values, indices)),
+            VertexAttributeValues::Half4(values) => VertexAttributeValues::Half4(gather(values, indices)),
+            VertexAttributeValues::Short2(values) => VertexAttributeValues::Short2(gather(values, indices)),
+            VertexAttributeValues::Short4(values) => VertexAttributeValues::Short4(gather(values, indices)),
+            VertexAttributeValues::Short2Norm(values) => VertexAttributeValues::Short2Norm(gather(values, indices)),
+            VertexAttributeValues::Short4Norm(values) => VertexAttributeValues::Short4Norm(gather(values, indices)),
+            VertexAttributeValues::Ushort2(values) => VertexAttributeValues::Ushort2(gather(values, indices)),
+            VertexAttributeValues::Ushort4(values) => VertexAttributeValues::Ushort4(gather(values, indices)),
+            VertexAttributeValues::Ushort2Norm(values) => VertexAttributeValues::Ushort2Norm(gather(values, indices)),
+            VertexAttributeValues::Ushort4Norm(values) => VertexAttributeValues::Ushort4Norm(gather(values, indices)),
+            VertexAttributeValues::Char2(values) => VertexAttributeValues::Char2(gather(values, indices)),
+            VertexAttributeValues::Char4(values) => VertexAttributeValues::Char4(gather(values, indices)),
+            VertexAttributeValues::Char2Norm(values) => VertexAttributeValues::Char2Norm(gather(values, indices)),
+            VertexAttributeValues::Char4Norm(values) => VertexAttributeValues::Char4Norm(gather(values, indices)),
+            VertexAttributeValues::Uchar2(values) => VertexAttributeValues::Uchar2(gather(values, indices)),
+            VertexAttributeValues::Uchar4(values) => VertexAttributeValues::Uchar4(gather(values, indices)),
+            VertexAttributeValues::Uchar2Norm(values) => VertexAttributeValues::Uchar2Norm(gather(values, indices)),
+            VertexAttributeValues::Uchar4Norm(values) => VertexAttributeValues::Uchar4Norm(gather(values, indices)),
+        }
+    }
+
+    /// Converts the values to half floats
+    pub fn half2(values: Vec<[f32; 2]>) -> Self {
+        VertexAttributeValues::Half2(values.iter().map(|v| [f32_to_f16(v[0]), f32_to_f16(v[1])]).collect())
+    }
+
+    /// Converts the values to half floats
+    pub fn half4(values: Vec<[f32; 4]>) -> Self {
+        VertexAttributeValues::Half4(
+            values.iter().map(|v| [f32_to_f16(v[0]), f32_to_f16(v[1]), f32_to_f16(v[2]), f32_to_f16(v[3])]).collect()
+        )
+    }
+}
+
+/// Converts a float into the bits of a half float, rounds to the nearest value
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    // infinity & NaN
+    if exponent == 0xff {
+        return sign | 0x7c00 | if mantissa != 0 { 0x0200 } else { 0 };
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    // values too small for a normal half float become subnormal or zero
+    let (half, shift) = if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        (0, (14 - exponent) as u32)
+    } else {
+        ((exponent as u32) << 10, 13)
+    };
+
+    let mantissa = if exponent <= 0 { mantissa | 0x0080_0000 } else { mantissa };
+    let half = half | (mantissa >> shift);
+    let round_bit = (mantissa >> (shift - 1)) & 1;
+    let sticky = mantissa & ((1 << (shift - 1)) - 1) != 0;
+
+    // round to nearest even, a carry into the exponent is still correct
+    let half = if round_bit == 1 && (sticky || half & 1 == 1) { half + 1 } else { half };
+    sign | half as u16
 }
 
 impl From<&VertexAttributeValues> for VertexFormat {
@@ -43,6 +257,32 @@ impl From<&VertexAttributeValues> for VertexFormat {
             VertexAttributeValues::Float2(_) => VertexFormat::Float2,
             VertexAttributeValues::Float3(_) => VertexFormat::Float3,
             VertexAttributeValues::Float4(_) => VertexFormat::Float4,
+            VertexAttributeValues::Int(_) => VertexFormat::Int,
+            VertexAttributeValues::Int2(_) => VertexFormat::Int2,
+            VertexAttributeValues::Int3(_) => VertexFormat::Int3,
+            VertexAttributeValues::Int4(_) => VertexFormat::Int4,
+            VertexAttributeValues::Uint(_) => VertexFormat::Uint,
+            VertexAttributeValues::Uint2(_) => VertexFormat::Uint2,
+            VertexAttributeValues::Uint3(_) => VertexFormat::Uint3,
+            VertexAttributeValues::Uint4(_) => VertexFormat::Uint4,
+            VertexAttributeValues::Half2(_) => VertexFormat::Half2,
+            VertexAttributeValues::Half4(_) => VertexFormat::Half4,
+            VertexAttributeValues::Short2(_) => VertexFormat::Short2,
+            VertexAttributeValues::Short4(_) => VertexFormat::Short4,
+            VertexAttributeValues::Short2Norm(_) => VertexFormat::Short2Norm,
+            VertexAttributeValues::Short4Norm(_) => VertexFormat::Short4Norm,
+            VertexAttributeValues::Ushort2(_) => VertexFormat::Ushort2,
+            VertexAttributeValues::Ushort4(_) => VertexFormat::Ushort4,
+            VertexAttributeValues::Ushort2Norm(_) => VertexFormat::Ushort2Norm,
+            VertexAttributeValues::Ushort4Norm(_) => VertexFormat::Ushort4Norm,
+            VertexAttributeValues::Char2(_) => VertexFormat::Char2,
+            VertexAttributeValues::Char4(_) => VertexFormat::Char4,
+            VertexAttributeValues::Char2Norm(_) => VertexFormat::Char2Norm,
+            VertexAttributeValues::Char4Norm(_) => VertexFormat::Char4Norm,
+            VertexAttributeValues::Uchar2(_) => VertexFormat::Uchar2,
+            VertexAttributeValues::Uchar4(_) => VertexFormat::Uchar4,
+            VertexAttributeValues::Uchar2Norm(_) => VertexFormat::Uchar2Norm,
+            VertexAttributeValues::Uchar4Norm(_) => VertexFormat::Uchar4Norm,
         }
     }
 }
@@ -53,6 +293,124 @@ impl From<Vec<f32>> for VertexAttributeValues {
     }
 }
 
+impl From<Vec<[f32; 2]>> for VertexAttributeValues {
+    fn from(vec: Vec<[f32; 2]>) -> Self {
+        VertexAttributeValues::Float2(vec)
+    }
+}
+
+impl From<Vec<[f32; 3]>> for VertexAttributeValues {
+    fn from(vec: Vec<[f32; 3]>) -> Self {
+        VertexAttributeValues::Float3(vec)
+    }
+}
+
+impl From<Vec<[f32; 4]>> for VertexAttributeValues {
+    fn from(vec: Vec<[f32; 4]>) -> Self {
+        VertexAttributeValues::Float4(vec)
+    }
+}
+
+impl From<Vec<i32>> for VertexAttributeValues {
+    fn from(vec: Vec<i32>) -> Self {
+        VertexAttributeValues::Int(vec)
+    }
+}
+
+impl From<Vec<[i32; 2]>> for VertexAttributeValues {
+    fn from(vec: Vec<[i32; 2]>) -> Self {
+        VertexAttributeValues::Int2(vec)
+    }
+}
+
+impl From<Vec<[i32; 3]>> for VertexAttributeValues {
+    fn from(vec: Vec<[i32; 3]>) -> Self {
+        VertexAttributeValues::Int3(vec)
+    }
+}
+
+impl From<Vec<[i32; 4]>> for VertexAttributeValues {
+    fn from(vec: Vec<[i32; 4]>) -> Self {
+        VertexAttributeValues::Int4(vec)
+    }
+}
+
+impl From<Vec<u32>> for VertexAttributeValues {
+    fn from(vec: Vec<u32>) -> Self {
+        VertexAttributeValues::Uint(vec)
+    }
+}
+
+impl From<Vec<[u32; 2]>> for VertexAttributeValues {
+    fn from(vec: Vec<[u32; 2]>) -> Self {
+        VertexAttributeValues::Uint2(vec)
+    }
+}
+
+impl From<Vec<[u32; 3]>> for VertexAttributeValues {
+    fn from(vec: Vec<[u32; 3]>) -> Self {
+        VertexAttributeValues::Uint3(vec)
+    }
+}
+
+impl From<Vec<[u32; 4]>> for VertexAttributeValues {
+    fn from(vec: Vec<[u32; 4]>) -> Self {
+        VertexAttributeValues::Uint4(vec)
+    }
+}
+
+// 16 bit values are mostly joint indices, therefore they keep integer formats,
+// normalized 16 bit data, e.g. texcoords, uses the `Ushort*Norm` & `Short*Norm` variants explicitly
+impl From<Vec<[u16; 2]>> for VertexAttributeValues {
+    fn from(vec: Vec<[u16; 2]>) -> Self {
+        VertexAttributeValues::Ushort2(vec)
+    }
+}
+
+impl From<Vec<[u16; 4]>> for VertexAttributeValues {
+    fn from(vec: Vec<[u16; 4]>) -> Self {
+        VertexAttributeValues::Ushort4(vec)
+    }
+}
+
+impl From<Vec<[i16; 2]>> for VertexAttributeValues {
+    fn from(vec: Vec<[i16; 2]>) -> Self {
+        VertexAttributeValues::Short2(vec)
+    }
+}
+
+impl From<Vec<[i16; 4]>> for VertexAttributeValues {
+    fn from(vec: Vec<[i16; 4]>) -> Self {
+        VertexAttributeValues::Short4(vec)
+    }
+}
+
+// 8 bit values are mostly colors & packed normals, therefore they convert to normalized formats,
+// 8 bit joint indices use the `Uchar4` variant explicitly
+impl From<Vec<[u8; 2]>> for VertexAttributeValues {
+    fn from(vec: Vec<[u8; 2]>) -> Self {
+        VertexAttributeValues::Uchar2Norm(vec)
+    }
+}
+
+impl From<Vec<[u8; 4]>> for VertexAttributeValues {
+    fn from(vec: Vec<[u8; 4]>) -> Self {
+        VertexAttributeValues::Uchar4Norm(vec)
+    }
+}
+
+impl From<Vec<[i8; 2]>> for VertexAttributeValues {
+    fn from(vec: Vec<[i8; 2]>) -> Self {
+        VertexAttributeValues::Char2Norm(vec)
+    }
+}
+
+impl From<Vec<[i8; 4]>> for VertexAttributeValues {
+    fn from(vec: Vec<[i8; 4]>) -> Self {
+        VertexAttributeValues::Char4Norm(vec)
+    }
+}
+
 #[derive(Debug)]
 pub struct VertexAttribute {
     /// The name of the vertex attribute
@@ -86,15 +444,37 @@ impl VertexAttribute {
         }
     }
 
+    /// Sets the tangents for all vertices, w contains the handedness of the bitangent
+    pub fn tangents(tangents: Vec<[f32; 4]>) -> Self {
+        VertexAttribute {
+            name: "tangents".into(),
+            values: VertexAttributeValues::Float4(tangents),
+        }
+    }
+
+    /// Creates an attribute with the given name, e.g. `VertexAttribute::new("colors", vec![[255u8, 0, 0, 255]])`
+    pub fn new<V: Into<VertexAttributeValues>>(name: &'static str, values: V) -> Self {
+        VertexAttribute {
+            name: name.into(),
+            values: values.into(),
+        }
+    }
+
     /// Returns the vertex size of this attribute
     pub fn vertex_size(&self) -> usize {
         let format: VertexFormat = (&self.values).into();
         format.size() as usize
     }
+
+    /// Returns the size of this attribute in an interleaved vertex, including padding
+    pub fn padded_size(&self) -> usize {
+        let format: VertexFormat = (&self.values).into();
+        format.padded_size() as usize
+    }
 }
 
 impl Display for VertexAttribute {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
     }
 }
@@ -128,6 +508,14 @@ impl Indices {
             Indices::U32(v) => v.len() as u32,
         }
     }
+
+    /// Returns all indices as u32 values
+    pub fn to_u32(&self) -> Vec<u32> {
+        match self {
+            Indices::U16(v) => v.iter().map(|&index| index as u32).collect(),
+            Indices::U32(v) => v.clone(),
+        }
+    }
 }
 
 /// A Mesh struct that contains vertices, normals, tex coords.
@@ -170,35 +558,17 @@ impl Mesh {
         vertex_count.unwrap_or(0)
     }
 
-    /// Returns the stride of a single vertex
+    /// Returns the stride of a single vertex, each attribute is aligned to 4 bytes
     pub fn vertex_stride(&self) -> usize {
         self.attributes
             .iter()
-            .map(|attribute| attribute.vertex_size())
+            .map(|attribute| attribute.padded_size())
             .sum()
     }
 
     /// Returns all bytes as u8 vector
     pub fn vertex_data(&self) -> Vec<u8> {
-        let vertex_count = self.vertex_count();
-        let vertex_size = self.vertex_stride();
-        let mut interleaved_data = vec![0; vertex_count * vertex_size];
-
-        let mut attribute_offset = 0;
-        for attribute in self.attributes.iter() {
-            let vertex_format: VertexFormat = attribute.into();
-            let attribute_size = vertex_format.size() as usize;
-            let attribute_bytes = attribute.values.get_bytes();
-
-            for (vertex_index, attribute_bytes) in attribute_bytes.chunks_exact(attribute_size).enumerate() {
-                let offset = vertex_index * vertex_size + attribute_offset;
-                interleaved_data[offset..offset + attribute_size].copy_from_slice(attribute_bytes);
-            }
-
-            attribute_offset += attribute_size;
-        }
-
-        interleaved_data
+        interleave(&self.attributes.iter().collect::<Vec<&VertexAttribute>>(), self.vertex_count())
     }
 
     /// Returns vertex buffer descriptor
@@ -210,152 +580,111 @@ impl Mesh {
 
         VertexBufferDescriptor::new(vertex_formats)
     }
-}
 
-pub mod shape {
-    use crate::{Indices, Mesh, PrimitiveTopology, VertexAttribute};
-
-    pub struct Cube {
-        /// Half the side length of the cube
-        pub size: f32,
+    /// Returns the attribute with the given name
+    pub fn attribute(&self, name: &str) -> Option<&VertexAttribute> {
+        self.attributes.iter().find(|attribute| attribute.name == name)
     }
 
-    impl Cube {
-        pub fn new(size: f32) -> Self {
-            Cube { size }
+    /// Adds the attribute, replaces an existing attribute with the same name
+    pub fn set_attribute(&mut self, attribute: VertexAttribute) {
+        match self.attributes.iter_mut().find(|existing| existing.name == attribute.name) {
+            Some(existing) => *existing = attribute,
+            None => self.attributes.push(attribute),
         }
     }
 
-    impl Default for Cube {
-        fn default() -> Self {
-            Cube { size: 1.0, }
+    /// Interleaves the attributes required by the shader inputs in order of their locations.
+    ///
+    /// Attributes the shader does not use are skipped, the returned descriptor replaces the
+    /// reflected vertex buffers of the pipeline.
+    pub fn map_attributes(
+        &self,
+        inputs: &[&VertexAttributeDescriptor],
+        mapping: &AttributeMapping,
+    ) -> Result<MappedVertices, MeshError> {
+        let mut inputs = inputs.to_vec();
+        inputs.sort_by_key(|input| input.location);
+
+        let mut attributes = Vec::new();
+        let mut descriptors = Vec::new();
+        let mut offset = 0;
+
+        for input in inputs {
+            let attribute = self.attributes
+                .iter()
+                .find(|attribute| mapping.matches(&attribute.name, input))
+                .ok_or_else(|| MeshError::MissingAttribute(input.name.clone(), input.location))?;
+
+            let format: VertexFormat = attribute.into();
+            if format.shader_format() != input.format.shader_format() {
+                return Err(MeshError::FormatMismatch(attribute.name.to_string(), format, input.format));
+            }
+
+            descriptors.push(VertexAttributeDescriptor {
+                name: attribute.name.to_string(),
+                offset,
+                location: input.location,
+                format,
+            });
+            attributes.push(attribute);
+            offset += format.padded_size();
         }
+
+        Ok(MappedVertices {
+            descriptor: VertexBufferDescriptor {
+                label: "Mesh".to_string(),
+                stride: offset,
+                step_mode: InputStepMode::Vertex,
+                attributes: descriptors,
+            },
+            data: interleave(&attributes, self.vertex_count()),
+        })
     }
+}
 
-    impl From<Cube> for Mesh {
-        fn from(cube: Cube) -> Self {
-            let half = cube.size / 2.0;
-            let vertices = vec![
-                [-half, -half,  half],
-                [ half, -half,  half],
-                [ half,  half,  half],
-                [-half,  half,  half],
-
-                [-half, -half, -half],
-                [-half,  half, -half],
-                [ half,  half, -half],
-                [ half, -half, -half],
-
-                [-half,  half, -half],
-                [-half,  half,  half],
-                [ half,  half,  half],
-                [ half,  half, -half],
-
-                [-half, -half, -half],
-                [ half, -half, -half],
-                [ half, -half,  half],
-                [-half, -half,  half],
-
-                [ half, -half, -half],
-                [ half,  half, -half],
-                [ half,  half,  half],
-                [ half, -half,  half],
-
-                [-half, -half, -half],
-                [-half, -half,  half],
-                [-half,  half,  half],
-                [-half,  half, -half],
-            ];
-
-            let normals = vec![
-                [0.0, 0.0, 1.0],
-                [0.0, 0.0, 1.0],
-                [0.0, 0.0, 1.0],
-                [0.0, 0.0, 1.0],
-
-                [0.0, 0.0, -1.0],
-                [0.0, 0.0, -1.0],
-                [0.0, 0.0, -1.0],
-                [0.0, 0.0, -1.0],
-
-                [0.0, 1.0, 0.0],
-                [0.0, 1.0, 0.0],
-                [0.0, 1.0, 0.0],
-                [0.0, 1.0, 0.0],
-
-                [0.0, -1.0, 0.0],
-                [0.0, -1.0, 0.0],
-                [0.0, -1.0, 0.0],
-                [0.0, -1.0, 0.0],
-
-                [1.0, 0.0, 0.0],
-                [1.0, 0.0, 0.0],
-                [1.0, 0.0, 0.0],
-                [1.0, 0.0, 0.0],
-
-                [-1.0, 0.0, 0.0],
-                [-1.0, 0.0, 0.0],
-                [-1.0, 0.0, 0.0],
-                [-1.0, 0.0, 0.0],
-            ];
-
-            let texcoords = vec![
-                [0.0,  0.0],
-                [1.0,  0.0],
-                [1.0,  1.0],
-                [0.0,  1.0],
-
-                [0.0,  0.0],
-                [1.0,  0.0],
-                [1.0,  1.0],
-                [0.0,  1.0],
-
-                [0.0,  0.0],
-                [1.0,  0.0],
-                [1.0,  1.0],
-                [0.0,  1.0],
-
-                [0.0,  0.0],
-                [1.0,  0.0],
-                [1.0,  1.0],
-                [0.0,  1.0],
-
-                [0.0,  0.0],
-                [1.0,  0.0],
-                [1.0,  1.0],
-                [0.0,  1.0],
-
-                [0.0,  0.0],
-                [1.0,  0.0],
-                [1.0,  1.0],
-                [0.0,  1.0],
-            ];
-
-            let indices = Indices::U32(vec![
-                0,  1,  2,    0,  2,  3,  // Front face
-                4,  5,  6,    4,  6,  7,  // Back face
-                8,  9, 10,    8, 10, 11,  // Top face
-               12, 13, 14,   12, 14, 15,  // Bottom face
-               16, 17, 18,   16, 18, 19,  // Right face
-               20, 21, 22,   20, 22, 23   // Left face
-            ]);
-
-            Mesh {
-                topology: PrimitiveTopology::TriangleList,
-                attributes: vec![
-                    VertexAttribute::positions(vertices),
-                    VertexAttribute::normals(normals),
-                    VertexAttribute::texcoords(texcoords),
-                ],
-                indices: Some(indices),
-            }
+/// Interleaved vertex data of a mesh, laid out as the vertex shader expects it
+#[derive(Debug, Clone)]
+pub struct MappedVertices {
+    /// Describes the layout of a single vertex
+    pub descriptor: VertexBufferDescriptor,
+    /// The interleaved bytes of all vertices
+    pub data: Vec<u8>,
+}
+
+/// Interleaves the values of all given attributes, one vertex after the other, padded to 4 bytes
+fn interleave(attributes: &[&VertexAttribute], vertex_count: usize) -> Vec<u8> {
+    let vertex_size: usize = attributes.iter().map(|attribute| attribute.padded_size()).sum();
+    let mut interleaved_data = vec![0; vertex_count * vertex_size];
+
+    let mut attribute_offset = 0;
+    for attribute in attributes.iter() {
+        let attribute_size = attribute.vertex_size();
+        let attribute_bytes = attribute.values.get_bytes();
+
+        for (vertex_index, attribute_bytes) in attribute_bytes.chunks_exact(attribute_size).enumerate() {
+            let offset = vertex_index * vertex_size + attribute_offset;
+            interleaved_data[offset..offset + attribute_size].copy_from_slice(attribute_bytes);
         }
+
+        attribute_offset += attribute.padded_size();
     }
+
+    interleaved_data
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Mesh, shape};
+    use crate::{f32_to_f16, AttributeMapping, Mesh, MeshError, PrimitiveTopology, VertexAttribute, VertexAttributeDescriptor, VertexAttributeValues, VertexFormat, shape};
+
+    fn input(name: &str, location: u32, format: VertexFormat) -> VertexAttributeDescriptor {
+        VertexAttributeDescriptor {
+            name: name.to_string(),
+            offset: 0,
+            location,
+            format,
+        }
+    }
 
     #[test]
     fn cube_vertex() {
@@ -365,4 +694,84 @@ mod tests {
         assert_eq!(cube.vertex_stride(), (3 + 3 + 2) * std::mem::size_of::<f32>());
         assert_eq!(cube.vertex_data().len(), cube.vertex_count() * cube.vertex_stride());
     }
+
+    #[test]
+    fn it_maps_attributes_to_shader_inputs() {
+        let cube: Mesh = shape::Cube::new(1.0).into();
+        let texcoord = input("i_uv", 0, VertexFormat::Float2);
+        let position = input("i_position", 3, VertexFormat::Float3);
+
+        let vertices = cube.map_attributes(&[&position, &texcoord], &AttributeMapping::default()).unwrap();
+        assert_eq!(
+            vec![
+                VertexAttributeDescriptor { name: "texcoords".into(), offset: 0, location: 0, format: VertexFormat::Float2 },
+                VertexAttributeDescriptor { name: "positions".into(), offset: 8, location: 3, format: VertexFormat::Float3 },
+            ],
+            vertices.descriptor.attributes,
+        );
+        assert_eq!(20, vertices.descriptor.stride);
+        assert_eq!(24 * 20, vertices.data.len());
+        // the first vertex starts with its texcoord, followed by its position
+        assert_eq!(bytemuck::cast_slice::<f32, u8>(&[0.0, 0.0, -0.5, -0.5, 0.5]), &vertices.data[..20]);
+
+        let color = input("i_color", 1, VertexFormat::Float4);
+        assert_eq!(
+            Err(MeshError::MissingAttribute("i_color".into(), 1)),
+            cube.map_attributes(&[&position, &color], &AttributeMapping::default()).map(|_| ()),
+        );
+
+        let normal = input("i_normal", 1, VertexFormat::Float4);
+        assert_eq!(
+            Err(MeshError::FormatMismatch("normals".into(), VertexFormat::Float3, VertexFormat::Float4)),
+            cube.map_attributes(&[&normal], &AttributeMapping::default()).map(|_| ()),
+        );
+    }
+
+    #[test]
+    fn it_interleaves_attributes_of_mixed_sizes() {
+        let mut mesh = Mesh::new(PrimitiveTopology::PointList);
+        mesh.attributes.push(VertexAttribute::new("uvs", vec![[255u8, 0], [0, 255]]));
+        mesh.attributes.push(VertexAttribute::new("joints", vec![[1u16, 2, 3, 4], [5, 6, 7, 8]]));
+        mesh.attributes.push(VertexAttribute::new("colors", vec![[255u8, 128, 0, 255], [0, 0, 0, 0]]));
+        mesh.attributes.push(VertexAttribute::new("ids", vec![7u32, 9]));
+
+        assert_eq!(VertexFormat::Uchar2Norm, (&mesh.attributes[0]).into());
+        assert_eq!(VertexFormat::Ushort4, (&mesh.attributes[1]).into());
+        assert_eq!(VertexFormat::Uchar4Norm, (&mesh.attributes[2]).into());
+        assert_eq!(VertexFormat::Uint, (&mesh.attributes[3]).into());
+
+        // the 2 byte uvs are padded to keep the following attributes aligned
+        assert_eq!(4 + 8 + 4 + 4, mesh.vertex_stride());
+        let offsets = mesh.vertex_buffer_descriptor().attributes.iter().map(|a| a.offset).collect::<Vec<u64>>();
+        assert_eq!(vec![0, 4, 12, 16], offsets);
+
+        let data = mesh.vertex_data();
+        assert_eq!(2 * 20, data.len());
+        assert_eq!(&[255, 0, 0, 0, 1, 0, 2, 0, 3, 0, 4, 0, 255, 128, 0, 255, 7, 0, 0, 0], &data[..20]);
+        assert_eq!(&[0, 255, 0, 0], &data[20..24]);
+
+        // normalized colors are read as vec4 by the shader
+        let color = input("a_color", 0, VertexFormat::Float4);
+        let vertices = mesh.map_attributes(&[&color], &AttributeMapping::default()).unwrap();
+        assert_eq!(4, vertices.descriptor.stride);
+        assert_eq!(VertexFormat::Uchar4Norm, vertices.descriptor.attributes[0].format);
+    }
+
+    #[test]
+    fn it_converts_floats_to_half_floats() {
+        assert_eq!(0x3c00, f32_to_f16(1.0));
+        assert_eq!(0xc000, f32_to_f16(-2.0));
+        assert_eq!(0x3800, f32_to_f16(0.5));
+        assert_eq!(0x2e66, f32_to_f16(0.1));
+        assert_eq!(0x7bff, f32_to_f16(65504.0));
+        assert_eq!(0x7c00, f32_to_f16(65536.0));
+        assert_eq!(0x0001, f32_to_f16(5.960_464_5e-8));
+        assert_eq!(0x0000, f32_to_f16(1.0e-9));
+        assert_eq!(0xfc00, f32_to_f16(f32::NEG_INFINITY));
+
+        assert_eq!(
+            VertexAttributeValues::Half2(vec![[0x3c00, 0x3800]]),
+            VertexAttributeValues::half2(vec![[1.0, 0.5]]),
+        );
+    }
 }
@@ -1,3 +1,13 @@
+pub mod attribute_mapping;
+pub mod export;
+pub mod gltf_scene;
 pub mod mesh;
+mod normals;
+pub mod obj;
+pub mod shape;
 
+pub use attribute_mapping::*;
+pub use export::*;
+pub use gltf_scene::*;
 pub use mesh::*;
+pub use obj::*;
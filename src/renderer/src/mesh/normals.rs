@@ -0,0 +1,395 @@
+use std::{collections::HashMap, hash::Hash};
+
+use cgmath::{InnerSpace, Rad, Vector3};
+
+use crate::{Indices, Mesh, MeshError, PrimitiveTopology, VertexAttribute, VertexAttributeValues, VertexFormat};
+
+impl Mesh {
+    /// Sets the normal of each vertex to the normal of its triangle.
+    ///
+    /// Vertices shared by triangles facing in different directions are split.
+    pub fn compute_flat_normals(&mut self) -> Result<(), MeshError> {
+        let indices = self.triangle_indices()?;
+        let positions = self.float3_values("positions")?;
+
+        let normals = indices
+            .chunks_exact(3)
+            .flat_map(|triangle| {
+                let normal = unit_normal(face_normal(positions, triangle)).unwrap_or_else(Vector3::unit_y);
+                vec![normal; 3]
+            })
+            .collect::<Vec<Vector3<f32>>>();
+
+        self.set_corner_normals(&indices, &normals);
+        Ok(())
+    }
+
+    /// Sets the normal of each vertex to the average normal of all triangles sharing its position.
+    ///
+    /// Triangles are weighted by their angle at the vertex. Triangles whose normals differ by more
+    /// than the angle threshold are not averaged, which keeps hard edges, e.g. the edges of a cube
+    /// with a threshold of `Deg(60.0)`. Vertices are split where their normals differ.
+    pub fn compute_smooth_normals<A: Into<Rad<f32>>>(&mut self, angle_threshold: A) -> Result<(), MeshError> {
+        let indices = self.triangle_indices()?;
+        let positions = self.float3_values("positions")?;
+        let min_cos = angle_threshold.into().0.cos();
+
+        let face_normals = indices
+            .chunks_exact(3)
+            .map(|triangle| unit_normal(face_normal(positions, triangle)))
+            .collect::<Vec<Option<Vector3<f32>>>>();
+
+        // the corners of all triangles at the same position, regardless of their vertex index
+        let corner_count = face_normals.len() * 3;
+        let mut corners: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+        for (corner, &index) in indices[..corner_count].iter().enumerate() {
+            corners.entry(position_key(positions[index as usize])).or_default().push(corner);
+        }
+
+        let weighted_normals = (0..corner_count)
+            .map(|corner| match face_normals[corner / 3] {
+                Some(normal) => normal * corner_angle(positions, &indices, corner),
+                None => Vector3::new(0.0, 0.0, 0.0),
+            })
+            .collect::<Vec<Vector3<f32>>>();
+
+        let normals = (0..corner_count)
+            .map(|corner| {
+                let neighbours = &corners[&position_key(positions[indices[corner] as usize])];
+                let smoothed = face_normals[corner / 3].and_then(|normal| {
+                    let sum = neighbours
+                        .iter()
+                        .filter(|&&other| matches!(face_normals[other / 3], Some(other) if normal.dot(other) >= min_cos))
+                        .map(|&other| weighted_normals[other])
+                        .sum();
+                    unit_normal(sum)
+                });
+
+                // degenerate triangles take the average of all their neighbours
+                smoothed
+                    .or_else(|| unit_normal(neighbours.iter().map(|&other| weighted_normals[other]).sum()))
+                    .unwrap_or_else(Vector3::unit_y)
+            })
+            .collect::<Vec<Vector3<f32>>>();
+
+        self.set_corner_normals(&indices, &normals);
+        Ok(())
+    }
+
+    /// Computes MikkTSpace tangents from positions, normals & texcoords, stored as `tangents` attribute.
+    ///
+    /// The w component contains the handedness, the bitangent is `cross(normal, tangent.xyz) * tangent.w`.
+    /// Tangents are generated per triangle corner, vertices whose corners got different tangents,
+    /// e.g. at the seam of mirrored texcoords, are split.
+    pub fn compute_tangents(&mut self) -> Result<(), MeshError> {
+        let indices = self.triangle_indices()?;
+        let mut tangent_space = TangentSpace {
+            indices: &indices,
+            positions: self.float3_values("positions")?,
+            normals: self.float3_values("normals")?,
+            texcoords: self.float2_values("texcoords")?,
+            tangents: vec![[1.0, 0.0, 0.0, 1.0]; indices.len()],
+        };
+
+        if !mikktspace::generate_tangents(&mut tangent_space) {
+            return Err(MeshError::TangentGenerationFailed);
+        }
+
+        let corner_tangents = tangent_space.tangents;
+        let tangents = self.split_vertices(&indices, &corner_tangents, |tangent| {
+            let [x, y, z, w] = *tangent;
+            [(x + 0.0).to_bits(), (y + 0.0).to_bits(), (z + 0.0).to_bits(), (w + 0.0).to_bits()]
+        });
+        self.set_attribute(VertexAttribute::tangents(tangents));
+        Ok(())
+    }
+
+    /// Returns the indices of all triangles, non indexed meshes use their vertices in order
+    pub(crate) fn triangle_indices(&self) -> Result<Vec<u32>, MeshError> {
+        if self.topology != PrimitiveTopology::TriangleList {
+            return Err(MeshError::UnsupportedTopology(self.topology));
+        }
+
+        Ok(match &self.indices {
+            Some(indices) => indices.to_u32(),
+            None => (0..self.vertex_count() as u32).collect(),
+        })
+    }
+
+    /// Returns the values of the attribute, fails if it is missing or not of format `Float3`
+    pub(crate) fn float3_values(&self, name: &str) -> Result<&[[f32; 3]], MeshError> {
+        let attribute = self.attribute(name).ok_or_else(|| MeshError::AttributeNotFound(name.to_string()))?;
+        match &attribute.values {
+            VertexAttributeValues::Float3(values) => Ok(values),
+            values => Err(MeshError::FormatMismatch(name.to_string(), values.into(), VertexFormat::Float3)),
+        }
+    }
+
+    /// Returns the values of the attribute, fails if it is missing or not of format `Float2`
+    pub(crate) fn float2_values(&self, name: &str) -> Result<&[[f32; 2]], MeshError> {
+        let attribute = self.attribute(name).ok_or_else(|| MeshError::AttributeNotFound(name.to_string()))?;
+        match &attribute.values {
+            VertexAttributeValues::Float2(values) => Ok(values),
+            values => Err(MeshError::FormatMismatch(name.to_string(), values.into(), VertexFormat::Float2)),
+        }
+    }
+
+    /// Sets a normal for each triangle corner, creates a new vertex for every distinct pair
+    /// of vertex & normal and copies all other attributes
+    fn set_corner_normals(&mut self, indices: &[u32], normals: &[Vector3<f32>]) {
+        let normals = normals.iter().map(|&normal| normal.into()).collect::<Vec<[f32; 3]>>();
+        let vertex_normals = self.split_vertices(indices, &normals, |normal| position_key(*normal));
+        self.set_attribute(VertexAttribute::normals(vertex_normals));
+    }
+
+    /// Creates a new vertex for every distinct pair of vertex & corner value, copies all attributes
+    /// and replaces the indices. Returns the value of each new vertex.
+    fn split_vertices<T, K, F>(&mut self, indices: &[u32], corner_values: &[T], key: F) -> Vec<T>
+    where
+        T: Copy,
+        K: Eq + Hash,
+        F: Fn(&T) -> K,
+    {
+        let mut vertices: HashMap<(u32, K), u32> = HashMap::new();
+        let mut source_indices = Vec::new();
+        let mut vertex_values = Vec::new();
+
+        let new_indices = indices
+            .iter()
+            .zip(corner_values.iter())
+            .map(|(&index, value)| {
+                *vertices.entry((index, key(value))).or_insert_with(|| {
+                    source_indices.push(index);
+                    vertex_values.push(*value);
+                    (source_indices.len() - 1) as u32
+                })
+            })
+            .collect::<Vec<u32>>();
+
+        for attribute in self.attributes.iter_mut() {
+            attribute.values = attribute.values.gather(&source_indices);
+        }
+
+        self.indices = Some(match self.indices {
+            Some(Indices::U16(_)) if source_indices.len() <= u16::MAX as usize + 1 => {
+                Indices::U16(new_indices.iter().map(|&index| index as u16).collect())
+            }
+            _ => Indices::U32(new_indices),
+        });
+        vertex_values
+    }
+}
+
+/// Provides the triangles of a mesh to the MikkTSpace generator
+struct TangentSpace<'a> {
+    indices: &'a [u32],
+    positions: &'a [[f32; 3]],
+    normals: &'a [[f32; 3]],
+    texcoords: &'a [[f32; 2]],
+    /// The tangent of each triangle corner
+    tangents: Vec<[f32; 4]>,
+}
+
+impl<'a> TangentSpace<'a> {
+    fn vertex(&self, face: usize, vert: usize) -> usize {
+        self.indices[face * 3 + vert] as usize
+    }
+}
+
+impl<'a> mikktspace::Geometry for TangentSpace<'a> {
+    fn num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.positions[self.vertex(face, vert)]
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.normals[self.vertex(face, vert)]
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.texcoords[self.vertex(face, vert)]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        self.tangents[face * 3 + vert] = tangent;
+    }
+}
+
+/// Returns the normal of the triangle, its length is twice the area of the triangle
+pub(super) fn face_normal(positions: &[[f32; 3]], triangle: &[u32]) -> Vector3<f32> {
+    let a = Vector3::from(positions[triangle[0] as usize]);
+    let b = Vector3::from(positions[triangle[1] as usize]);
+    let c = Vector3::from(positions[triangle[2] as usize]);
+    (b - a).cross(c - a)
+}
+
+/// Returns the angle of the triangle at the given corner
+fn corner_angle(positions: &[[f32; 3]], indices: &[u32], corner: usize) -> f32 {
+    let triangle = corner / 3 * 3;
+    let position = |offset: usize| Vector3::from(positions[indices[triangle + (corner + offset) % 3] as usize]);
+    let vertex = position(0);
+    (position(1) - vertex).angle(position(2) - vertex).0
+}
+
+/// Returns the normalized vector, degenerate vectors have no direction
+pub(super) fn unit_normal(normal: Vector3<f32>) -> Option<Vector3<f32>> {
+    if normal.magnitude2() > f32::EPSILON * f32::EPSILON {
+        Some(normal.normalize())
+    } else {
+        None
+    }
+}
+
+/// Returns the bits of the vector to use it as a key, `-0.0` equals `0.0`
+fn position_key(position: [f32; 3]) -> [u32; 3] {
+    [(position[0] + 0.0).to_bits(), (position[1] + 0.0).to_bits(), (position[2] + 0.0).to_bits()]
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Deg, InnerSpace, Vector3};
+
+    use crate::{Indices, Mesh, MeshError, PrimitiveTopology, VertexAttribute, VertexAttributeValues, shape};
+
+    fn float3_values(mesh: &Mesh, name: &str) -> Vec<[f32; 3]> {
+        match &mesh.attribute(name).unwrap().values {
+            VertexAttributeValues::Float3(values) => values.clone(),
+            values => panic!("Unexpected values {:?}", values),
+        }
+    }
+
+    fn assert_near(expected: &[f32], actual: &[f32]) {
+        for (expected, actual) in expected.iter().zip(actual.iter()) {
+            assert!((expected - actual).abs() < 1e-5, "Expected {:?}, got {:?}", expected, actual);
+        }
+    }
+
+    /// A pyramid with a square base, all triangles share the apex
+    fn pyramid() -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.attributes.push(VertexAttribute::positions(vec![
+            [-1.0, 0.0, 1.0], [1.0, 0.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 0.0, -1.0], [0.0, 1.0, 0.0],
+        ]));
+        mesh.attributes.push(VertexAttribute::new("colors", vec![[255u8, 0, 0, 255]; 5]));
+        mesh.indices = Some(Indices::U16(vec![0, 1, 4, 1, 2, 4, 2, 3, 4, 3, 0, 4, 0, 3, 2, 0, 2, 1]));
+        mesh
+    }
+
+    #[test]
+    fn it_computes_flat_normals() {
+        let mut mesh = pyramid();
+        mesh.compute_flat_normals().unwrap();
+
+        // the base is flat, the apex & the other vertices are split for each side
+        assert_eq!(4 * 3 + 4, mesh.vertex_count());
+        assert_eq!(18, mesh.indices.as_ref().unwrap().len());
+        assert!(matches!(mesh.indices, Some(Indices::U16(_))));
+        assert_eq!(16, mesh.attribute("colors").unwrap().values.len());
+
+        let normals = float3_values(&mesh, "normals");
+        let positions = float3_values(&mesh, "positions");
+        let side = 1.0 / 2.0f32.sqrt();
+        assert_near(&[0.0, side, side], &normals[0]);
+        assert_near(&[-1.0, 0.0, 1.0], &positions[0]);
+
+        // cube faces are already split, their normals stay the same
+        let mut cube: Mesh = shape::Cube::new(1.0).into();
+        let expected = float3_values(&cube, "normals");
+        cube.compute_flat_normals().unwrap();
+        assert_eq!(24, cube.vertex_count());
+        for (expected, actual) in expected.iter().zip(float3_values(&cube, "normals").iter()) {
+            assert_near(expected, actual);
+        }
+    }
+
+    #[test]
+    fn it_computes_smooth_normals_within_angle_threshold() {
+        let mut sphere: Mesh = shape::UvSphere::new(1.0).into();
+        let vertex_count = sphere.vertex_count();
+        sphere.compute_smooth_normals(Deg(60.0)).unwrap();
+
+        // sphere normals point away from the center, the texture seam does not split the normals,
+        // the unused vertices at the poles are dropped
+        assert_eq!(vertex_count - 2, sphere.vertex_count());
+        for (position, normal) in float3_values(&sphere, "positions").iter().zip(float3_values(&sphere, "normals").iter()) {
+            assert!(Vector3::from(*position).dot(Vector3::from(*normal)) > 0.99);
+        }
+        shape::tests::assert_valid_mesh(&sphere);
+
+        // the edges of a cube stay sharp below 90 degrees
+        let mut cube: Mesh = shape::Cube::new(1.0).into();
+        let expected = float3_values(&cube, "normals");
+        cube.compute_smooth_normals(Deg(60.0)).unwrap();
+        for (expected, actual) in expected.iter().zip(float3_values(&cube, "normals").iter()) {
+            assert_near(expected, actual);
+        }
+
+        cube.compute_smooth_normals(Deg(120.0)).unwrap();
+        let diagonal = 1.0 / 3.0f32.sqrt();
+        for (position, normal) in float3_values(&cube, "positions").iter().zip(float3_values(&cube, "normals").iter()) {
+            let expected = [position[0].signum() * diagonal, position[1].signum() * diagonal, position[2].signum() * diagonal];
+            assert_near(&expected, normal);
+        }
+    }
+
+    #[test]
+    fn it_computes_tangents() {
+        let mut plane: Mesh = shape::Plane::new(2.0, 2.0).with_subdivisions(2).into();
+        plane.compute_tangents().unwrap();
+
+        // u runs along +X, v along +Z, which is mirrored relative to cross(normal, tangent)
+        match &plane.attribute("tangents").unwrap().values {
+            VertexAttributeValues::Float4(tangents) => {
+                assert_eq!(9, tangents.len());
+                for tangent in tangents {
+                    assert_near(&[1.0, 0.0, 0.0, -1.0], tangent);
+                }
+            }
+            values => panic!("Unexpected tangents {:?}", values),
+        }
+
+        let mut mesh = pyramid();
+        assert_eq!(Err(MeshError::AttributeNotFound("normals".into())), mesh.compute_tangents());
+        mesh.topology = PrimitiveTopology::LineList;
+        assert_eq!(Err(MeshError::UnsupportedTopology(PrimitiveTopology::LineList)), mesh.compute_flat_normals());
+    }
+
+    #[test]
+    fn it_splits_vertices_at_mirrored_texcoords() {
+        // two quads sharing an edge at x = 0, the texcoords of the left quad are mirrored
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.attributes.push(VertexAttribute::positions(vec![
+            [-1.0, 0.0, 1.0], [0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [-1.0, 0.0, -1.0], [0.0, 0.0, -1.0], [1.0, 0.0, -1.0],
+        ]));
+        mesh.attributes.push(VertexAttribute::normals(vec![[0.0, 1.0, 0.0]; 6]));
+        mesh.attributes.push(VertexAttribute::texcoords(vec![
+            [1.0, 1.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0], [1.0, 0.0],
+        ]));
+        mesh.indices = Some(Indices::U16(vec![0, 1, 4, 0, 4, 3, 1, 2, 5, 1, 5, 4]));
+        mesh.compute_tangents().unwrap();
+
+        // the two vertices on the shared edge are split
+        assert_eq!(8, mesh.vertex_count());
+        assert_eq!(8, mesh.attribute("texcoords").unwrap().values.len());
+
+        let tangents = match &mesh.attribute("tangents").unwrap().values {
+            VertexAttributeValues::Float4(tangents) => tangents.clone(),
+            values => panic!("Unexpected tangents {:?}", values),
+        };
+        let indices = mesh.indices.as_ref().unwrap().to_u32();
+        for (triangle, corners) in indices.chunks_exact(3).enumerate() {
+            let direction = if triangle < 2 { -1.0 } else { 1.0 };
+            for &index in corners {
+                let tangent = tangents[index as usize];
+                assert_near(&[direction, 0.0, 0.0], &tangent[..3]);
+            }
+        }
+    }
+}
@@ -0,0 +1,730 @@
+use std::{collections::HashMap, fmt::{self, Display}, fs::File, io::Read, path::Path};
+
+use cgmath::{InnerSpace, Vector2, Vector3};
+
+use crate::{Indices, Mesh, PrimitiveTopology, VertexAttribute};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjError {
+    /// Failed to open file
+    OpenFileFailed(String),
+    /// Failed to read from file
+    ReadFromFileFailed(String),
+    /// A statement could not be parsed, contains file name, line number & reason
+    InvalidStatement(String, usize, String),
+    /// A face references a vertex that does not exist, contains file name, line number & index
+    IndexOutOfRange(String, usize, i64),
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ObjError::OpenFileFailed(file) => format!("Failed to open file: {}", file),
+            ObjError::ReadFromFileFailed(file) => format!("Failed to read from file: {}", file),
+            ObjError::InvalidStatement(file, line, reason) => format!("{}:{}: {}", file, line, reason),
+            ObjError::IndexOutOfRange(file, line, index) => format!("{}:{}: Index {} is out of range", file, line, index),
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The parameters of a material from an MTL library, unset values are `None`.
+///
+/// Texture paths are stored as written in the library, relative to the library file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjMaterial {
+    /// The name the material is referenced by, `newmtl`
+    pub name: String,
+    /// Ambient color, `Ka`
+    pub ambient: Option<[f32; 3]>,
+    /// Diffuse color, `Kd`
+    pub diffuse: Option<[f32; 3]>,
+    /// Specular color, `Ks`
+    pub specular: Option<[f32; 3]>,
+    /// Emissive color, `Ke`
+    pub emissive: Option<[f32; 3]>,
+    /// Specular exponent, `Ns`
+    pub shininess: Option<f32>,
+    /// Index of refraction, `Ni`
+    pub optical_density: Option<f32>,
+    /// Opacity, `d` or the inverse of `Tr`
+    pub dissolve: Option<f32>,
+    /// Illumination model, `illum`
+    pub illumination: Option<u32>,
+    /// `map_Ka`
+    pub ambient_texture: Option<String>,
+    /// `map_Kd`
+    pub diffuse_texture: Option<String>,
+    /// `map_Ks`
+    pub specular_texture: Option<String>,
+    /// `map_Ke`
+    pub emissive_texture: Option<String>,
+    /// `map_d`
+    pub dissolve_texture: Option<String>,
+    /// `norm`, `bump` or `map_Bump`
+    pub normal_texture: Option<String>,
+}
+
+/// A mesh of an OBJ file, a new mesh starts with every object, group & material
+#[derive(Debug)]
+pub struct ObjMesh {
+    /// The name of the group, or of the object if the mesh is not grouped
+    pub name: String,
+    /// Index of the material in `ObjModel::materials`, if the material was found
+    pub material: Option<usize>,
+    /// Indexed triangles with positions, normals & texcoords if every vertex has them, and vertex colors
+    pub mesh: Mesh,
+}
+
+/// The meshes & materials of a Wavefront OBJ file.
+///
+/// Polygons are triangulated, negative indices refer to the vertices read so far. Texcoords are
+/// flipped vertically, textures have their origin at the top left. Material libraries that fail
+/// to load are skipped, meshes using their materials have no material.
+///
+/// Example:
+/// let model = ObjModel::load("assets/teapot.obj")?;
+/// for obj_mesh in model.meshes.iter() {
+///     let material = obj_mesh.material.map(|index| &model.materials[index]);
+///     ...
+/// }
+///
+#[derive(Debug)]
+pub struct ObjModel {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: Vec<ObjMaterial>,
+    /// Errors of material libraries that were skipped
+    pub warnings: Vec<ObjError>,
+}
+
+impl ObjModel {
+    /// Loads the OBJ file, material libraries are loaded relative to it
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ObjError> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        let source = read_file(path)?;
+
+        ObjModel::parse(&source, &path.display().to_string(), |library| {
+            let library_path = directory.join(library);
+            let source = read_file(&library_path)?;
+            parse_mtl(&source, &library_path.display().to_string())
+        })
+    }
+
+    /// Parses the OBJ source, `name` is used in errors. Material libraries are loaded by the given function.
+    pub fn parse<F>(source: &str, name: &str, mut load_library: F) -> Result<Self, ObjError>
+    where
+        F: FnMut(&str) -> Result<Vec<ObjMaterial>, ObjError>,
+    {
+        let mut parser = ObjParser::default();
+        let mut materials = Vec::new();
+        let mut warnings = Vec::new();
+
+        for (number, text) in logical_lines(source) {
+            let location = Location { file: name, line: number };
+            let mut tokens = text.split_whitespace();
+            let keyword = tokens.next().unwrap_or("");
+            let args = tokens.collect::<Vec<&str>>();
+
+            match keyword {
+                "v" => parser.add_position(&location, &args)?,
+                "vt" => parser.add_texcoord(&location, &args)?,
+                "vn" => parser.add_normal(&location, &args)?,
+                "f" => parser.add_face(&location, &args)?,
+                "o" => parser.set_object(args.join(" ")),
+                "g" => parser.set_group(args.join(" ")),
+                "usemtl" => parser.set_material(args.join(" ")),
+                "mtllib" => {
+                    for library in args {
+                        match load_library(library) {
+                            Ok(library) => materials.extend(library),
+                            Err(error) => warnings.push(error),
+                        }
+                    }
+                }
+                // smoothing groups, lines, points & free form geometry are not supported
+                _ => (),
+            }
+        }
+
+        let meshes = parser.finish(&materials);
+        Ok(ObjModel { meshes, materials, warnings })
+    }
+}
+
+/// Parses the materials of an MTL library, `name` is used in errors
+pub fn parse_mtl(source: &str, name: &str) -> Result<Vec<ObjMaterial>, ObjError> {
+    let mut materials: Vec<ObjMaterial> = Vec::new();
+
+    for (number, text) in logical_lines(source) {
+        let location = Location { file: name, line: number };
+        let mut tokens = text.split_whitespace();
+        let keyword = tokens.next().unwrap_or("");
+        let args = tokens.collect::<Vec<&str>>();
+
+        if keyword == "newmtl" {
+            materials.push(ObjMaterial {
+                name: args.join(" "),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let material = materials
+            .last_mut()
+            .ok_or_else(|| location.error(format!("'{}' before first 'newmtl'", keyword)))?;
+
+        match keyword {
+            "Ka" => material.ambient = Some(location.color(&args)?),
+            "Kd" => material.diffuse = Some(location.color(&args)?),
+            "Ks" => material.specular = Some(location.color(&args)?),
+            "Ke" => material.emissive = Some(location.color(&args)?),
+            "Ns" => material.shininess = Some(location.float(&args)?),
+            "Ni" => material.optical_density = Some(location.float(&args)?),
+            "d" => material.dissolve = Some(location.float(&args)?),
+            "Tr" => material.dissolve = Some(1.0 - location.float(&args)?),
+            "illum" => material.illumination = Some(location.parse(args.first().copied().unwrap_or(""))?),
+            "map_Ka" => material.ambient_texture = Some(location.texture(&args)?),
+            "map_Kd" => material.diffuse_texture = Some(location.texture(&args)?),
+            "map_Ks" => material.specular_texture = Some(location.texture(&args)?),
+            "map_Ke" => material.emissive_texture = Some(location.texture(&args)?),
+            "map_d" => material.dissolve_texture = Some(location.texture(&args)?),
+            "norm" | "bump" | "map_Bump" | "map_bump" => material.normal_texture = Some(location.texture(&args)?),
+            _ => (),
+        }
+    }
+
+    Ok(materials)
+}
+
+/// Reads the whole file into a string
+fn read_file(path: &Path) -> Result<String, ObjError> {
+    let display = path.display();
+
+    let mut source = String::new();
+    let mut file = File::open(path).map_err(|_| ObjError::OpenFileFailed(format!("{}", display)))?;
+    file.read_to_string(&mut source).map_err(|_| ObjError::ReadFromFileFailed(format!("{}", display)))?;
+    Ok(source)
+}
+
+/// Returns all non empty lines without comments & their line numbers, lines ending with `\` are joined
+fn logical_lines(source: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut continued: Option<(usize, String)> = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        let (number, mut text) = continued.take().unwrap_or_else(|| (index + 1, String::new()));
+
+        match line.trim_end().strip_suffix('\\') {
+            Some(line) => {
+                text.push_str(line);
+                text.push(' ');
+                continued = Some((number, text));
+            }
+            None => {
+                text.push_str(line);
+                lines.push((number, text));
+            }
+        }
+    }
+    lines.extend(continued);
+
+    lines.retain(|(_, text)| !text.trim().is_empty());
+    lines
+}
+
+/// The position of a statement, used to report errors
+struct Location<'a> {
+    file: &'a str,
+    line: usize,
+}
+
+impl<'a> Location<'a> {
+    fn error(&self, reason: String) -> ObjError {
+        ObjError::InvalidStatement(self.file.to_string(), self.line, reason)
+    }
+
+    fn parse<T: std::str::FromStr>(&self, token: &str) -> Result<T, ObjError> {
+        token.parse().map_err(|_| self.error(format!("Invalid number '{}'", token)))
+    }
+
+    /// Parses all arguments as floats, fails if their number is not in the given range
+    fn floats(&self, args: &[&str], min: usize, max: usize) -> Result<Vec<f32>, ObjError> {
+        if args.len() < min || args.len() > max {
+            return Err(self.error(format!("Expected {} to {} values, found {}", min, max, args.len())));
+        }
+        args.iter().map(|arg| self.parse(arg)).collect()
+    }
+
+    fn float(&self, args: &[&str]) -> Result<f32, ObjError> {
+        Ok(self.floats(args, 1, 1)?[0])
+    }
+
+    /// Parses a color, a single value is used for all channels
+    fn color(&self, args: &[&str]) -> Result<[f32; 3], ObjError> {
+        match self.floats(args, 1, 3)?.as_slice() {
+            [value] => Ok([*value; 3]),
+            [r, g, b] => Ok([*r, *g, *b]),
+            _ => Err(self.error(String::from("Expected 1 or 3 color values"))),
+        }
+    }
+
+    /// Returns the file name of a texture statement, the name follows all options
+    fn texture(&self, args: &[&str]) -> Result<String, ObjError> {
+        args.last()
+            .map(|file| file.to_string())
+            .ok_or_else(|| self.error(String::from("Missing texture file")))
+    }
+
+    /// Resolves a one based or negative relative index into the list of `count` values
+    fn index(&self, token: &str, count: usize) -> Result<usize, ObjError> {
+        let index: i64 = self.parse(token)?;
+        let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+
+        if index == 0 || resolved < 0 || resolved >= count as i64 {
+            return Err(ObjError::IndexOutOfRange(self.file.to_string(), self.line, index));
+        }
+        Ok(resolved as usize)
+    }
+}
+
+/// A corner of a face, references the vertex data by index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ObjVertex {
+    position: usize,
+    texcoord: Option<usize>,
+    normal: Option<usize>,
+}
+
+/// Collects the triangles of a single mesh, vertices with the same indices are shared
+#[derive(Default)]
+struct MeshBuilder {
+    name: String,
+    material: Option<String>,
+    vertices: Vec<ObjVertex>,
+    lookup: HashMap<ObjVertex, u32>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    fn add_vertex(&mut self, vertex: ObjVertex) {
+        let vertices = &mut self.vertices;
+        let index = *self.lookup.entry(vertex).or_insert_with(|| {
+            vertices.push(vertex);
+            (vertices.len() - 1) as u32
+        });
+        self.indices.push(index);
+    }
+}
+
+/// The state while parsing an OBJ file, vertex data is shared by all meshes
+#[derive(Default)]
+struct ObjParser {
+    positions: Vec<[f32; 3]>,
+    colors: Vec<Option<[f32; 4]>>,
+    texcoords: Vec<[f32; 2]>,
+    normals: Vec<[f32; 3]>,
+    object: Option<String>,
+    group: Option<String>,
+    material: Option<String>,
+    current: MeshBuilder,
+    meshes: Vec<MeshBuilder>,
+}
+
+impl ObjParser {
+    /// Parses `v x y z [w]` or `v x y z r g b`
+    fn add_position(&mut self, location: &Location, args: &[&str]) -> Result<(), ObjError> {
+        let values = location.floats(args, 3, 6)?;
+        self.positions.push([values[0], values[1], values[2]]);
+        self.colors.push(match values.as_slice() {
+            [_, _, _, r, g, b] => Some([*r, *g, *b, 1.0]),
+            [_, _, _, _, _] => return Err(location.error(String::from("Expected 3, 4 or 6 values"))),
+            _ => None,
+        });
+        Ok(())
+    }
+
+    /// Parses `vt u [v] [w]`, v is flipped
+    fn add_texcoord(&mut self, location: &Location, args: &[&str]) -> Result<(), ObjError> {
+        let values = location.floats(args, 1, 3)?;
+        self.texcoords.push([values[0], 1.0 - values.get(1).copied().unwrap_or(0.0)]);
+        Ok(())
+    }
+
+    /// Parses `vn x y z`
+    fn add_normal(&mut self, location: &Location, args: &[&str]) -> Result<(), ObjError> {
+        let values = location.floats(args, 3, 3)?;
+        self.normals.push([values[0], values[1], values[2]]);
+        Ok(())
+    }
+
+    /// Parses a face of vertices like `v`, `v/vt`, `v//vn` or `v/vt/vn` and triangulates it
+    fn add_face(&mut self, location: &Location, args: &[&str]) -> Result<(), ObjError> {
+        if args.len() < 3 {
+            return Err(location.error(format!("Face needs at least 3 vertices, found {}", args.len())));
+        }
+
+        let vertices = args
+            .iter()
+            .map(|arg| self.parse_vertex(location, arg))
+            .collect::<Result<Vec<ObjVertex>, ObjError>>()?;
+        let points = vertices
+            .iter()
+            .map(|vertex| Vector3::from(self.positions[vertex.position]))
+            .collect::<Vec<Vector3<f32>>>();
+
+        for triangle in triangulate(&points).iter() {
+            for &corner in triangle.iter() {
+                self.current.add_vertex(vertices[corner]);
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_vertex(&self, location: &Location, arg: &str) -> Result<ObjVertex, ObjError> {
+        let parts = arg.split('/').collect::<Vec<&str>>();
+        if parts.len() > 3 {
+            return Err(location.error(format!("Invalid face vertex '{}'", arg)));
+        }
+
+        let optional_index = |part: Option<&&str>, count: usize| match part {
+            Some(part) if !part.is_empty() => location.index(part, count).map(Some),
+            _ => Ok(None),
+        };
+
+        Ok(ObjVertex {
+            position: location.index(parts[0], self.positions.len())?,
+            texcoord: optional_index(parts.get(1), self.texcoords.len())?,
+            normal: optional_index(parts.get(2), self.normals.len())?,
+        })
+    }
+
+    fn set_object(&mut self, name: String) {
+        self.object = Some(name);
+        self.group = None;
+        self.start_mesh();
+    }
+
+    fn set_group(&mut self, name: String) {
+        self.group = Some(name).filter(|name| !name.is_empty());
+        self.start_mesh();
+    }
+
+    fn set_material(&mut self, name: String) {
+        self.material = Some(name);
+        self.start_mesh();
+    }
+
+    /// Finishes the current mesh, the following faces are added to a new mesh
+    fn start_mesh(&mut self) {
+        let name = self.group.as_ref().or(self.object.as_ref()).cloned();
+        let next = MeshBuilder {
+            name: name.unwrap_or_else(|| String::from("default")),
+            material: self.material.clone(),
+            ..Default::default()
+        };
+
+        let previous = std::mem::replace(&mut self.current, next);
+        if !previous.indices.is_empty() {
+            self.meshes.push(previous);
+        }
+    }
+
+    /// Creates the meshes, attributes that are missing for some vertices are skipped
+    fn finish(mut self, materials: &[ObjMaterial]) -> Vec<ObjMesh> {
+        self.start_mesh();
+
+        let ObjParser { positions, colors, texcoords, normals, meshes, .. } = self;
+        meshes
+            .into_iter()
+            .map(|builder| {
+                let vertices = &builder.vertices;
+                let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+                mesh.attributes.push(VertexAttribute::positions(
+                    vertices.iter().map(|vertex| positions[vertex.position]).collect(),
+                ));
+                if let Some(normals) = vertices.iter().map(|vertex| vertex.normal.map(|index| normals[index])).collect() {
+                    mesh.attributes.push(VertexAttribute::normals(normals));
+                }
+                if let Some(texcoords) = vertices.iter().map(|vertex| vertex.texcoord.map(|index| texcoords[index])).collect() {
+                    mesh.attributes.push(VertexAttribute::texcoords(texcoords));
+                }
+                if let Some(colors) = vertices.iter().map(|vertex| colors[vertex.position]).collect::<Option<Vec<[f32; 4]>>>() {
+                    mesh.attributes.push(VertexAttribute::new("colors", colors));
+                }
+                mesh.indices = Some(Indices::U32(builder.indices));
+
+                ObjMesh {
+                    name: builder.name,
+                    material: builder.material.and_then(|name| materials.iter().position(|material| material.name == name)),
+                    mesh,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Splits the polygon into triangles by clipping ears in the plane of the polygon, polygons
+/// without ears, e.g. self intersecting ones, are split into a triangle fan
+fn triangulate(points: &[Vector3<f32>]) -> Vec<[usize; 3]> {
+    let mut remaining = (0..points.len()).collect::<Vec<usize>>();
+    let mut triangles = Vec::new();
+
+    // Newell's method, the normal follows the counter clockwise winding of the polygon
+    let normal = (0..points.len()).fold(Vector3::new(0.0, 0.0, 0.0), |normal, i| {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        normal + Vector3::new((a.y - b.y) * (a.z + b.z), (a.z - b.z) * (a.x + b.x), (a.x - b.x) * (a.y + b.y))
+    });
+
+    if points.len() > 3 && normal.magnitude2() > 0.0 {
+        let normal = normal.normalize();
+        let axis = if normal.x.abs() < 0.9 { Vector3::unit_x() } else { Vector3::unit_y() };
+        let u = normal.cross(axis).normalize();
+        let v = normal.cross(u);
+        let projected = points
+            .iter()
+            .map(|point| Vector2::new(point.dot(u), point.dot(v)))
+            .collect::<Vec<Vector2<f32>>>();
+
+        while remaining.len() > 3 {
+            let count = remaining.len();
+            match find_ear(&projected, &remaining) {
+                Some(ear) => {
+                    triangles.push([remaining[(ear + count - 1) % count], remaining[ear], remaining[(ear + 1) % count]]);
+                    remaining.remove(ear);
+                }
+                None => break,
+            }
+        }
+    }
+
+    for i in 1..remaining.len() - 1 {
+        triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+    }
+    triangles
+}
+
+/// Returns a convex corner whose triangle contains no other corner of the polygon. The search starts
+/// at the second corner, which splits convex polygons into a fan around the first corner.
+fn find_ear(points: &[Vector2<f32>], remaining: &[usize]) -> Option<usize> {
+    let count = remaining.len();
+    (1..=count).map(|i| i % count).find(|&i| {
+        let (a, b, c) = (remaining[(i + count - 1) % count], remaining[i], remaining[(i + 1) % count]);
+        let convex = (points[b] - points[a]).perp_dot(points[c] - points[b]) > 0.0;
+
+        convex && remaining
+            .iter()
+            .filter(|&&other| other != a && other != b && other != c)
+            .all(|&other| !in_triangle(points[other], [points[a], points[b], points[c]]))
+    })
+}
+
+/// Returns true if the point is inside or on the edges of the counter clockwise triangle
+fn in_triangle(point: Vector2<f32>, [a, b, c]: [Vector2<f32>; 3]) -> bool {
+    (b - a).perp_dot(point - a) >= 0.0 && (c - b).perp_dot(point - b) >= 0.0 && (a - c).perp_dot(point - c) >= 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Indices, Mesh, ObjError, ObjMaterial, ObjModel, VertexAttributeValues, parse_mtl};
+
+    const MATERIALS: &str = r#"
+    # two materials
+    newmtl red
+    Kd 1.0 0.0 0.0
+    Ks 0.5
+    Ns 32
+    d 0.75
+    illum 2
+    map_Kd -s 1 1 1 textures/red.png
+
+    newmtl blue
+    Kd 0.0 0.0 1.0
+    Tr 0.25
+    bump normals.png
+    "#;
+
+    const MODEL: &str = r#"
+    mtllib materials.mtl
+    o cube
+    v -1 -1 1
+    v 1 -1 1
+    v 1 1 1
+    v -1 1 1
+    vt 0 0
+    vt 1 0
+    vt 1 1
+    vt 0 1
+    vn 0 0 1
+    usemtl red
+    f 1/1/1 2/2/1 3/3/1 4/4/1
+    g back
+    usemtl blue
+    v -1 -1 -1 1 0 0
+    v -1 1 -1 0 1 0
+    v 1 1 -1 0 0 1
+    f -3// -2 \
+      -1
+    g missing
+    usemtl unknown
+    f 1 2 3
+    "#;
+
+    fn load_library(library: &str) -> Result<Vec<ObjMaterial>, ObjError> {
+        assert_eq!("materials.mtl", library);
+        parse_mtl(MATERIALS, library)
+    }
+
+    fn indices(mesh: &Mesh) -> &Vec<u32> {
+        match &mesh.indices {
+            Some(Indices::U32(indices)) => indices,
+            indices => panic!("Unexpected indices {:?}", indices),
+        }
+    }
+
+    #[test]
+    fn it_parses_materials() {
+        let materials = parse_mtl(MATERIALS, "materials.mtl").unwrap();
+
+        assert_eq!(2, materials.len());
+        assert_eq!(
+            ObjMaterial {
+                name: "red".into(),
+                diffuse: Some([1.0, 0.0, 0.0]),
+                specular: Some([0.5, 0.5, 0.5]),
+                shininess: Some(32.0),
+                dissolve: Some(0.75),
+                illumination: Some(2),
+                diffuse_texture: Some("textures/red.png".into()),
+                ..Default::default()
+            },
+            materials[0],
+        );
+        assert_eq!(Some(0.75), materials[1].dissolve);
+        assert_eq!(Some("normals.png".to_string()), materials[1].normal_texture);
+    }
+
+    #[test]
+    fn it_parses_objects_groups_and_materials() {
+        let model = ObjModel::parse(MODEL, "model.obj", load_library).unwrap();
+
+        assert_eq!(2, model.materials.len());
+        assert_eq!(3, model.meshes.len());
+
+        let front = &model.meshes[0];
+        assert_eq!("cube", front.name);
+        assert_eq!(Some(0), front.material);
+        assert_eq!(4, front.mesh.vertex_count());
+        assert_eq!(&vec![0, 1, 2, 0, 2, 3], indices(&front.mesh));
+        // texcoords are flipped vertically
+        assert_eq!(
+            VertexAttributeValues::Float2(vec![[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]]),
+            front.mesh.attribute("texcoords").unwrap().values,
+        );
+        assert!(front.mesh.attribute("normals").is_some());
+        assert!(front.mesh.attribute("colors").is_none());
+
+        // negative indices refer to the vertices read so far, the face continues on the next line
+        let back = &model.meshes[1];
+        assert_eq!("back", back.name);
+        assert_eq!(Some(1), back.material);
+        assert_eq!(
+            VertexAttributeValues::Float3(vec![[-1.0, -1.0, -1.0], [-1.0, 1.0, -1.0], [1.0, 1.0, -1.0]]),
+            back.mesh.attribute("positions").unwrap().values,
+        );
+        assert_eq!(
+            VertexAttributeValues::Float4(vec![[1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]]),
+            back.mesh.attribute("colors").unwrap().values,
+        );
+        assert!(back.mesh.attribute("normals").is_none());
+        assert!(back.mesh.attribute("texcoords").is_none());
+
+        assert_eq!("missing", model.meshes[2].name);
+        assert_eq!(None, model.meshes[2].material);
+    }
+
+    #[test]
+    fn it_triangulates_concave_polygons() {
+        // an L shape, a fan around the first vertex would cover the notch
+        let source = r#"
+        v 0 0 0
+        v 2 0 0
+        v 2 1 0
+        v 1 1 0
+        v 1 2 0
+        v 0 2 0
+        f 3 4 5 6 1 2
+        "#;
+        let model = ObjModel::parse(source, "shape.obj", |_| Ok(Vec::new())).unwrap();
+        let mesh = &model.meshes[0].mesh;
+        let positions = match &mesh.attribute("positions").unwrap().values {
+            VertexAttributeValues::Float3(positions) => positions,
+            values => panic!("Unexpected positions {:?}", values),
+        };
+
+        let triangles = indices(mesh);
+        assert_eq!(4 * 3, triangles.len());
+
+        // all triangles are counter clockwise & cover exactly the area of the polygon
+        let area: f32 = triangles
+            .chunks_exact(3)
+            .map(|triangle| {
+                let [a, b, c] = [positions[triangle[0] as usize], positions[triangle[1] as usize], positions[triangle[2] as usize]];
+                let area = ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])) / 2.0;
+                assert!(area > 0.0, "Triangle {:?} is not counter clockwise", triangle);
+                area
+            })
+            .sum();
+        assert!((area - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn it_reports_errors_with_line_numbers() {
+        let parse = |source: &str| ObjModel::parse(source, "broken.obj", |_| Ok(Vec::new())).map(|_| ());
+
+        assert_eq!(
+            Err(ObjError::InvalidStatement("broken.obj".into(), 3, "Invalid number 'one'".into())),
+            parse("v 0 0 0\n\nv one 0 0"),
+        );
+        assert_eq!(
+            Err(ObjError::IndexOutOfRange("broken.obj".into(), 4, 4)),
+            parse("v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 2 4"),
+        );
+        assert_eq!(
+            Err(ObjError::IndexOutOfRange("broken.obj".into(), 2, -2)),
+            parse("v 0 0 0\nf 1 -2 1"),
+        );
+        assert_eq!(
+            Err(ObjError::InvalidStatement("broken.obj".into(), 2, "Face needs at least 3 vertices, found 2".into())),
+            parse("v 0 0 0\nf 1 1"),
+        );
+        assert_eq!(
+            Err(ObjError::InvalidStatement("materials.mtl".into(), 1, "'Kd' before first 'newmtl'".into())),
+            parse_mtl("Kd 1 1 1", "materials.mtl").map(|_| ()),
+        );
+        assert_eq!(
+            Err(ObjError::OpenFileFailed("missing.obj".into())),
+            ObjModel::load("missing.obj").map(|_| ()),
+        );
+    }
+
+    #[test]
+    fn it_skips_missing_material_libraries() {
+        let missing = ObjError::OpenFileFailed("materials.mtl".into());
+        let model = ObjModel::parse(MODEL, "model.obj", |_| Err(missing.clone())).unwrap();
+
+        assert!(model.materials.is_empty());
+        assert_eq!(vec![missing], model.warnings);
+        assert_eq!(3, model.meshes.len());
+        assert!(model.meshes.iter().all(|obj_mesh| obj_mesh.material.is_none()));
+
+        let dir = std::env::temp_dir().join("renderer_obj_missing_mtl");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("model.obj"), MODEL).unwrap();
+
+        let model = ObjModel::load(dir.join("model.obj")).unwrap();
+        assert_eq!(vec![ObjError::OpenFileFailed(dir.join("materials.mtl").display().to_string())], model.warnings);
+        assert_eq!(3, model.meshes.len());
+    }
+}
@@ -0,0 +1,86 @@
+use std::f32::consts::PI;
+
+use crate::Mesh;
+
+use super::{sphere::pole_to_pole_indices, triangle_mesh};
+
+/// A cylinder along the Y axis with hemispheres at both ends
+pub struct Capsule {
+    /// Radius of the cylinder & hemispheres
+    pub radius: f32,
+    /// Height of the cylinder, the total height includes both hemispheres
+    pub height: f32,
+    /// Number of segments around the Y axis
+    pub sectors: u32,
+    /// Number of segments of each hemisphere from pole to equator
+    pub rings: u32,
+}
+
+impl Capsule {
+    pub fn new(radius: f32, height: f32) -> Self {
+        Capsule {
+            radius,
+            height,
+            sectors: 32,
+            rings: 8,
+        }
+    }
+
+    /// Sets the number of segments around the Y axis & of each hemisphere
+    pub fn with_resolution(mut self, sectors: u32, rings: u32) -> Self {
+        self.sectors = sectors.max(3);
+        self.rings = rings.max(1);
+        self
+    }
+}
+
+impl Default for Capsule {
+    fn default() -> Self {
+        Capsule::new(0.5, 1.0)
+    }
+}
+
+impl From<Capsule> for Mesh {
+    fn from(capsule: Capsule) -> Self {
+        let (sectors, rings) = (capsule.sectors.max(3), capsule.rings.max(1));
+        let half_height = capsule.height / 2.0;
+        let total_height = capsule.height + 2.0 * capsule.radius;
+
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut texcoords = Vec::new();
+
+        // both hemispheres contain the equator, the side of the cylinder connects them
+        for row in 0..2 * (rings + 1) {
+            let (ring, offset) = if row <= rings { (row, half_height) } else { (row - 1, -half_height) };
+            let (ring_radius, y) = (ring as f32 / rings as f32 * PI / 2.0).sin_cos();
+
+            for sector in 0..=sectors {
+                let u = sector as f32 / sectors as f32;
+                let (sin, cos) = (u * 2.0 * PI).sin_cos();
+                let normal = [ring_radius * cos, y, -ring_radius * sin];
+                let position_y = offset + capsule.radius * y;
+                positions.push([capsule.radius * normal[0], position_y, capsule.radius * normal[2]]);
+                normals.push(normal);
+                texcoords.push([u, (half_height + capsule.radius - position_y) / total_height]);
+            }
+        }
+
+        let indices = pole_to_pole_indices(2 * rings + 1, sectors);
+        triangle_mesh(positions, normals, texcoords, indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Mesh, shape::{Capsule, tests::assert_valid_mesh}};
+
+    #[test]
+    fn it_creates_capsules() {
+        let capsule: Mesh = Capsule::new(0.5, 2.0).with_resolution(16, 4).into();
+
+        assert_eq!(2 * 5 * 17, capsule.vertex_count());
+        assert_eq!(12 * 16 * 4, capsule.indices.as_ref().unwrap().len());
+        assert_valid_mesh(&capsule);
+    }
+}
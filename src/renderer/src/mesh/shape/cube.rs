@@ -0,0 +1,152 @@
+use crate::{Indices, Mesh, PrimitiveTopology, VertexAttribute};
+
+pub struct Cube {
+    /// Half the side length of the cube
+    pub size: f32,
+}
+
+impl Cube {
+    pub fn new(size: f32) -> Self {
+        Cube { size }
+    }
+}
+
+impl Default for Cube {
+    fn default() -> Self {
+        Cube { size: 1.0, }
+    }
+}
+
+impl From<Cube> for Mesh {
+    fn from(cube: Cube) -> Self {
+        let half = cube.size / 2.0;
+        let vertices = vec![
+            [-half, -half,  half],
+            [ half, -half,  half],
+            [ half,  half,  half],
+            [-half,  half,  half],
+
+            [-half, -half, -half],
+            [-half,  half, -half],
+            [ half,  half, -half],
+            [ half, -half, -half],
+
+            [-half,  half, -half],
+            [-half,  half,  half],
+            [ half,  half,  half],
+            [ half,  half, -half],
+
+            [-half, -half, -half],
+            [ half, -half, -half],
+            [ half, -half,  half],
+            [-half, -half,  half],
+
+            [ half, -half, -half],
+            [ half,  half, -half],
+            [ half,  half,  half],
+            [ half, -half,  half],
+
+            [-half, -half, -half],
+            [-half, -half,  half],
+            [-half,  half,  half],
+            [-half,  half, -half],
+        ];
+
+        let normals = vec![
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0],
+
+            [0.0, 0.0, -1.0],
+            [0.0, 0.0, -1.0],
+            [0.0, 0.0, -1.0],
+            [0.0, 0.0, -1.0],
+
+            [0.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+
+            [0.0, -1.0, 0.0],
+            [0.0, -1.0, 0.0],
+            [0.0, -1.0, 0.0],
+            [0.0, -1.0, 0.0],
+
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+
+            [-1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0],
+        ];
+
+        let texcoords = vec![
+            [0.0,  0.0],
+            [1.0,  0.0],
+            [1.0,  1.0],
+            [0.0,  1.0],
+
+            [0.0,  0.0],
+            [1.0,  0.0],
+            [1.0,  1.0],
+            [0.0,  1.0],
+
+            [0.0,  0.0],
+            [1.0,  0.0],
+            [1.0,  1.0],
+            [0.0,  1.0],
+
+            [0.0,  0.0],
+            [1.0,  0.0],
+            [1.0,  1.0],
+            [0.0,  1.0],
+
+            [0.0,  0.0],
+            [1.0,  0.0],
+            [1.0,  1.0],
+            [0.0,  1.0],
+
+            [0.0,  0.0],
+            [1.0,  0.0],
+            [1.0,  1.0],
+            [0.0,  1.0],
+        ];
+
+        let indices = Indices::U32(vec![
+            0,  1,  2,    0,  2,  3,  // Front face
+            4,  5,  6,    4,  6,  7,  // Back face
+            8,  9, 10,    8, 10, 11,  // Top face
+           12, 13, 14,   12, 14, 15,  // Bottom face
+           16, 17, 18,   16, 18, 19,  // Right face
+           20, 21, 22,   20, 22, 23   // Left face
+        ]);
+
+        Mesh {
+            topology: PrimitiveTopology::TriangleList,
+            attributes: vec![
+                VertexAttribute::positions(vertices),
+                VertexAttribute::normals(normals),
+                VertexAttribute::texcoords(texcoords),
+            ],
+            indices: Some(indices),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Mesh, shape::{Cube, tests::assert_valid_mesh}};
+
+    #[test]
+    fn it_creates_cubes() {
+        let cube: Mesh = Cube::new(2.0).into();
+
+        assert_eq!(24, cube.vertex_count());
+        assert_eq!(36, cube.indices.as_ref().unwrap().len());
+        assert_valid_mesh(&cube);
+    }
+}
@@ -0,0 +1,199 @@
+use std::f32::consts::PI;
+
+use crate::Mesh;
+
+use super::{grid_quad, triangle_mesh};
+
+/// A cylinder along the Y axis, closed by a cap at both ends
+pub struct Cylinder {
+    /// Radius of the cylinder
+    pub radius: f32,
+    /// Height of the cylinder
+    pub height: f32,
+    /// Number of segments around the Y axis
+    pub sectors: u32,
+    /// Number of segments along the Y axis
+    pub segments: u32,
+}
+
+impl Cylinder {
+    pub fn new(radius: f32, height: f32) -> Self {
+        Cylinder {
+            radius,
+            height,
+            sectors: 32,
+            segments: 1,
+        }
+    }
+
+    /// Sets the number of segments around & along the Y axis
+    pub fn with_resolution(mut self, sectors: u32, segments: u32) -> Self {
+        self.sectors = sectors.max(3);
+        self.segments = segments.max(1);
+        self
+    }
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Cylinder::new(0.5, 1.0)
+    }
+}
+
+impl From<Cylinder> for Mesh {
+    fn from(cylinder: Cylinder) -> Self {
+        let mut builder = FrustumBuilder::default();
+        let (sectors, segments) = (cylinder.sectors.max(3), cylinder.segments.max(1));
+        builder.side(cylinder.radius, cylinder.radius, cylinder.height, sectors, segments);
+        builder.cap(cylinder.height / 2.0, cylinder.radius, sectors, true);
+        builder.cap(-cylinder.height / 2.0, cylinder.radius, sectors, false);
+        builder.into()
+    }
+}
+
+/// A cone along the Y axis with its tip at the top, closed by a cap at the bottom
+pub struct Cone {
+    /// Radius at the bottom of the cone
+    pub radius: f32,
+    /// Height of the cone
+    pub height: f32,
+    /// Number of segments around the Y axis
+    pub sectors: u32,
+    /// Number of segments along the Y axis
+    pub segments: u32,
+}
+
+impl Cone {
+    pub fn new(radius: f32, height: f32) -> Self {
+        Cone {
+            radius,
+            height,
+            sectors: 32,
+            segments: 1,
+        }
+    }
+
+    /// Sets the number of segments around & along the Y axis
+    pub fn with_resolution(mut self, sectors: u32, segments: u32) -> Self {
+        self.sectors = sectors.max(3);
+        self.segments = segments.max(1);
+        self
+    }
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Cone::new(0.5, 1.0)
+    }
+}
+
+impl From<Cone> for Mesh {
+    fn from(cone: Cone) -> Self {
+        let mut builder = FrustumBuilder::default();
+        let (sectors, segments) = (cone.sectors.max(3), cone.segments.max(1));
+        builder.side(0.0, cone.radius, cone.height, sectors, segments);
+        builder.cap(-cone.height / 2.0, cone.radius, sectors, false);
+        builder.into()
+    }
+}
+
+/// Collects the vertices of cylinders & cones
+#[derive(Default)]
+struct FrustumBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    texcoords: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+impl FrustumBuilder {
+    /// Adds the side between the top & bottom radius, a top radius of 0 ends in a tip
+    fn side(&mut self, top_radius: f32, bottom_radius: f32, height: f32, sectors: u32, segments: u32) {
+        let first = self.positions.len() as u32;
+        let slope = bottom_radius - top_radius;
+        let normal_length = (height * height + slope * slope).sqrt();
+
+        for segment in 0..=segments {
+            let v = segment as f32 / segments as f32;
+            let radius = top_radius + slope * v;
+            let y = height / 2.0 - height * v;
+            for sector in 0..=sectors {
+                let u = sector as f32 / sectors as f32;
+                let (sin, cos) = (u * 2.0 * PI).sin_cos();
+                self.positions.push([radius * cos, y, -radius * sin]);
+                self.normals.push([
+                    height * cos / normal_length,
+                    slope / normal_length,
+                    -height * sin / normal_length,
+                ]);
+                self.texcoords.push([u, v]);
+            }
+        }
+
+        for segment in 0..segments {
+            for sector in 0..sectors {
+                let quad = grid_quad(segment, sector, sectors + 1);
+                // the first triangle of a quad at the tip is degenerate
+                if segment != 0 || top_radius != 0.0 {
+                    self.indices.extend(quad[..3].iter().map(|index| first + index));
+                }
+                self.indices.extend(quad[3..].iter().map(|index| first + index));
+            }
+        }
+    }
+
+    /// Adds a disc at the given height, facing up or down
+    fn cap(&mut self, y: f32, radius: f32, sectors: u32, facing_up: bool) {
+        let center = self.positions.len() as u32;
+        let normal = if facing_up { [0.0, 1.0, 0.0] } else { [0.0, -1.0, 0.0] };
+
+        self.positions.push([0.0, y, 0.0]);
+        self.normals.push(normal);
+        self.texcoords.push([0.5, 0.5]);
+
+        for sector in 0..=sectors {
+            let (sin, cos) = (sector as f32 / sectors as f32 * 2.0 * PI).sin_cos();
+            self.positions.push([radius * cos, y, -radius * sin]);
+            self.normals.push(normal);
+            self.texcoords.push([0.5 + 0.5 * cos, 0.5 - 0.5 * sin]);
+        }
+
+        for sector in 0..sectors {
+            let (current, next) = (center + 1 + sector, center + 2 + sector);
+            if facing_up {
+                self.indices.extend_from_slice(&[center, current, next]);
+            } else {
+                self.indices.extend_from_slice(&[center, next, current]);
+            }
+        }
+    }
+}
+
+impl From<FrustumBuilder> for Mesh {
+    fn from(builder: FrustumBuilder) -> Self {
+        triangle_mesh(builder.positions, builder.normals, builder.texcoords, builder.indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Mesh, shape::{Cone, Cylinder, tests::assert_valid_mesh}};
+
+    #[test]
+    fn it_creates_cylinders() {
+        let cylinder: Mesh = Cylinder::new(1.0, 2.0).with_resolution(12, 3).into();
+
+        assert_eq!(4 * 13 + 2 * 14, cylinder.vertex_count());
+        assert_eq!(6 * 12 * 3 + 2 * 3 * 12, cylinder.indices.as_ref().unwrap().len());
+        assert_valid_mesh(&cylinder);
+    }
+
+    #[test]
+    fn it_creates_cones() {
+        let cone: Mesh = Cone::new(1.0, 2.0).with_resolution(12, 2).into();
+
+        assert_eq!(3 * 13 + 14, cone.vertex_count());
+        assert_eq!(6 * 12 * 2 - 3 * 12 + 3 * 12, cone.indices.as_ref().unwrap().len());
+        assert_valid_mesh(&cone);
+    }
+}
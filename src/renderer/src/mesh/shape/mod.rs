@@ -0,0 +1,103 @@
+//! Procedural shapes, each converts into an indexed `Mesh` with positions, normals & texcoords.
+//!
+//! All shapes are centered at the origin with Y up, front faces are counter clockwise.
+
+pub mod capsule;
+pub mod cube;
+pub mod cylinder;
+pub mod plane;
+pub mod quad;
+pub mod sphere;
+pub mod torus;
+
+pub use capsule::*;
+pub use cube::*;
+pub use cylinder::*;
+pub use plane::*;
+pub use quad::*;
+pub use sphere::*;
+pub use torus::*;
+
+use crate::{Indices, Mesh, PrimitiveTopology, VertexAttribute};
+
+/// Creates an indexed triangle list from the generated vertices
+fn triangle_mesh(
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    texcoords: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+) -> Mesh {
+    Mesh {
+        topology: PrimitiveTopology::TriangleList,
+        attributes: vec![
+            VertexAttribute::positions(positions),
+            VertexAttribute::normals(normals),
+            VertexAttribute::texcoords(texcoords),
+        ],
+        indices: Some(Indices::U32(indices)),
+    }
+}
+
+/// Returns the indices of two triangles for the quad of a grid, `columns` is the number of vertices per row
+fn grid_quad(row: u32, column: u32, columns: u32) -> [u32; 6] {
+    let top_left = row * columns + column;
+    let bottom_left = top_left + columns;
+    [top_left, bottom_left, top_left + 1, top_left + 1, bottom_left, bottom_left + 1]
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{Indices, Mesh, VertexAttributeValues};
+
+    /// Checks that all indices reference vertices, all normals have unit length and all
+    /// triangles face in the direction of their vertex normals
+    pub(crate) fn assert_valid_mesh(mesh: &Mesh) {
+        let positions = match &mesh.attribute("positions").unwrap().values {
+            VertexAttributeValues::Float3(positions) => positions,
+            values => panic!("Unexpected positions {:?}", values),
+        };
+        let normals = match &mesh.attribute("normals").unwrap().values {
+            VertexAttributeValues::Float3(normals) => normals,
+            values => panic!("Unexpected normals {:?}", values),
+        };
+        let indices = match &mesh.indices {
+            Some(Indices::U32(indices)) => indices,
+            indices => panic!("Unexpected indices {:?}", indices),
+        };
+
+        assert_eq!(mesh.vertex_count(), mesh.attribute("texcoords").unwrap().values.len());
+        assert_eq!(0, indices.len() % 3);
+        assert!(indices.iter().all(|index| (*index as usize) < positions.len()));
+
+        for normal in normals.iter() {
+            let length = dot(*normal, *normal).sqrt();
+            assert!((length - 1.0).abs() < 1e-4, "Normal {:?} has length {}", normal, length);
+        }
+
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [positions[triangle[0] as usize], positions[triangle[1] as usize], positions[triangle[2] as usize]];
+            let face_normal = cross(sub(b, a), sub(c, a));
+            if dot(face_normal, face_normal) < 1e-12 {
+                continue;
+            }
+            for index in triangle {
+                assert!(
+                    dot(face_normal, normals[*index as usize]) > 0.0,
+                    "Triangle {:?} faces away from its normals", triangle,
+                );
+            }
+        }
+    }
+
+    fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+        [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+    }
+
+    fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+    }
+
+    fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+        [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+    }
+}
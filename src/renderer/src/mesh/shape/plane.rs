@@ -0,0 +1,78 @@
+use crate::Mesh;
+
+use super::{grid_quad, triangle_mesh};
+
+/// A plane in the XZ plane facing up, subdivided into a grid of quads
+pub struct Plane {
+    /// Size along the X axis
+    pub width: f32,
+    /// Size along the Z axis
+    pub depth: f32,
+    /// Number of quads along each side
+    pub subdivisions: u32,
+}
+
+impl Plane {
+    pub fn new(width: f32, depth: f32) -> Self {
+        Plane {
+            width,
+            depth,
+            subdivisions: 1,
+        }
+    }
+
+    /// Sets the number of quads along each side
+    pub fn with_subdivisions(mut self, subdivisions: u32) -> Self {
+        self.subdivisions = subdivisions.max(1);
+        self
+    }
+}
+
+impl Default for Plane {
+    fn default() -> Self {
+        Plane::new(1.0, 1.0)
+    }
+}
+
+impl From<Plane> for Mesh {
+    fn from(plane: Plane) -> Self {
+        let subdivisions = plane.subdivisions.max(1);
+        let columns = subdivisions + 1;
+
+        let mut positions = Vec::new();
+        let mut texcoords = Vec::new();
+        for row in 0..=subdivisions {
+            let v = row as f32 / subdivisions as f32;
+            for column in 0..=subdivisions {
+                let u = column as f32 / subdivisions as f32;
+                positions.push([(u - 0.5) * plane.width, 0.0, (v - 0.5) * plane.depth]);
+                texcoords.push([u, v]);
+            }
+        }
+        let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+
+        let indices = (0..subdivisions)
+            .flat_map(|row| (0..subdivisions).flat_map(move |column| grid_quad(row, column, columns).to_vec()))
+            .collect();
+
+        triangle_mesh(positions, normals, texcoords, indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Mesh, shape::{Plane, tests::assert_valid_mesh}};
+
+    #[test]
+    fn it_creates_subdivided_planes() {
+        let plane: Mesh = Plane::new(2.0, 1.0).with_subdivisions(4).into();
+
+        assert_eq!(25, plane.vertex_count());
+        assert_eq!(4 * 4 * 6, plane.indices.as_ref().unwrap().len());
+        assert_valid_mesh(&plane);
+
+        let plane: Mesh = Plane::default().into();
+        assert_eq!(4, plane.vertex_count());
+        assert_valid_mesh(&plane);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::Mesh;
+
+use super::triangle_mesh;
+
+/// A rectangle in the XY plane facing the viewer (+Z), e.g. for sprites & billboards
+pub struct Quad {
+    /// Size along the X axis
+    pub width: f32,
+    /// Size along the Y axis
+    pub height: f32,
+}
+
+impl Quad {
+    pub fn new(width: f32, height: f32) -> Self {
+        Quad { width, height }
+    }
+}
+
+impl Default for Quad {
+    fn default() -> Self {
+        Quad::new(1.0, 1.0)
+    }
+}
+
+impl From<Quad> for Mesh {
+    fn from(quad: Quad) -> Self {
+        let (x, y) = (quad.width / 2.0, quad.height / 2.0);
+        let positions = vec![[-x, -y, 0.0], [x, -y, 0.0], [x, y, 0.0], [-x, y, 0.0]];
+        let texcoords = vec![[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+        let normals = vec![[0.0, 0.0, 1.0]; 4];
+
+        triangle_mesh(positions, normals, texcoords, vec![0, 1, 2, 0, 2, 3])
+    }
+}
+
+/// A single triangle that covers the whole screen in clip space, used for post processing.
+///
+/// The texcoords of the visible area are in range `[0, 1]`, with `(0, 0)` at the top left.
+#[derive(Default)]
+pub struct FullscreenTriangle;
+
+impl From<FullscreenTriangle> for Mesh {
+    fn from(_: FullscreenTriangle) -> Self {
+        let positions = vec![[-1.0, -1.0, 0.0], [3.0, -1.0, 0.0], [-1.0, 3.0, 0.0]];
+        let texcoords = vec![[0.0, 1.0], [2.0, 1.0], [0.0, -1.0]];
+        let normals = vec![[0.0, 0.0, 1.0]; 3];
+
+        triangle_mesh(positions, normals, texcoords, vec![0, 1, 2])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Mesh, shape::{FullscreenTriangle, Quad, tests::assert_valid_mesh}};
+
+    #[test]
+    fn it_creates_quads_and_fullscreen_triangles() {
+        let quad: Mesh = Quad::new(2.0, 1.0).into();
+        assert_eq!(4, quad.vertex_count());
+        assert_eq!(6, quad.indices.as_ref().unwrap().len());
+        assert_valid_mesh(&quad);
+
+        let triangle: Mesh = FullscreenTriangle.into();
+        assert_eq!(3, triangle.vertex_count());
+        assert_valid_mesh(&triangle);
+    }
+}
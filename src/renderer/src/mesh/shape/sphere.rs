@@ -0,0 +1,199 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use crate::Mesh;
+
+use super::{grid_quad, triangle_mesh};
+
+/// A sphere made of rings of latitude & longitude, texcoords wrap around once
+pub struct UvSphere {
+    /// Radius of the sphere
+    pub radius: f32,
+    /// Number of segments around the Y axis
+    pub sectors: u32,
+    /// Number of segments from pole to pole
+    pub stacks: u32,
+}
+
+impl UvSphere {
+    pub fn new(radius: f32) -> Self {
+        UvSphere {
+            radius,
+            sectors: 32,
+            stacks: 16,
+        }
+    }
+
+    /// Sets the number of segments around the Y axis & from pole to pole
+    pub fn with_resolution(mut self, sectors: u32, stacks: u32) -> Self {
+        self.sectors = sectors.max(3);
+        self.stacks = stacks.max(2);
+        self
+    }
+}
+
+impl Default for UvSphere {
+    fn default() -> Self {
+        UvSphere::new(1.0)
+    }
+}
+
+impl From<UvSphere> for Mesh {
+    fn from(sphere: UvSphere) -> Self {
+        let sectors = sphere.sectors.max(3);
+        let stacks = sphere.stacks.max(2);
+
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut texcoords = Vec::new();
+        for stack in 0..=stacks {
+            let v = stack as f32 / stacks as f32;
+            let (ring, y) = (v * PI).sin_cos();
+            for sector in 0..=sectors {
+                let u = sector as f32 / sectors as f32;
+                let (sin, cos) = (u * 2.0 * PI).sin_cos();
+                let normal = [ring * cos, y, -ring * sin];
+                positions.push([normal[0] * sphere.radius, normal[1] * sphere.radius, normal[2] * sphere.radius]);
+                normals.push(normal);
+                texcoords.push([u, v]);
+            }
+        }
+
+        let indices = pole_to_pole_indices(stacks, sectors);
+        triangle_mesh(positions, normals, texcoords, indices)
+    }
+}
+
+/// Returns the indices of a grid whose first & last rows collapse into a single point each,
+/// the degenerate triangles at both poles are skipped
+pub(crate) fn pole_to_pole_indices(rows: u32, sectors: u32) -> Vec<u32> {
+    let mut indices = Vec::new();
+    for row in 0..rows {
+        for sector in 0..sectors {
+            let quad = grid_quad(row, sector, sectors + 1);
+            if row != 0 {
+                indices.extend_from_slice(&quad[..3]);
+            }
+            if row != rows - 1 {
+                indices.extend_from_slice(&quad[3..]);
+            }
+        }
+    }
+    indices
+}
+
+/// A sphere made of evenly sized triangles by subdividing an icosahedron.
+///
+/// Texcoords are mapped spherically, triangles crossing the seam interpolate across the whole texture.
+pub struct Icosphere {
+    /// Radius of the sphere
+    pub radius: f32,
+    /// Number of times each triangle is split into four
+    pub subdivisions: u32,
+}
+
+impl Icosphere {
+    pub fn new(radius: f32) -> Self {
+        Icosphere {
+            radius,
+            subdivisions: 3,
+        }
+    }
+
+    /// Sets the number of times each triangle is split into four
+    pub fn with_subdivisions(mut self, subdivisions: u32) -> Self {
+        self.subdivisions = subdivisions;
+        self
+    }
+}
+
+impl Default for Icosphere {
+    fn default() -> Self {
+        Icosphere::new(1.0)
+    }
+}
+
+impl From<Icosphere> for Mesh {
+    fn from(sphere: Icosphere) -> Self {
+        let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+        let mut normals = vec![
+            [-1.0, t, 0.0], [1.0, t, 0.0], [-1.0, -t, 0.0], [1.0, -t, 0.0],
+            [0.0, -1.0, t], [0.0, 1.0, t], [0.0, -1.0, -t], [0.0, 1.0, -t],
+            [t, 0.0, -1.0], [t, 0.0, 1.0], [-t, 0.0, -1.0], [-t, 0.0, 1.0],
+        ]
+        .into_iter()
+        .map(normalize)
+        .collect::<Vec<[f32; 3]>>();
+
+        let mut indices: Vec<u32> = vec![
+            0, 11, 5,   0, 5, 1,    0, 1, 7,    0, 7, 10,   0, 10, 11,
+            1, 5, 9,    5, 11, 4,   11, 10, 2,  10, 7, 6,   7, 1, 8,
+            3, 9, 4,    3, 4, 2,    3, 2, 6,    3, 6, 8,    3, 8, 9,
+            4, 9, 5,    2, 4, 11,   6, 2, 10,   8, 6, 7,    9, 8, 1,
+        ];
+
+        for _ in 0..sphere.subdivisions {
+            let mut midpoints = HashMap::<(u32, u32), u32>::new();
+            let mut midpoint = |a: u32, b: u32, normals: &mut Vec<[f32; 3]>| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let (a, b) = (normals[a as usize], normals[b as usize]);
+                    normals.push(normalize([a[0] + b[0], a[1] + b[1], a[2] + b[2]]));
+                    normals.len() as u32 - 1
+                })
+            };
+
+            indices = indices
+                .chunks_exact(3)
+                .flat_map(|triangle| {
+                    let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+                    let ab = midpoint(a, b, &mut normals);
+                    let bc = midpoint(b, c, &mut normals);
+                    let ca = midpoint(c, a, &mut normals);
+                    vec![a, ab, ca, b, bc, ab, c, ca, bc, ab, bc, ca]
+                })
+                .collect();
+        }
+
+        let positions = normals
+            .iter()
+            .map(|n| [n[0] * sphere.radius, n[1] * sphere.radius, n[2] * sphere.radius])
+            .collect();
+        let texcoords = normals
+            .iter()
+            .map(|n| [0.5 + n[2].atan2(n[0]) / (2.0 * PI), n[1].clamp(-1.0, 1.0).acos() / PI])
+            .collect();
+
+        triangle_mesh(positions, normals, texcoords, indices)
+    }
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    [v[0] / length, v[1] / length, v[2] / length]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Mesh, shape::{Icosphere, UvSphere, tests::assert_valid_mesh}};
+
+    #[test]
+    fn it_creates_uv_spheres() {
+        let sphere: Mesh = UvSphere::new(2.0).with_resolution(16, 8).into();
+
+        assert_eq!(9 * 17, sphere.vertex_count());
+        assert_eq!(6 * 16 * 7, sphere.indices.as_ref().unwrap().len());
+        assert_valid_mesh(&sphere);
+    }
+
+    #[test]
+    fn it_creates_icospheres() {
+        let sphere: Mesh = Icosphere::new(1.0).with_subdivisions(0).into();
+        assert_eq!(12, sphere.vertex_count());
+        assert_eq!(60, sphere.indices.as_ref().unwrap().len());
+        assert_valid_mesh(&sphere);
+
+        let sphere: Mesh = Icosphere::new(1.5).with_subdivisions(2).into();
+        assert_eq!(10 * 16 + 2, sphere.vertex_count());
+        assert_eq!(60 * 16, sphere.indices.as_ref().unwrap().len());
+        assert_valid_mesh(&sphere);
+    }
+}
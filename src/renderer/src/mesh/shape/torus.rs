@@ -0,0 +1,87 @@
+use std::f32::consts::PI;
+
+use crate::Mesh;
+
+use super::{grid_quad, triangle_mesh};
+
+/// A ring shaped torus lying in the XZ plane
+pub struct Torus {
+    /// Distance from the center of the torus to the center of the tube
+    pub radius: f32,
+    /// Radius of the tube
+    pub tube_radius: f32,
+    /// Number of segments around the Y axis
+    pub sectors: u32,
+    /// Number of segments around the tube
+    pub sides: u32,
+}
+
+impl Torus {
+    pub fn new(radius: f32, tube_radius: f32) -> Self {
+        Torus {
+            radius,
+            tube_radius,
+            sectors: 32,
+            sides: 16,
+        }
+    }
+
+    /// Sets the number of segments around the Y axis & around the tube
+    pub fn with_resolution(mut self, sectors: u32, sides: u32) -> Self {
+        self.sectors = sectors.max(3);
+        self.sides = sides.max(3);
+        self
+    }
+}
+
+impl Default for Torus {
+    fn default() -> Self {
+        Torus::new(1.0, 0.25)
+    }
+}
+
+impl From<Torus> for Mesh {
+    fn from(torus: Torus) -> Self {
+        let (sectors, sides) = (torus.sectors.max(3), torus.sides.max(3));
+
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut texcoords = Vec::new();
+        for side in 0..=sides {
+            let v = side as f32 / sides as f32;
+            let (tube_sin, tube_cos) = (v * 2.0 * PI).sin_cos();
+            for sector in 0..=sectors {
+                let u = sector as f32 / sectors as f32;
+                let (sin, cos) = (u * 2.0 * PI).sin_cos();
+                let normal = [tube_cos * cos, -tube_sin, -tube_cos * sin];
+                positions.push([
+                    torus.radius * cos + torus.tube_radius * normal[0],
+                    torus.tube_radius * normal[1],
+                    -torus.radius * sin + torus.tube_radius * normal[2],
+                ]);
+                normals.push(normal);
+                texcoords.push([u, v]);
+            }
+        }
+
+        let indices = (0..sides)
+            .flat_map(|side| (0..sectors).flat_map(move |sector| grid_quad(side, sector, sectors + 1).to_vec()))
+            .collect();
+
+        triangle_mesh(positions, normals, texcoords, indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Mesh, shape::{Torus, tests::assert_valid_mesh}};
+
+    #[test]
+    fn it_creates_tori() {
+        let torus: Mesh = Torus::new(1.0, 0.3).with_resolution(24, 12).into();
+
+        assert_eq!(25 * 13, torus.vertex_count());
+        assert_eq!(6 * 24 * 12, torus.indices.as_ref().unwrap().len());
+        assert_valid_mesh(&torus);
+    }
+}
@@ -1,6 +1,6 @@
 use uuid::Uuid;
 
-use crate::{ShaderStage, TextureComponentType, TextureViewDimension, UniformProperty};
+use crate::{LayoutRule, ShaderError, ShaderStages, TextureComponentType, TextureFormat, TextureViewDimension, UniformProperty};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub struct BindGroupId(Uuid);
@@ -18,18 +18,36 @@ pub enum BindingType {
         dynamic: bool,
         property: UniformProperty,
     },
+    /// Binding type is a storage buffer, a runtime sized array as last member has length 0
+    StorageBuffer {
+        dynamic: bool,
+        readonly: bool,
+        property: UniformProperty,
+    },
     /// A sampled texture
     SampledTexture {
         dimension: TextureViewDimension,
         component_type: TextureComponentType,
     },
+    /// A sampler to sample a texture with, comparison samplers are used with depth textures
+    Sampler {
+        comparison: bool,
+    },
+    /// A storage texture (image) that can be read & written in the shader
+    StorageTexture {
+        dimension: TextureViewDimension,
+        format: TextureFormat,
+        readonly: bool,
+    },
     Unknown,
 }
 
 impl BindingType {
+    /// Returns the minimum size of the buffer for uniform & storage buffer bindings
     pub fn get_size(&self) -> Option<u64> {
         match self {
             BindingType::Uniform { property, .. } => Some(property.get_size()),
+            BindingType::StorageBuffer { property, .. } => Some(property.size(LayoutRule::Std430)),
             _ => None,
         }
     }
@@ -42,7 +60,25 @@ impl From<&BindingType> for wgpu::BindingType {
                 dynamic: *dynamic,
                 min_binding_size: binding_type.get_size().and_then(wgpu::BufferSize::new),
             },
-            _ => panic!("Binding type currently not supported"),
+            BindingType::SampledTexture { dimension, component_type } => wgpu::BindingType::SampledTexture {
+                dimension: (*dimension).into(),
+                component_type: (*component_type).into(),
+                multisampled: false,
+            },
+            BindingType::StorageBuffer { dynamic, readonly, .. } => wgpu::BindingType::StorageBuffer {
+                dynamic: *dynamic,
+                min_binding_size: binding_type.get_size().and_then(wgpu::BufferSize::new),
+                readonly: *readonly,
+            },
+            BindingType::Sampler { comparison } => wgpu::BindingType::Sampler {
+                comparison: *comparison,
+            },
+            BindingType::StorageTexture { dimension, format, readonly } => wgpu::BindingType::StorageTexture {
+                dimension: (*dimension).into(),
+                format: (*format).into(),
+                readonly: *readonly,
+            },
+            BindingType::Unknown => panic!("Binding type currently not supported"),
         }
     }
 }
@@ -71,8 +107,8 @@ pub struct BindGroupEntry {
     pub index: u32,
     /// The binding type
     pub binding_type: BindingType,
-    /// The shader stage this binding is associated with
-    pub shader_stage: ShaderStage,
+    /// The shader stages this binding is visible in
+    pub shader_stages: ShaderStages,
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
@@ -103,16 +139,87 @@ impl BindGroupDescriptor {
         }
     }
 
+    /// Returns the binding with the given name
+    pub fn find_binding(&self, name: &str) -> Option<&BindGroupEntry> {
+        self.bindings.iter().find(|binding| binding.name == name)
+    }
+
     /// Finds a given Binding Descriptor in this Bind Group Descriptor
-    pub fn contains(&self, other: &BindGroupEntry) -> bool {
-        if let Some(binding) = self.bindings.iter().find(|rhs| rhs.index == other.index) {
-            if binding.binding_type == other.binding_type && binding.name == other.name {
-                true
-            } else {
-                panic!("Binding {} in BindGroup {} is not consistent across shader types: ", binding.index, self.index);
-            }
-        } else {
-            false
+    ///
+    /// Returns an error when a binding with the same index has a different name or type.
+    pub fn contains(&self, other: &BindGroupEntry) -> Result<bool, ShaderError> {
+        match self.bindings.iter().find(|rhs| rhs.index == other.index) {
+            Some(binding) if binding.binding_type == other.binding_type && binding.name == other.name => Ok(true),
+            Some(binding) => Err(ShaderError::InconsistentBinding(self.index, binding.index)),
+            None => Ok(false),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{BindingType, LayoutRule, TextureComponentType, TextureFormat, TextureViewDimension, UniformProperty};
+
+    fn particles() -> UniformProperty {
+        // buffer Particles { uint count; vec4 positions[]; };
+        UniformProperty::structure(
+            vec![
+                ("count", UniformProperty::UInt),
+                ("positions", UniformProperty::Array(Box::new(UniformProperty::Vec4), 0)),
+            ],
+            LayoutRule::Std430,
+        )
+    }
+
+    #[test]
+    fn it_maps_buffer_bindings() {
+        let uniform = BindingType::Uniform { dynamic: true, property: UniformProperty::Mat4 };
+        assert_eq!(
+            wgpu::BindingType::UniformBuffer { dynamic: true, min_binding_size: wgpu::BufferSize::new(64) },
+            wgpu::BindingType::from(&uniform),
+        );
+
+        let storage = BindingType::StorageBuffer { dynamic: false, readonly: true, property: particles() };
+        assert_eq!(
+            wgpu::BindingType::StorageBuffer {
+                dynamic: false,
+                min_binding_size: wgpu::BufferSize::new(16),
+                readonly: true,
+            },
+            wgpu::BindingType::from(&storage),
+        );
+    }
+
+    #[test]
+    fn it_maps_texture_bindings() {
+        let texture = BindingType::SampledTexture {
+            dimension: TextureViewDimension::D2,
+            component_type: TextureComponentType::UInt,
+        };
+        assert_eq!(
+            wgpu::BindingType::SampledTexture {
+                dimension: wgpu::TextureViewDimension::D2,
+                component_type: wgpu::TextureComponentType::Uint,
+                multisampled: false,
+            },
+            wgpu::BindingType::from(&texture),
+        );
+
+        let sampler = BindingType::Sampler { comparison: true };
+        assert_eq!(wgpu::BindingType::Sampler { comparison: true }, wgpu::BindingType::from(&sampler));
+
+        let image = BindingType::StorageTexture {
+            dimension: TextureViewDimension::D3,
+            format: TextureFormat::R32Float,
+            readonly: false,
+        };
+        assert_eq!(
+            wgpu::BindingType::StorageTexture {
+                dimension: wgpu::TextureViewDimension::D3,
+                format: wgpu::TextureFormat::R32Float,
+                readonly: false,
+            },
+            wgpu::BindingType::from(&image),
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use std::fmt;
+
+use crate::{BindGroupDescriptor, BindGroupEntry, BindingType, Buffer, BufferUsage, SamplerId, Texture, TextureUsage, Uniform, UniformBuffer};
+
+#[derive(Debug, PartialEq)]
+pub enum BindGroupError {
+    /// No binding with the given name or index exists in the bind group
+    BindingNotFound(String),
+    /// A resource was attached more than once to the same binding
+    DuplicateBinding(String),
+    /// The bind group requires a resource for this binding, but none was attached
+    MissingBinding(String),
+    /// The attached resource does not match the reflected binding type
+    BindingTypeMismatch(String, String),
+    /// The attached buffer is smaller than the size required by the binding
+    BufferTooSmall(String, u64, u64),
+    /// The attached resource was not created with the usage required by the binding
+    InvalidUsage(String),
+    /// The sampler is (not) a comparison sampler, contains whether the binding requires one
+    ComparisonMismatch(String, bool),
+}
+
+impl fmt::Display for BindGroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            BindGroupError::BindingNotFound(slot) => format!("No binding '{}' found in bind group", slot),
+            BindGroupError::DuplicateBinding(name) => format!("Binding '{}' is set more than once", name),
+            BindGroupError::MissingBinding(name) => format!("No resource set for binding '{}'", name),
+            BindGroupError::BindingTypeMismatch(name, resource) => format!("Binding '{}' does not accept a {}", name, resource),
+            BindGroupError::BufferTooSmall(name, size, required) => format!(
+                "Buffer for binding '{}' has {} bytes, but requires at least {} bytes", name, size, required,
+            ),
+            BindGroupError::InvalidUsage(name) => format!("Resource for binding '{}' has an invalid usage", name),
+            BindGroupError::ComparisonMismatch(name, true) => format!("Binding '{}' requires a comparison sampler", name),
+            BindGroupError::ComparisonMismatch(name, false) => format!("Binding '{}' does not accept a comparison sampler", name),
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// References a binding in a bind group, either by its index or by its reflected name
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindingSlot {
+    Index(u32),
+    Name(String),
+}
+
+impl From<u32> for BindingSlot {
+    fn from(index: u32) -> Self {
+        BindingSlot::Index(index)
+    }
+}
+
+impl From<&str> for BindingSlot {
+    fn from(name: &str) -> Self {
+        BindingSlot::Name(name.to_string())
+    }
+}
+
+impl fmt::Display for BindingSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingSlot::Index(index) => write!(f, "{}", index),
+            BindingSlot::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// A resource that is attached to a single binding
+#[derive(Debug, Clone)]
+pub enum BindingResource<'a> {
+    /// A buffer, e.g. a uniform buffer
+    Buffer(Buffer),
+    /// A texture, bound by its texture view
+    Texture(&'a Texture),
+    /// A texture sampler
+    Sampler(SamplerId),
+}
+
+impl<'a> BindingResource<'a> {
+    /// Returns the name of the resource kind, used in error messages
+    fn kind(&self) -> &'static str {
+        match self {
+            BindingResource::Buffer(_) => "buffer",
+            BindingResource::Texture(_) => "texture",
+            BindingResource::Sampler(_) => "sampler",
+        }
+    }
+}
+
+/// Collects the resources of a bind group, the bind group itself is created by the Renderer.
+///
+/// Example:
+/// let bind_group = BindGroupBuilder::new(&descriptor)
+///     .buffer("Uniforms", &uniform_buffer)
+///     .texture(1, &texture)
+///     .sampler(2, &sampler);
+/// let bind_group_id = renderer.create_bind_group(&bind_group)?;
+///
+#[derive(Debug)]
+pub struct BindGroupBuilder<'a> {
+    /// The reflected descriptor of the bind group
+    pub descriptor: &'a BindGroupDescriptor,
+    /// The list of attached resources
+    pub resources: Vec<(BindingSlot, BindingResource<'a>)>,
+}
+
+impl<'a> BindGroupBuilder<'a> {
+    /// Creates a new builder for the given bind group descriptor
+    pub fn new(descriptor: &'a BindGroupDescriptor) -> Self {
+        Self {
+            descriptor,
+            resources: Vec::new(),
+        }
+    }
+
+    /// Attaches a buffer to the binding
+    pub fn buffer<S: Into<BindingSlot>>(mut self, slot: S, buffer: &Buffer) -> Self {
+        self.resources.push((slot.into(), BindingResource::Buffer(*buffer)));
+        self
+    }
+
+    /// Attaches a typed uniform buffer to the binding it was validated against
+    pub fn uniform_buffer<T: Uniform>(self, uniform_buffer: &UniformBuffer<T>) -> Self {
+        let name = uniform_buffer.name.clone();
+        self.buffer(name.as_str(), &uniform_buffer.buffer)
+    }
+
+    /// Attaches a texture to the binding
+    pub fn texture<S: Into<BindingSlot>>(mut self, slot: S, texture: &'a Texture) -> Self {
+        self.resources.push((slot.into(), BindingResource::Texture(texture)));
+        self
+    }
+
+    /// Attaches a sampler to the binding
+    pub fn sampler<S: Into<BindingSlot>>(mut self, slot: S, sampler: &SamplerId) -> Self {
+        self.resources.push((slot.into(), BindingResource::Sampler(*sampler)));
+        self
+    }
+
+    /// Resolves all attached resources to their binding index and validates them
+    /// against the reflected binding types.
+    pub fn resolve(&self) -> Result<Vec<(u32, BindingResource<'a>)>, BindGroupError> {
+        let mut resolved: Vec<(u32, BindingResource<'a>)> = Vec::new();
+
+        for (slot, resource) in self.resources.iter() {
+            let entry = self.find_entry(slot)
+                .ok_or_else(|| BindGroupError::BindingNotFound(slot.to_string()))?;
+
+            if resolved.iter().any(|(index, _)| *index == entry.index) {
+                return Err(BindGroupError::DuplicateBinding(entry.name.clone()));
+            }
+
+            validate_resource(entry, resource)?;
+            resolved.push((entry.index, resource.clone()));
+        }
+
+        if let Some(missing) = self.descriptor.bindings
+            .iter()
+            .find(|entry| !resolved.iter().any(|(index, _)| *index == entry.index))
+        {
+            return Err(BindGroupError::MissingBinding(missing.name.clone()));
+        }
+
+        resolved.sort_by_key(|(index, _)| *index);
+        Ok(resolved)
+    }
+
+    /// Finds the reflected binding by index or name
+    fn find_entry(&self, slot: &BindingSlot) -> Option<&BindGroupEntry> {
+        self.descriptor.bindings
+            .iter()
+            .find(|entry| match slot {
+                BindingSlot::Index(index) => entry.index == *index,
+                BindingSlot::Name(name) => entry.name == *name,
+            })
+    }
+}
+
+/// Checks that the resource is compatible with the reflected binding type
+fn validate_resource(entry: &BindGroupEntry, resource: &BindingResource) -> Result<(), BindGroupError> {
+    match (&entry.binding_type, resource) {
+        (BindingType::Uniform { .. }, BindingResource::Buffer(buffer)) => {
+            if !buffer.usage.contains(BufferUsage::UNIFORM) {
+                return Err(BindGroupError::InvalidUsage(entry.name.clone()));
+            }
+            validate_buffer_size(entry, buffer)
+        }
+        (BindingType::StorageBuffer { .. }, BindingResource::Buffer(buffer)) => {
+            if !buffer.usage.contains(BufferUsage::STORAGE) {
+                return Err(BindGroupError::InvalidUsage(entry.name.clone()));
+            }
+            validate_buffer_size(entry, buffer)
+        }
+        (BindingType::SampledTexture { .. }, BindingResource::Texture(texture)) => {
+            if !texture.usage().contains(TextureUsage::SAMPLED) {
+                return Err(BindGroupError::InvalidUsage(entry.name.clone()));
+            }
+            Ok(())
+        }
+        (BindingType::StorageTexture { .. }, BindingResource::Texture(texture)) => {
+            if !texture.usage().contains(TextureUsage::STORAGE) {
+                return Err(BindGroupError::InvalidUsage(entry.name.clone()));
+            }
+            Ok(())
+        }
+        (BindingType::Sampler { comparison }, BindingResource::Sampler(sampler)) => {
+            if sampler.comparison != *comparison {
+                return Err(BindGroupError::ComparisonMismatch(entry.name.clone(), *comparison));
+            }
+            Ok(())
+        }
+        (_, resource) => Err(BindGroupError::BindingTypeMismatch(
+            entry.name.clone(),
+            resource.kind().to_string(),
+        )),
+    }
+}
+
+/// Checks that the buffer is large enough for the reflected block of the binding
+fn validate_buffer_size(entry: &BindGroupEntry, buffer: &Buffer) -> Result<(), BindGroupError> {
+    let required = entry.binding_type.get_size().unwrap_or(0);
+    if buffer.size < required {
+        return Err(BindGroupError::BufferTooSmall(entry.name.clone(), buffer.size, required));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BindGroupBuilder, BindGroupDescriptor, BindGroupEntry, BindGroupError, BindingType, Buffer, BufferUsage, LayoutRule, SamplerId, ShaderStage, UniformProperty};
+
+    fn descriptor() -> BindGroupDescriptor {
+        BindGroupDescriptor::new(
+            0,
+            vec![
+                BindGroupEntry {
+                    name: "Uniforms".into(),
+                    index: 0,
+                    binding_type: BindingType::Uniform {
+                        dynamic: false,
+                        property: UniformProperty::structure(
+                            vec![("modelView", UniformProperty::Mat4)],
+                            LayoutRule::Std140,
+                        ),
+                    },
+                    shader_stages: ShaderStage::Vertex.into(),
+                },
+                BindGroupEntry {
+                    name: "colorSampler".into(),
+                    index: 1,
+                    binding_type: BindingType::Sampler { comparison: false },
+                    shader_stages: ShaderStage::Fragment.into(),
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn it_resolves_bindings_by_name_and_index() {
+        let descriptor = descriptor();
+        let buffer = Buffer::new(64, BufferUsage::UNIFORM | BufferUsage::COPY_DST);
+        let sampler = SamplerId::new(false);
+
+        let builder = BindGroupBuilder::new(&descriptor)
+            .sampler(1, &sampler)
+            .buffer("Uniforms", &buffer);
+
+        let resolved = builder.resolve().unwrap();
+        assert_eq!(vec![0, 1], resolved.iter().map(|(index, _)| *index).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn it_fails_on_unknown_or_missing_bindings() {
+        let descriptor = descriptor();
+        let buffer = Buffer::new(64, BufferUsage::UNIFORM);
+
+        let unknown = BindGroupBuilder::new(&descriptor).buffer("Unknown", &buffer);
+        assert_eq!(Err(BindGroupError::BindingNotFound("Unknown".into())), unknown.resolve().map(|_| ()));
+
+        let missing = BindGroupBuilder::new(&descriptor).buffer(0, &buffer);
+        assert_eq!(Err(BindGroupError::MissingBinding("colorSampler".into())), missing.resolve().map(|_| ()));
+    }
+
+    #[test]
+    fn it_validates_resources_against_binding_type() {
+        let descriptor = descriptor();
+        let sampler = SamplerId::new(false);
+        let small_buffer = Buffer::new(16, BufferUsage::UNIFORM);
+        let vertex_buffer = Buffer::new(64, BufferUsage::VERTEX);
+
+        let builder = BindGroupBuilder::new(&descriptor).sampler(0, &sampler);
+        assert_eq!(
+            Err(BindGroupError::BindingTypeMismatch("Uniforms".into(), "sampler".into())),
+            builder.resolve().map(|_| ()),
+        );
+
+        let builder = BindGroupBuilder::new(&descriptor).buffer(0, &small_buffer).sampler(1, &sampler);
+        assert_eq!(Err(BindGroupError::BufferTooSmall("Uniforms".into(), 16, 64)), builder.resolve().map(|_| ()));
+
+        let builder = BindGroupBuilder::new(&descriptor).buffer(0, &vertex_buffer).sampler(1, &sampler);
+        assert_eq!(Err(BindGroupError::InvalidUsage("Uniforms".into())), builder.resolve().map(|_| ()));
+    }
+
+    #[test]
+    fn it_validates_storage_buffers() {
+        let descriptor = BindGroupDescriptor::new(
+            0,
+            vec![
+                BindGroupEntry {
+                    name: "Particles".into(),
+                    index: 0,
+                    binding_type: BindingType::StorageBuffer {
+                        dynamic: false,
+                        readonly: false,
+                        property: UniformProperty::structure(
+                            vec![("positions", UniformProperty::Array(Box::new(UniformProperty::Vec4), 8))],
+                            LayoutRule::Std430,
+                        ),
+                    },
+                    shader_stages: ShaderStage::Compute.into(),
+                },
+            ],
+        );
+
+        let buffer = Buffer::new(128, BufferUsage::STORAGE);
+        assert!(BindGroupBuilder::new(&descriptor).buffer(0, &buffer).resolve().is_ok());
+
+        let uniform_buffer = Buffer::new(128, BufferUsage::UNIFORM);
+        let builder = BindGroupBuilder::new(&descriptor).buffer(0, &uniform_buffer);
+        assert_eq!(Err(BindGroupError::InvalidUsage("Particles".into())), builder.resolve().map(|_| ()));
+
+        let small_buffer = Buffer::new(64, BufferUsage::STORAGE);
+        let builder = BindGroupBuilder::new(&descriptor).buffer(0, &small_buffer);
+        assert_eq!(Err(BindGroupError::BufferTooSmall("Particles".into(), 64, 128)), builder.resolve().map(|_| ()));
+    }
+
+    #[test]
+    fn it_validates_comparison_samplers() {
+        let descriptor = descriptor();
+        let shadow_descriptor = BindGroupDescriptor::new(
+            0,
+            vec![
+                BindGroupEntry {
+                    name: "shadowSampler".into(),
+                    index: 0,
+                    binding_type: BindingType::Sampler { comparison: true },
+                    shader_stages: ShaderStage::Fragment.into(),
+                },
+            ],
+        );
+        let buffer = Buffer::new(64, BufferUsage::UNIFORM);
+        let sampler = SamplerId::new(false);
+        let shadow_sampler = SamplerId::new(true);
+
+        assert!(BindGroupBuilder::new(&shadow_descriptor).sampler(0, &shadow_sampler).resolve().is_ok());
+
+        let builder = BindGroupBuilder::new(&shadow_descriptor).sampler(0, &sampler);
+        assert_eq!(Err(BindGroupError::ComparisonMismatch("shadowSampler".into(), true)), builder.resolve().map(|_| ()));
+
+        let builder = BindGroupBuilder::new(&descriptor).buffer(0, &buffer).sampler(1, &shadow_sampler);
+        assert_eq!(Err(BindGroupError::ComparisonMismatch("colorSampler".into(), false)), builder.resolve().map(|_| ()));
+    }
+}
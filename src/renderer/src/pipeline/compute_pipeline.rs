@@ -0,0 +1,104 @@
+use uuid::Uuid;
+
+use crate::{PipelineLayout, Shader, ShaderError, ShaderStage};
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct ComputePipelineId(Uuid);
+
+impl ComputePipelineId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+/// Describes a Compute Pipeline
+pub struct ComputePipelineDescriptor {
+    /// The name of the pipeline (optional), used for debugging
+    pub label: Option<String>,
+    /// The pipeline layout, reflected from the compute shader
+    pub layout: PipelineLayout,
+    /// Compute Shader
+    pub compute_shader: Shader,
+    /// Name of the entry point function of the compute shader
+    pub entry_point: String,
+    /// The workgroup size declared in the compute shader
+    pub local_size: [u32; 3],
+}
+
+impl ComputePipelineDescriptor {
+    /// Creates the descriptor, fails if the shader is not a compute shader or its layout is invalid
+    pub fn new(compute_shader: Shader) -> Result<Self, ShaderError> {
+        if compute_shader.stage != ShaderStage::Compute {
+            return Err(ShaderError::WrongStage(ShaderStage::Compute, compute_shader.stage));
+        }
+
+        let layout = PipelineLayout::from_shaders(vec![&compute_shader])?;
+        let entry_point = compute_shader.layout().entry_point.clone();
+        let local_size = compute_shader.layout().local_size.unwrap_or([1, 1, 1]);
+
+        Ok(Self {
+            label: None,
+            layout,
+            compute_shader,
+            entry_point,
+            local_size,
+        })
+    }
+
+    /// Returns the number of workgroups to dispatch to cover the given number of invocations
+    pub fn workgroups(&self, invocations: [u32; 3]) -> [u32; 3] {
+        workgroup_count(invocations, self.local_size)
+    }
+}
+
+/// Returns the number of workgroups of the given size required to cover all invocations
+pub fn workgroup_count(invocations: [u32; 3], local_size: [u32; 3]) -> [u32; 3] {
+    let mut count = [1; 3];
+    for i in 0..3 {
+        let size = local_size[i].max(1);
+        count[i] = (invocations[i] + size - 1) / size;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::workgroup_count;
+    #[cfg(feature = "glsl")]
+    use crate::{ComputePipelineDescriptor, Renderer, Shader, ShaderError, ShaderStage};
+
+    #[test]
+    fn it_rounds_workgroups_up() {
+        assert_eq!([4, 1, 1], workgroup_count([256, 1, 1], [64, 1, 1]));
+        assert_eq!([5, 3, 1], workgroup_count([33, 20, 1], [8, 8, 1]));
+        assert_eq!([0, 1, 1], workgroup_count([0, 1, 1], [64, 1, 1]));
+        assert_eq!([10, 1, 1], workgroup_count([10, 1, 1], [0, 1, 1]));
+    }
+
+    #[test]
+    #[cfg(feature = "glsl")]
+    fn it_rejects_shaders_of_other_stages() {
+        let renderer = futures::executor::block_on(Renderer::new()).unwrap();
+        let vertex_shader = Shader::compile(
+            "#version 450\nvoid main() { gl_Position = vec4(0.0); }",
+            ShaderStage::Vertex,
+            &renderer.device,
+        ).unwrap();
+
+        match ComputePipelineDescriptor::new(vertex_shader) {
+            Err(ShaderError::WrongStage(expected, found)) => {
+                assert_eq!(ShaderStage::Compute, expected);
+                assert_eq!(ShaderStage::Vertex, found);
+            }
+            result => panic!("Expected wrong stage, got {:?}", result.map(|_| ())),
+        }
+
+        let compute_shader = Shader::compile(
+            "#version 450\nlayout(local_size_x = 64) in;\nvoid main() {}",
+            ShaderStage::Compute,
+            &renderer.device,
+        ).unwrap();
+        let descriptor = ComputePipelineDescriptor::new(compute_shader).unwrap();
+        assert_eq!([64, 1, 1], descriptor.local_size);
+    }
+}
@@ -1,16 +1,26 @@
+use std::hash::{Hash, Hasher};
+
 use crate::TextureFormat;
 
 pub mod bind_group;
+pub mod bind_group_builder;
+pub mod compute_pipeline;
 pub mod pipeline;
+pub mod pipeline_builder;
 pub mod pipeline_layout;
 pub mod state;
+pub mod uniform_layout;
 
 pub use bind_group::*;
+pub use bind_group_builder::*;
+pub use compute_pipeline::*;
 pub use pipeline::*;
+pub use pipeline_builder::*;
 pub use pipeline_layout::*;
 pub use state::*;
+pub use uniform_layout::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FrontFace {
     /// Counter Clock wise
     Ccw,
@@ -27,7 +37,7 @@ impl From<FrontFace> for wgpu::FrontFace {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CullMode {
     Front,
     Back,
@@ -44,7 +54,7 @@ impl From<CullMode> for wgpu::CullMode {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RasterizationStateDescriptor {
     pub front_face: FrontFace,
     pub cull_mode: CullMode,
@@ -67,6 +77,31 @@ impl Default for RasterizationStateDescriptor {
     }
 }
 
+impl Hash for RasterizationStateDescriptor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.front_face.hash(state);
+        self.cull_mode.hash(state);
+        self.depth_bias.hash(state);
+        self.depth_bias_slope_scale.to_bits().hash(state);
+        self.depth_bias_clamp.to_bits().hash(state);
+        self.clamp_depth.hash(state);
+    }
+}
+
+/// Floats are compared by their bits, consistent with the hash
+impl PartialEq for RasterizationStateDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        self.front_face == other.front_face
+            && self.cull_mode == other.cull_mode
+            && self.depth_bias == other.depth_bias
+            && self.depth_bias_slope_scale.to_bits() == other.depth_bias_slope_scale.to_bits()
+            && self.depth_bias_clamp.to_bits() == other.depth_bias_clamp.to_bits()
+            && self.clamp_depth == other.clamp_depth
+    }
+}
+
+impl Eq for RasterizationStateDescriptor {}
+
 impl From<&RasterizationStateDescriptor> for wgpu::RasterizationStateDescriptor {
     fn from(val: &RasterizationStateDescriptor) -> Self {
         wgpu::RasterizationStateDescriptor {
@@ -80,7 +115,7 @@ impl From<&RasterizationStateDescriptor> for wgpu::RasterizationStateDescriptor
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PrimitiveTopology {
     PointList,
     LineList,
@@ -89,7 +124,7 @@ pub enum PrimitiveTopology {
     TriangleStrip,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DepthStencilStateDescriptor {
     pub format: TextureFormat,
     pub depth_write_enabled: bool,
@@ -124,7 +159,7 @@ impl From<&DepthStencilStateDescriptor> for wgpu::DepthStencilStateDescriptor {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StencilStateDescriptor {
     pub front: StencilStateFaceDescriptor,
     pub back: StencilStateFaceDescriptor,
@@ -143,7 +178,7 @@ impl From<&StencilStateDescriptor> for wgpu::StencilStateDescriptor {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StencilStateFaceDescriptor {
     pub compare: CompareFunction,
     pub fail_op: StencilOperation,
@@ -171,7 +206,7 @@ impl StencilStateFaceDescriptor {
     };
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum StencilOperation {
     Keep,
     Zero,
@@ -215,34 +250,46 @@ pub enum UniformProperty {
     UInt,
     Int,
     IVec2,
+    IVec3,
+    IVec4,
     Float,
+    UVec2,
+    UVec3,
     UVec4,
     Vec2,
     Vec3,
     Vec4,
+    Mat2,
     Mat3,
     Mat4,
-    Struct(Vec<UniformProperty>),
+    Struct(Vec<UniformMember>),
     Array(Box<UniformProperty>, usize),
 }
 
+/// A named member of a uniform struct
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformMember {
+    /// Name of the member as declared in the shader
+    pub name: String,
+    /// Byte offset relative to the start of the enclosing struct
+    pub offset: u64,
+    /// The type of the member
+    pub property: UniformProperty,
+}
+
+impl UniformMember {
+    pub fn new(name: &str, offset: u64, property: UniformProperty) -> Self {
+        Self {
+            name: name.to_string(),
+            offset,
+            property,
+        }
+    }
+}
+
 impl UniformProperty {
+    /// Returns the size of the property laid out as a uniform block (std140)
     pub fn get_size(&self) -> u64 {
-        match self {
-            UniformProperty::UInt => 4,
-            UniformProperty::Int => 4,
-            UniformProperty::IVec2 => 4 * 2,
-            UniformProperty::Float => 4,
-            UniformProperty::UVec4 => 4 * 4,
-            UniformProperty::Vec2 => 4 * 2,
-            UniformProperty::Vec3 => 4 * 3,
-            UniformProperty::Vec4 => 4 * 4,
-            UniformProperty::Mat3 => 4 * 4 * 3,
-            UniformProperty::Mat4 => 4 * 4 * 4,
-            UniformProperty::Struct(properties) => {
-                properties.iter().map(|p| p.get_size()).sum()
-            }
-            UniformProperty::Array(property, length) => property.get_size() * *length as u64,
-        }
+        self.size(LayoutRule::Std140)
     }
 }
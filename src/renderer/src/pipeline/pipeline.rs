@@ -1,6 +1,6 @@
 use uuid::Uuid;
 
-use crate::{ColorStateDescriptor, DepthStencilStateDescriptor, IndexFormat, PipelineLayout, PrimitiveTopology, RasterizationStateDescriptor, Shader};
+use crate::{ColorStateDescriptor, DepthStencilStateDescriptor, IndexFormat, PipelineBuilder, PipelineLayout, PrimitiveTopology, RasterizationStateDescriptor, Shader, ShaderDefs, ShaderError, ShaderKey, VertexAttributeDescriptor, VertexBufferDescriptor};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub struct RenderPipelineId(Uuid);
@@ -11,16 +11,45 @@ impl RenderPipelineId {
     }
 }
 
+/// Identifies a render pipeline by its shaders & states, equal keys describe identical pipelines.
+///
+/// Shaders are compared by content, all states by value. The label is not part of the key, neither
+/// is the layout: it is always reflected from the shaders, and its bind group descriptors carry ids
+/// that are generated anew for every reflection, the layouts of identical shaders would never match.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    vertex_shader: ShaderKey,
+    fragment_shader: ShaderKey,
+    vertex_entry_point: String,
+    fragment_entry_point: String,
+    vertex_buffers: Option<Vec<VertexBufferDescriptor>>,
+    color_states: Vec<ColorStateDescriptor>,
+    rasterization_state: Option<RasterizationStateDescriptor>,
+    primitive_topology: PrimitiveTopology,
+    depth_stencil_state: Option<DepthStencilStateDescriptor>,
+    index_format: IndexFormat,
+    sample_count: u32,
+    sample_mask: u32,
+    alpha_to_coverage_enabled: bool,
+}
+
 /// Describes a Render Pipeline
+#[derive(Clone)]
 pub struct PipelineDescriptor {
     /// The name of the pipeline (optional), used for debugging
     pub label: Option<String>,
-    /// THe The pipeline layout
-    pub layout: Option<PipelineLayout>,
+    /// The pipeline layout, reflected from both shaders
+    pub(crate) layout: PipelineLayout,
     /// Vertex Shader
-    pub vertex_shader: Shader,
+    pub(crate) vertex_shader: Shader,
     /// Fragment Shader
-    pub fragment_shader: Shader,
+    pub(crate) fragment_shader: Shader,
+    /// Overrides the entry point function reflected from the vertex shader
+    pub vertex_entry_point: Option<String>,
+    /// Overrides the entry point function reflected from the fragment shader
+    pub fragment_entry_point: Option<String>,
+    /// Vertex buffers replacing the reflected ones, e.g. the interleaved attributes of a mesh
+    pub vertex_buffers: Option<Vec<VertexBufferDescriptor>>,
     /// List of color state descriptors
     pub color_states: Vec<ColorStateDescriptor>,
     /// Describes the state of the rasterizer in this pipeline
@@ -44,36 +73,222 @@ pub struct PipelineDescriptor {
 }
 
 impl PipelineDescriptor {
+    /// Creates a descriptor with default states, use `PipelineBuilder` to configure them.
+    /// Fails if the layouts of both shaders are not compatible.
     pub fn new(
         vertex_shader: Shader,
         fragment_shader: Shader,
-    ) -> Self {
-        let layout = PipelineLayout::from_shaders(
-            vec![&vertex_shader, &fragment_shader]
-        ).unwrap();
-
-        let color_states = vec![
-            ColorStateDescriptor::default(),
-        ];
-
-        Self {
-            label: Some(String::from("Test")),
-            layout: Some(layout),
-            vertex_shader,
-            fragment_shader,
-            color_states,
-            rasterization_state: Some(RasterizationStateDescriptor::default()),
-            primitive_topology: PrimitiveTopology::TriangleList,
-            depth_stencil_state: Some(DepthStencilStateDescriptor::default()),
-            index_format: IndexFormat::Uint32,
-            sample_count: 1,
-            sample_mask: !0,
-            alpha_to_coverage_enabled: false,
+    ) -> Result<Self, ShaderError> {
+        PipelineBuilder::new(vertex_shader, fragment_shader).build()
+    }
+
+    /// Returns a builder to configure the pipeline states
+    pub fn builder(vertex_shader: Shader, fragment_shader: Shader) -> PipelineBuilder {
+        PipelineBuilder::new(vertex_shader, fragment_shader)
+    }
+
+    /// Returns the defines the shader permutations of this pipeline were compiled with, merged
+    /// from both stages. Fails if the stages were compiled with different values for a define.
+    pub fn shader_defs(&self) -> Result<ShaderDefs, ShaderError> {
+        self.vertex_shader.defs.merge(&self.fragment_shader.defs)
+    }
+
+    /// Returns the name of the vertex shader entry point, either set explicitly or reflected from the shader
+    pub fn vertex_entry_point(&self) -> &str {
+        self.vertex_entry_point
+            .as_deref()
+            .unwrap_or(&self.vertex_shader.layout().entry_point)
+    }
+
+    /// Returns the name of the fragment shader entry point, either set explicitly or reflected from the shader
+    pub fn fragment_entry_point(&self) -> &str {
+        self.fragment_entry_point
+            .as_deref()
+            .unwrap_or(&self.fragment_shader.layout().entry_point)
+    }
+
+    /// Returns the vertex shader
+    pub fn vertex_shader(&self) -> &Shader {
+        &self.vertex_shader
+    }
+
+    /// Returns the fragment shader
+    pub fn fragment_shader(&self) -> &Shader {
+        &self.fragment_shader
+    }
+
+    /// Returns the pipeline layout reflected from both shaders
+    pub fn layout(&self) -> &PipelineLayout {
+        &self.layout
+    }
+
+    /// Replaces both shaders, e.g. after they were recompiled, and reflects the layout again.
+    /// All states & overridden entry points are kept, fails if the shaders are not compatible.
+    pub fn set_shaders(&mut self, vertex_shader: Shader, fragment_shader: Shader) -> Result<(), ShaderError> {
+        self.layout = PipelineLayout::from_shaders(vec![&vertex_shader, &fragment_shader])?;
+        self.vertex_shader = vertex_shader;
+        self.fragment_shader = fragment_shader;
+        Ok(())
+    }
+
+    /// Returns the vertex buffers of the pipeline, either set explicitly or reflected from the vertex shader
+    pub fn vertex_buffer_descriptors(&self) -> &[VertexBufferDescriptor] {
+        match &self.vertex_buffers {
+            Some(vertex_buffers) => vertex_buffers,
+            None => &self.layout.vertex_buffer_descriptors,
+        }
+    }
+
+    /// Checks that the vertex buffers provide an attribute for every input of the vertex shader
+    pub fn validate(&self) -> Result<(), ShaderError> {
+        let vertex_buffers = self.vertex_buffer_descriptors();
+        let missing = self.layout.vertex_buffer_descriptors
+            .iter()
+            .flat_map(|buffer| buffer.attributes.iter())
+            .filter(|input| {
+                !vertex_buffers
+                    .iter()
+                    .flat_map(|buffer| buffer.attributes.iter())
+                    .any(|attribute| attribute.location == input.location)
+            })
+            .cloned()
+            .collect::<Vec<VertexAttributeDescriptor>>();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(ShaderError::MissingVertexAttributes(missing))
         }
     }
 
-    /// Returns a reference to the associated Pipeline layout
-    pub fn get_layout(&self) -> Option<&PipelineLayout> {
-        self.layout.as_ref()
+    /// Returns the key to look up an identical pipeline in the pipeline cache
+    pub fn cache_key(&self) -> PipelineKey {
+        PipelineKey {
+            vertex_shader: self.vertex_shader.key(),
+            fragment_shader: self.fragment_shader.key(),
+            vertex_entry_point: self.vertex_entry_point().to_string(),
+            fragment_entry_point: self.fragment_entry_point().to_string(),
+            vertex_buffers: self.vertex_buffers.clone(),
+            color_states: self.color_states.clone(),
+            rasterization_state: self.rasterization_state.clone(),
+            primitive_topology: self.primitive_topology,
+            depth_stencil_state: self.depth_stencil_state.clone(),
+            index_format: self.index_format,
+            sample_count: self.sample_count,
+            sample_mask: self.sample_mask,
+            alpha_to_coverage_enabled: self.alpha_to_coverage_enabled,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "glsl"))]
+mod tests {
+    use crate::{BlendDescriptor, PipelineBuilder, PipelineDescriptor, Renderer, Shader, ShaderError, ShaderStage, VertexBufferDescriptor, VertexFormat};
+
+    const VERTEX_SHADER: &str = r#"
+    #version 450
+
+    layout(set = 0, binding = 0) uniform Camera {
+        mat4 viewProjection;
+    };
+
+    void main() {
+        gl_Position = viewProjection * vec4(0.0, 0.0, 0.0, 1.0);
+    }
+    "#;
+
+    const FRAGMENT_SHADER: &str = r#"
+    #version 450
+
+    layout(location = 0) out vec4 outColor;
+
+    void main() {
+        outColor = vec4(1.0);
+    }
+    "#;
+
+    fn pipeline_descriptor(renderer: &Renderer) -> PipelineDescriptor {
+        let vertex_shader = Shader::compile(VERTEX_SHADER, ShaderStage::Vertex, &renderer.device).unwrap();
+        let fragment_shader = Shader::compile(FRAGMENT_SHADER, ShaderStage::Fragment, &renderer.device).unwrap();
+        PipelineDescriptor::new(vertex_shader, fragment_shader).unwrap()
+    }
+
+    #[test]
+    fn it_hashes_shaders_by_content_and_states_by_value() {
+        let renderer = futures::executor::block_on(Renderer::new()).unwrap();
+
+        let descriptor = pipeline_descriptor(&renderer);
+        let mut other = pipeline_descriptor(&renderer);
+        other.label = Some(String::from("Other"));
+        assert_eq!(descriptor.cache_key(), other.cache_key());
+
+        other.color_states[0].color_blend = BlendDescriptor::REPLACE;
+        assert_ne!(descriptor.cache_key(), other.cache_key());
+
+        let mut other = pipeline_descriptor(&renderer);
+        other.rasterization_state.as_mut().unwrap().depth_bias_slope_scale = 1.5;
+        assert_ne!(descriptor.cache_key(), other.cache_key());
+    }
+
+    #[test]
+    fn it_excludes_the_reflected_layout_from_the_key() {
+        let renderer = futures::executor::block_on(Renderer::new()).unwrap();
+
+        // every reflection generates new bind group ids, the layouts of identical shaders differ
+        let descriptor = pipeline_descriptor(&renderer);
+        let other = pipeline_descriptor(&renderer);
+        assert!(std::ptr::eq(descriptor.vertex_shader().layout(), descriptor.vertex_shader().clone().layout()));
+        assert_ne!(descriptor.layout().bind_groups, other.layout().bind_groups);
+        assert_eq!(descriptor.cache_key(), other.cache_key());
+    }
+
+    #[test]
+    fn it_reuses_pipelines_of_identical_descriptors() {
+        let mut renderer = futures::executor::block_on(Renderer::new()).unwrap();
+
+        let pipeline = renderer.create_pipeline(&pipeline_descriptor(&renderer)).unwrap();
+        assert_eq!(pipeline, renderer.create_pipeline(&pipeline_descriptor(&renderer)).unwrap());
+        assert_eq!(1, renderer.pipeline_count());
+
+        assert!(renderer.evict_pipeline(&pipeline));
+        assert!(!renderer.evict_pipeline(&pipeline));
+        assert!(!renderer.update_pipeline(&pipeline, &pipeline_descriptor(&renderer)).unwrap());
+        assert_eq!(0, renderer.pipeline_count());
+        assert_ne!(pipeline, renderer.create_pipeline(&pipeline_descriptor(&renderer)).unwrap());
+    }
+
+    #[test]
+    fn it_keeps_the_layout_in_sync_with_the_shaders() {
+        let mut renderer = futures::executor::block_on(Renderer::new()).unwrap();
+        let mut descriptor = pipeline_descriptor(&renderer);
+        let pipeline = renderer.create_pipeline(&descriptor).unwrap();
+
+        // replacing the shaders reflects the layout again, the pipeline is no longer found by the new key
+        let vertex_shader = Shader::compile(r#"
+        #version 450
+        layout(location = 0) in vec3 position;
+        layout(location = 1) in vec2 uv;
+        void main() { gl_Position = vec4(position, uv.x); }
+        "#, ShaderStage::Vertex, &renderer.device).unwrap();
+        let fragment_shader = descriptor.fragment_shader().clone();
+        descriptor.set_shaders(vertex_shader.clone(), fragment_shader.clone()).unwrap();
+        assert!(descriptor.layout().bind_groups.is_empty());
+        assert_eq!(2, descriptor.layout().vertex_buffer_descriptors.len());
+        assert_ne!(pipeline, renderer.create_pipeline(&descriptor).unwrap());
+
+        // vertex buffers have to provide all inputs of the vertex shader
+        let result = PipelineBuilder::new(vertex_shader, fragment_shader)
+            .vertex_buffers(vec![VertexBufferDescriptor::new(vec![VertexFormat::Float3])])
+            .build();
+        match result {
+            Err(ShaderError::MissingVertexAttributes(inputs)) => {
+                assert_eq!(1, inputs.len());
+                assert_eq!("uv", inputs[0].name);
+            }
+            result => panic!("Expected missing vertex attributes, got {:?}", result.map(|_| ())),
+        }
+
+        descriptor.vertex_buffers = Some(vec![VertexBufferDescriptor::new(vec![VertexFormat::Float3])]);
+        assert!(matches!(renderer.create_pipeline(&descriptor), Err(ShaderError::MissingVertexAttributes(_))));
     }
 }
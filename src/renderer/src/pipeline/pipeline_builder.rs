@@ -0,0 +1,270 @@
+use crate::{BlendMode, ColorStateDescriptor, CompareFunction, CullMode, DepthStencilStateDescriptor, FrontFace, IndexFormat, PipelineDescriptor, PipelineLayout, PrimitiveTopology, RasterizationStateDescriptor, Shader, ShaderError, TextureFormat, VertexBufferDescriptor};
+
+/// Configures the states of a render pipeline, unset states keep their defaults.
+///
+/// The entry points of both shaders are taken from their reflected layouts, unless overridden.
+///
+/// Example:
+/// let pipeline_descriptor = PipelineBuilder::new(vertex_shader, fragment_shader)
+///     .label("Particles")
+///     .cull_mode(CullMode::None)
+///     .depth_write(false)
+///     .blend(BlendMode::Additive)
+///     .build()?;
+/// let pipeline = renderer.create_pipeline(&pipeline_descriptor)?;
+///
+pub struct PipelineBuilder {
+    label: Option<String>,
+    vertex_shader: Shader,
+    fragment_shader: Shader,
+    vertex_entry_point: Option<String>,
+    fragment_entry_point: Option<String>,
+    vertex_buffers: Option<Vec<VertexBufferDescriptor>>,
+    color_states: Vec<ColorStateDescriptor>,
+    rasterization_state: RasterizationStateDescriptor,
+    primitive_topology: PrimitiveTopology,
+    depth_stencil_state: Option<DepthStencilStateDescriptor>,
+    index_format: IndexFormat,
+    sample_count: u32,
+}
+
+impl PipelineBuilder {
+    pub fn new(vertex_shader: Shader, fragment_shader: Shader) -> Self {
+        Self {
+            label: None,
+            vertex_shader,
+            fragment_shader,
+            vertex_entry_point: None,
+            fragment_entry_point: None,
+            vertex_buffers: None,
+            color_states: vec![ColorStateDescriptor::default()],
+            rasterization_state: RasterizationStateDescriptor::default(),
+            primitive_topology: PrimitiveTopology::TriangleList,
+            depth_stencil_state: Some(DepthStencilStateDescriptor::default()),
+            index_format: IndexFormat::Uint32,
+            sample_count: 1,
+        }
+    }
+
+    /// Sets the name of the pipeline, used for debugging
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Overrides the reflected entry point of the vertex shader
+    pub fn vertex_entry_point(mut self, entry_point: &str) -> Self {
+        self.vertex_entry_point = Some(entry_point.to_string());
+        self
+    }
+
+    /// Overrides the reflected entry point of the fragment shader
+    pub fn fragment_entry_point(mut self, entry_point: &str) -> Self {
+        self.fragment_entry_point = Some(entry_point.to_string());
+        self
+    }
+
+    /// Replaces the reflected vertex buffers, e.g. by the descriptor of `Mesh::map_attributes`
+    pub fn vertex_buffers(mut self, vertex_buffers: Vec<VertexBufferDescriptor>) -> Self {
+        self.vertex_buffers = Some(vertex_buffers);
+        self
+    }
+
+    /// Sets the way vertices are assembled into primitives
+    pub fn topology(mut self, topology: PrimitiveTopology) -> Self {
+        self.primitive_topology = topology;
+        self
+    }
+
+    /// Sets which faces are culled
+    pub fn cull_mode(mut self, cull_mode: CullMode) -> Self {
+        self.rasterization_state.cull_mode = cull_mode;
+        self
+    }
+
+    /// Sets the winding order of front faces
+    pub fn front_face(mut self, front_face: FrontFace) -> Self {
+        self.rasterization_state.front_face = front_face;
+        self
+    }
+
+    /// Sets the format of index buffers used with this pipeline
+    pub fn index_format(mut self, index_format: IndexFormat) -> Self {
+        self.index_format = index_format;
+        self
+    }
+
+    /// Enables the depth test with the given compare function
+    pub fn depth_test(mut self, compare: CompareFunction) -> Self {
+        self.depth_stencil_state().depth_compare = compare;
+        self
+    }
+
+    /// Enables or disables writing to the depth buffer, the depth test stays enabled
+    pub fn depth_write(mut self, enabled: bool) -> Self {
+        self.depth_stencil_state().depth_write_enabled = enabled;
+        self
+    }
+
+    /// Sets the format of the depth buffer
+    pub fn depth_format(mut self, format: TextureFormat) -> Self {
+        self.depth_stencil_state().format = format;
+        self
+    }
+
+    /// Disables the depth test, e.g. for render targets without depth buffer
+    pub fn no_depth(mut self) -> Self {
+        self.depth_stencil_state = None;
+        self
+    }
+
+    /// Sets the number of samples per pixel, MSAA
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Sets the format of all color targets
+    pub fn color_format(mut self, format: TextureFormat) -> Self {
+        for color_state in self.color_states.iter_mut() {
+            color_state.format = format;
+        }
+        self
+    }
+
+    /// Sets the blend preset of all color targets
+    pub fn blend(mut self, mode: BlendMode) -> Self {
+        self.color_states = self.color_states
+            .drain(..)
+            .map(|color_state| color_state.with_blend_mode(mode))
+            .collect();
+        self
+    }
+
+    /// Replaces all color targets, e.g. to render into multiple targets
+    pub fn color_states(mut self, color_states: Vec<ColorStateDescriptor>) -> Self {
+        self.color_states = color_states;
+        self
+    }
+
+    /// Creates the pipeline descriptor, fails if the layouts of both shaders are not compatible
+    /// or the vertex buffers do not provide all inputs of the vertex shader
+    pub fn build(self) -> Result<PipelineDescriptor, ShaderError> {
+        let layout = PipelineLayout::from_shaders(vec![&self.vertex_shader, &self.fragment_shader])?;
+
+        let descriptor = PipelineDescriptor {
+            label: self.label,
+            layout,
+            vertex_shader: self.vertex_shader,
+            fragment_shader: self.fragment_shader,
+            vertex_entry_point: self.vertex_entry_point,
+            fragment_entry_point: self.fragment_entry_point,
+            vertex_buffers: self.vertex_buffers,
+            color_states: self.color_states,
+            rasterization_state: Some(self.rasterization_state),
+            primitive_topology: self.primitive_topology,
+            depth_stencil_state: self.depth_stencil_state,
+            index_format: self.index_format,
+            sample_count: self.sample_count,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        };
+        descriptor.validate()?;
+
+        Ok(descriptor)
+    }
+
+    /// Returns the depth stencil state, enables it with defaults first if disabled
+    fn depth_stencil_state(&mut self) -> &mut DepthStencilStateDescriptor {
+        self.depth_stencil_state.get_or_insert_with(DepthStencilStateDescriptor::default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BlendDescriptor, BlendFactor, BlendMode};
+    #[cfg(feature = "glsl")]
+    use crate::{CompareFunction, CullMode, PipelineBuilder, PrimitiveTopology, Renderer, Shader, ShaderStage, TextureFormat};
+
+    #[cfg(feature = "glsl")]
+    const VERTEX_SHADER: &str = r#"
+    #version 450
+
+    void main() {
+        gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+    }
+    "#;
+
+    #[cfg(feature = "glsl")]
+    const FRAGMENT_SHADER: &str = r#"
+    #version 450
+
+    layout(location = 0) out vec4 outColor;
+
+    void main() {
+        outColor = vec4(1.0);
+    }
+    "#;
+
+    #[test]
+    fn it_maps_blend_presets_to_blend_descriptors() {
+        assert_eq!(BlendDescriptor::REPLACE, BlendMode::Opaque.color_blend());
+        assert_eq!(BlendDescriptor::REPLACE, BlendMode::Opaque.alpha_blend());
+        assert_eq!(
+            BlendDescriptor::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+            BlendMode::Alpha.color_blend(),
+        );
+        assert_eq!(
+            BlendDescriptor::new(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+            BlendMode::Premultiplied.color_blend(),
+        );
+        assert_eq!(
+            BlendDescriptor::new(BlendFactor::SrcAlpha, BlendFactor::One),
+            BlendMode::Additive.color_blend(),
+        );
+        assert_eq!(
+            BlendDescriptor::new(BlendFactor::DstColor, BlendFactor::Zero),
+            BlendMode::Multiply.color_blend(),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "glsl")]
+    fn it_builds_pipeline_descriptors() {
+        let renderer = futures::executor::block_on(Renderer::new()).unwrap();
+        let vertex_shader = Shader::compile(VERTEX_SHADER, ShaderStage::Vertex, &renderer.device).unwrap();
+        let fragment_shader = Shader::compile(FRAGMENT_SHADER, ShaderStage::Fragment, &renderer.device).unwrap();
+
+        let descriptor = PipelineBuilder::new(vertex_shader.clone(), fragment_shader.clone())
+            .label("Sprites")
+            .topology(PrimitiveTopology::TriangleStrip)
+            .cull_mode(CullMode::None)
+            .no_depth()
+            .depth_test(CompareFunction::LessEqual)
+            .sample_count(4)
+            .color_format(TextureFormat::Rgba8UnormSrgb)
+            .blend(BlendMode::Premultiplied)
+            .build()
+            .unwrap();
+
+        assert_eq!(Some(String::from("Sprites")), descriptor.label);
+        assert_eq!(None, descriptor.vertex_entry_point);
+        assert_eq!("main", descriptor.vertex_entry_point());
+        assert_eq!("main", descriptor.fragment_entry_point());
+        assert_eq!(PrimitiveTopology::TriangleStrip, descriptor.primitive_topology);
+        assert_eq!(CullMode::None, descriptor.rasterization_state.unwrap().cull_mode);
+        assert_eq!(CompareFunction::LessEqual, descriptor.depth_stencil_state.unwrap().depth_compare);
+        assert_eq!(4, descriptor.sample_count);
+        assert_eq!(TextureFormat::Rgba8UnormSrgb, descriptor.color_states[0].format);
+        assert_eq!(BlendMode::Premultiplied.alpha_blend(), descriptor.color_states[0].alpha_blend);
+
+        // overrides are kept when a shader is replaced, e.g. on reload
+        let mut descriptor = PipelineBuilder::new(vertex_shader.clone(), fragment_shader.clone())
+            .vertex_entry_point("vs_main")
+            .build()
+            .unwrap();
+        descriptor.set_shaders(vertex_shader, fragment_shader).unwrap();
+        assert_eq!("vs_main", descriptor.vertex_entry_point());
+        assert_eq!("main", descriptor.fragment_entry_point());
+    }
+}
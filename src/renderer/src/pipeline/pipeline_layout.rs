@@ -1,38 +1,109 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
-use crate::{BindGroupDescriptor, Shader, ShaderError, ShaderLayout, VertexBufferDescriptor};
+use crate::{BindGroupDescriptor, Shader, ShaderError, ShaderLayout, ShaderStage, VertexAttributeDescriptor, VertexBufferDescriptor};
 
-#[derive(Debug)]
+/// A varying that does not match between the outputs of the vertex & the inputs of the fragment shader
+#[derive(Debug, Clone, PartialEq)]
+pub enum VaryingMismatch {
+    /// The fragment shader reads a location the vertex shader does not write
+    Missing(VertexAttributeDescriptor),
+    /// The vertex shader writes a location the fragment shader does not read
+    Extra(VertexAttributeDescriptor),
+    /// Both stages declare the location with different types, vertex output first
+    TypeMismatch(VertexAttributeDescriptor, VertexAttributeDescriptor),
+}
+
+impl fmt::Display for VaryingMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            VaryingMismatch::Missing(input) => format!(
+                "fragment input '{}' at location {} is not written by the vertex shader", input.name, input.location,
+            ),
+            VaryingMismatch::Extra(output) => format!(
+                "vertex output '{}' at location {} is not read by the fragment shader", output.name, output.location,
+            ),
+            VaryingMismatch::TypeMismatch(output, input) => format!(
+                "vertex output '{}' ({:?}) does not match fragment input '{}' ({:?}) at location {}",
+                output.name, output.format, input.name, input.format, input.location,
+            ),
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Checks that the outputs of the vertex shader match the inputs of the fragment shader by location & type.
+///
+/// Missing inputs & type mismatches are errors, vertex outputs the fragment shader does not read
+/// are valid and returned as warnings.
+pub fn validate_stage_interface(vertex: &ShaderLayout, fragment: &ShaderLayout) -> Result<Vec<VaryingMismatch>, ShaderError> {
+    let inputs = fragment.inputs();
+    let mut mismatches = Vec::new();
+    let mut warnings = Vec::new();
+
+    for input in inputs.iter() {
+        match vertex.outputs.iter().find(|output| output.location == input.location) {
+            Some(output) if output.format != input.format => {
+                mismatches.push(VaryingMismatch::TypeMismatch(output.clone(), (*input).clone()));
+            }
+            Some(_) => (),
+            None => mismatches.push(VaryingMismatch::Missing((*input).clone())),
+        }
+    }
+
+    for output in vertex.outputs.iter() {
+        if !inputs.iter().any(|input| input.location == output.location) {
+            warnings.push(VaryingMismatch::Extra(output.clone()));
+        }
+    }
+
+    if mismatches.is_empty() {
+        Ok(warnings)
+    } else {
+        Err(ShaderError::InterfaceMismatch(mismatches))
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct PipelineLayout {
     /// The list of bind group descriptors
     pub bind_groups: Vec<BindGroupDescriptor>,
     /// The list of vertex buffer descriptors
     pub vertex_buffer_descriptors: Vec<VertexBufferDescriptor>,
+    /// Vertex outputs the fragment shader does not read
+    pub warnings: Vec<VaryingMismatch>,
 }
 
 impl PipelineLayout {
     /// Creates a pipeline layout from the list of given shaders
-    /// It checks all bind groups of the shaders and sees if they are the same for all shader stages.
+    /// It checks all bind groups of the shaders and sees if they are the same for all shader stages,
+    /// and that the outputs of the vertex shader match the inputs of the fragment shader.
     pub fn from_shaders(shaders: Vec<&Shader>) -> Result<Self, ShaderError> {
         let mut bind_groups = HashMap::<u32, BindGroupDescriptor>::new();
 
-        let mut shader_layouts = shaders
+        let shader_layouts = shaders
             .iter()
             .map(|shader| shader.layout())
-            .collect::<Vec<ShaderLayout>>();
+            .collect::<Vec<&ShaderLayout>>();
 
-        for shader_layout in shader_layouts.iter_mut() {
-            for shader_bind_group in shader_layout.bind_groups.iter_mut() {
+        for shader_layout in shader_layouts.iter() {
+            for shader_bind_group in shader_layout.bind_groups.iter() {
                 match bind_groups.get_mut(&shader_bind_group.index) {
                     Some(bind_group) => {
                         for shader_binding in shader_bind_group.bindings.iter() {
-                            if !shader_bind_group.contains(shader_binding) {
+                            if bind_group.contains(shader_binding)? {
+                                // the binding is shared, make it visible in this stage as well
+                                if let Some(binding) = bind_group.bindings.iter_mut().find(|b| b.index == shader_binding.index) {
+                                    binding.shader_stages |= shader_binding.shader_stages;
+                                }
+                            } else {
                                 bind_group.bindings.push(shader_binding.clone());
                             }
                         }
                     }
                     None => {
-                        bind_groups.insert(shader_bind_group.index, shader_bind_group.clone());
+                        // shader layouts are shared, the merged bind group gets an id of its own
+                        let bind_group = BindGroupDescriptor::new(shader_bind_group.index, shader_bind_group.bindings.clone());
+                        bind_groups.insert(shader_bind_group.index, bind_group);
                     }
                 }
             }
@@ -43,13 +114,149 @@ impl PipelineLayout {
             .map(|(_index, descriptor)| descriptor)
             .collect();
 
-        let vertex_buffer_descriptors = shader_layouts[0].vertex_buffer_descriptors.iter()
-            .map(|vb| vb.clone())
-            .collect::<Vec<VertexBufferDescriptor>>();
+        let vertex_layout = shader_layouts.iter().find(|layout| layout.stage == ShaderStage::Vertex);
+        let fragment_layout = shader_layouts.iter().find(|layout| layout.stage == ShaderStage::Fragment);
+        let warnings = match (vertex_layout, fragment_layout) {
+            (Some(vertex_layout), Some(fragment_layout)) => validate_stage_interface(vertex_layout, fragment_layout)?,
+            _ => Vec::new(),
+        };
+
+        // vertex buffers are only consumed by the vertex stage
+        let vertex_buffer_descriptors = vertex_layout
+            .map(|layout| layout.vertex_buffer_descriptors.clone())
+            .unwrap_or_default();
 
         Ok(PipelineLayout {
             bind_groups,
             vertex_buffer_descriptors,
+            warnings,
         })
     }
 }
+
+#[cfg(all(test, feature = "glsl"))]
+mod tests {
+    use crate::{PipelineLayout, Renderer, Shader, ShaderError, ShaderStage, ShaderStages, VaryingMismatch, VertexFormat};
+
+    const VERTEX_SHADER: &str = r#"
+    #version 450
+
+    layout(location = 0) in vec3 i_position;
+    layout(location = 1) in vec3 i_normal;
+
+    layout(location = 0) out vec3 o_normal;
+    layout(location = 1) out vec2 o_uv;
+
+    void main() {
+        o_normal = i_normal;
+        o_uv = i_position.xy;
+        gl_Position = vec4(i_position, 1.0);
+    }
+    "#;
+
+    #[test]
+    fn it_validates_vertex_outputs_against_fragment_inputs() {
+        let renderer = futures::executor::block_on(Renderer::new()).unwrap();
+        let compile = |source: &str, stage| Shader::compile(source, stage, &renderer.device).unwrap();
+        let vertex_shader = compile(VERTEX_SHADER, ShaderStage::Vertex);
+
+        let fragment_shader = compile(r#"
+        #version 450
+        layout(location = 0) in vec3 normal;
+        layout(location = 1) in vec2 uv;
+        layout(location = 0) out vec4 out_color;
+        void main() { out_color = vec4(normal, uv.x); }
+        "#, ShaderStage::Fragment);
+
+        // vertex buffers are taken from the vertex stage, regardless of the order of shaders
+        let layout = PipelineLayout::from_shaders(vec![&fragment_shader, &vertex_shader]).unwrap();
+        assert_eq!(2, layout.vertex_buffer_descriptors.len());
+        assert_eq!("i_position", layout.vertex_buffer_descriptors[0].attributes[0].name);
+        assert!(layout.warnings.is_empty());
+
+        // unread vertex outputs are only warnings
+        let fragment_shader = compile(r#"
+        #version 450
+        layout(location = 0) in vec3 normal;
+        layout(location = 0) out vec4 out_color;
+        void main() { out_color = vec4(normal, 1.0); }
+        "#, ShaderStage::Fragment);
+
+        let layout = PipelineLayout::from_shaders(vec![&vertex_shader, &fragment_shader]).unwrap();
+        assert_eq!(1, layout.warnings.len());
+        assert!(matches!(&layout.warnings[0], VaryingMismatch::Extra(output) if output.name == "o_uv"));
+
+        let fragment_shader = compile(r#"
+        #version 450
+        layout(location = 0) in vec4 normal;
+        layout(location = 2) in float depth;
+        layout(location = 0) out vec4 out_color;
+        void main() { out_color = normal * depth; }
+        "#, ShaderStage::Fragment);
+
+        match PipelineLayout::from_shaders(vec![&vertex_shader, &fragment_shader]) {
+            Err(ShaderError::InterfaceMismatch(mismatches)) => {
+                assert_eq!(2, mismatches.len());
+                match &mismatches[0] {
+                    VaryingMismatch::TypeMismatch(output, input) => {
+                        assert_eq!(VertexFormat::Float3, output.format);
+                        assert_eq!(VertexFormat::Float4, input.format);
+                    }
+                    mismatch => panic!("Unexpected mismatch {:?}", mismatch),
+                }
+                assert!(matches!(&mismatches[1], VaryingMismatch::Missing(input) if input.name == "depth"));
+            }
+            result => panic!("Expected interface mismatch, got {:?}", result.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn it_merges_bindings_of_all_stages() {
+        let renderer = futures::executor::block_on(Renderer::new()).unwrap();
+        let compile = |source: &str, stage| Shader::compile(source, stage, &renderer.device).unwrap();
+
+        let vertex_shader = compile(r#"
+        #version 450
+        layout(location = 0) in vec3 i_position;
+        layout(location = 0) out vec2 o_uv;
+        layout(set = 0, binding = 0) uniform Camera { mat4 view_proj; };
+        void main() {
+            o_uv = i_position.xy;
+            gl_Position = view_proj * vec4(i_position, 1.0);
+        }
+        "#, ShaderStage::Vertex);
+
+        let fragment_shader = compile(r#"
+        #version 450
+        layout(location = 0) in vec2 uv;
+        layout(location = 0) out vec4 out_color;
+        layout(set = 0, binding = 0) uniform Camera { mat4 view_proj; };
+        layout(set = 0, binding = 1) uniform sampler2D u_texture;
+        void main() { out_color = view_proj * texture(u_texture, uv); }
+        "#, ShaderStage::Fragment);
+
+        let layout = PipelineLayout::from_shaders(vec![&vertex_shader, &fragment_shader]).unwrap();
+        assert_eq!(1, layout.bind_groups.len());
+
+        let bind_group = &layout.bind_groups[0];
+        assert_eq!(2, bind_group.bindings.len());
+        assert_eq!(ShaderStages::VERTEX | ShaderStages::FRAGMENT, bind_group.find_binding("Camera").unwrap().shader_stages);
+        assert_eq!(ShaderStages::FRAGMENT, bind_group.find_binding("u_texture").unwrap().shader_stages);
+
+        let fragment_shader = compile(r#"
+        #version 450
+        layout(location = 0) in vec2 uv;
+        layout(location = 0) out vec4 out_color;
+        layout(set = 0, binding = 0) uniform sampler2D u_texture;
+        void main() { out_color = texture(u_texture, uv); }
+        "#, ShaderStage::Fragment);
+
+        match PipelineLayout::from_shaders(vec![&vertex_shader, &fragment_shader]) {
+            Err(ShaderError::InconsistentBinding(set, binding)) => {
+                assert_eq!(0, set);
+                assert_eq!(0, binding);
+            }
+            result => panic!("Expected inconsistent binding, got {:?}", result.map(|_| ())),
+        }
+    }
+}
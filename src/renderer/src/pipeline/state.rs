@@ -1,5 +1,6 @@
 use crate::TextureFormat;
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ColorStateDescriptor {
     pub format: TextureFormat,
     pub alpha_blend: BlendDescriptor,
@@ -18,6 +19,15 @@ impl Default for ColorStateDescriptor {
     }
 }
 
+impl ColorStateDescriptor {
+    /// Replaces color & alpha blending with the given preset
+    pub fn with_blend_mode(mut self, mode: BlendMode) -> Self {
+        self.color_blend = mode.color_blend();
+        self.alpha_blend = mode.alpha_blend();
+        self
+    }
+}
+
 impl From<&ColorStateDescriptor> for wgpu::ColorStateDescriptor {
     fn from(val: &ColorStateDescriptor) -> Self {
         wgpu::ColorStateDescriptor {
@@ -29,7 +39,7 @@ impl From<&ColorStateDescriptor> for wgpu::ColorStateDescriptor {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BlendDescriptor {
     pub src_factor: BlendFactor,
     pub dst_factor: BlendFactor,
@@ -47,6 +57,15 @@ impl From<&BlendDescriptor> for wgpu::BlendDescriptor {
 }
 
 impl BlendDescriptor {
+    /// Creates a blend descriptor that adds source & destination weighted by the given factors
+    pub const fn new(src_factor: BlendFactor, dst_factor: BlendFactor) -> Self {
+        Self {
+            src_factor,
+            dst_factor,
+            operation: BlendOperation::Add,
+        }
+    }
+
     pub const COLOR_BLEND: Self = Self {
         src_factor: BlendFactor::SrcAlpha,
         dst_factor: BlendFactor::OneMinusSrcAlpha,
@@ -66,7 +85,47 @@ impl BlendDescriptor {
     };
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Commonly used blend presets, each maps to a color & an alpha `BlendDescriptor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// No blending, the output replaces the target
+    Opaque,
+    /// Straight alpha blending, `src * src_alpha + dst * (1 - src_alpha)`
+    Alpha,
+    /// Blending of colors already multiplied by alpha, `src + dst * (1 - src_alpha)`
+    Premultiplied,
+    /// Adds the output weighted by its alpha to the target, e.g. for particles & glow
+    Additive,
+    /// Multiplies the output with the target, `src * dst`
+    Multiply,
+}
+
+impl BlendMode {
+    /// Returns the blend descriptor of the color channels
+    pub fn color_blend(&self) -> BlendDescriptor {
+        match self {
+            BlendMode::Opaque => BlendDescriptor::REPLACE,
+            BlendMode::Alpha => BlendDescriptor::COLOR_BLEND,
+            BlendMode::Premultiplied => BlendDescriptor::new(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+            BlendMode::Additive => BlendDescriptor::new(BlendFactor::SrcAlpha, BlendFactor::One),
+            BlendMode::Multiply => BlendDescriptor::new(BlendFactor::DstColor, BlendFactor::Zero),
+        }
+    }
+
+    /// Returns the blend descriptor of the alpha channel
+    pub fn alpha_blend(&self) -> BlendDescriptor {
+        match self {
+            BlendMode::Opaque => BlendDescriptor::REPLACE,
+            BlendMode::Alpha | BlendMode::Premultiplied => {
+                BlendDescriptor::new(BlendFactor::One, BlendFactor::OneMinusSrcAlpha)
+            }
+            BlendMode::Additive => BlendDescriptor::ALPHA_BLEND,
+            BlendMode::Multiply => BlendDescriptor::new(BlendFactor::DstAlpha, BlendFactor::Zero),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BlendFactor {
     Zero,
     One,
@@ -103,7 +162,7 @@ impl From<BlendFactor> for wgpu::BlendFactor {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BlendOperation {
     Add,
     Subtract,
@@ -148,7 +207,7 @@ impl From<ColorWrite> for wgpu::ColorWrite {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CompareFunction {
     Never,
     Less,
@@ -0,0 +1,419 @@
+use crate::{UniformMember, UniformProperty};
+
+/// The memory layout rules of a GLSL interface block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutRule {
+    /// Layout of uniform blocks, arrays & structs are aligned to 16 bytes
+    Std140,
+    /// Layout of storage buffer blocks, arrays & structs are tightly aligned
+    Std430,
+}
+
+/// The computed layout of a single uniform property
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformLayout {
+    /// Byte offset relative to the start of the enclosing struct
+    pub offset: u64,
+    /// Size in bytes, including padding of arrays & structs
+    pub size: u64,
+    /// Base alignment in bytes
+    pub align: u64,
+    /// Stride between array elements, only set for arrays & matrices
+    pub array_stride: Option<u64>,
+    /// Layouts of all members, only set for structs
+    pub members: Vec<UniformLayout>,
+}
+
+/// The location of a member inside a uniform block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformField {
+    /// Byte offset relative to the start of the block
+    pub offset: u64,
+    /// The type of the member
+    pub property: UniformProperty,
+}
+
+/// Rounds the value up to the next multiple of align
+fn round_up(value: u64, align: u64) -> u64 {
+    ((value + align - 1) / align) * align
+}
+
+/// Returns the alignment rounded up to a vec4 under std140 rules
+fn array_align(align: u64, rule: LayoutRule) -> u64 {
+    match rule {
+        LayoutRule::Std140 => round_up(align, 16),
+        LayoutRule::Std430 => align,
+    }
+}
+
+impl UniformProperty {
+    /// Returns the column type & number of columns, if the property is a matrix.
+    /// Matrices are laid out like arrays of column vectors.
+    fn columns(&self) -> Option<(UniformProperty, usize)> {
+        match self {
+            UniformProperty::Mat2 => Some((UniformProperty::Vec2, 2)),
+            UniformProperty::Mat3 => Some((UniformProperty::Vec3, 3)),
+            UniformProperty::Mat4 => Some((UniformProperty::Vec4, 4)),
+            _ => None,
+        }
+    }
+
+    /// Returns the base alignment of the property
+    pub fn align(&self, rule: LayoutRule) -> u64 {
+        if let Some((column, _)) = self.columns() {
+            return array_align(column.align(rule), rule);
+        }
+
+        match self {
+            UniformProperty::UInt | UniformProperty::Int | UniformProperty::Float => 4,
+            UniformProperty::IVec2 | UniformProperty::UVec2 | UniformProperty::Vec2 => 8,
+            UniformProperty::IVec3 | UniformProperty::UVec3 | UniformProperty::Vec3 => 16,
+            UniformProperty::IVec4 | UniformProperty::UVec4 | UniformProperty::Vec4 => 16,
+            UniformProperty::Array(property, _) => array_align(property.align(rule), rule),
+            UniformProperty::Struct(members) => {
+                let align = members.iter().map(|m| m.property.align(rule)).max().unwrap_or(4);
+                array_align(align, rule)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the layout of the property placed at the given offset
+    pub fn layout_at(&self, offset: u64, rule: LayoutRule) -> UniformLayout {
+        let align = self.align(rule);
+
+        let (size, array_stride, members) = if let Some((column, count)) = self.columns() {
+            let stride = round_up(column.layout_at(0, rule).size, align);
+            (stride * count as u64, Some(stride), Vec::new())
+        } else {
+            match self {
+                UniformProperty::Array(property, length) => {
+                    let element = property.layout_at(0, rule);
+                    let stride = round_up(element.size, align);
+                    (stride * *length as u64, Some(stride), Vec::new())
+                }
+                UniformProperty::Struct(members) => {
+                    let mut end = 0;
+                    let members = members
+                        .iter()
+                        .map(|UniformMember { property, .. }| {
+                            let member = property.layout_at(round_up(end, property.align(rule)), rule);
+                            end = member.offset + member.size;
+                            member
+                        })
+                        .collect::<Vec<UniformLayout>>();
+                    (round_up(end, align), None, members)
+                }
+                UniformProperty::Vec3 | UniformProperty::IVec3 | UniformProperty::UVec3 => (4 * 3, None, Vec::new()),
+                property => (property.align(rule), None, Vec::new()),
+            }
+        };
+
+        UniformLayout {
+            offset,
+            size,
+            align,
+            array_stride,
+            members,
+        }
+    }
+
+    /// Returns the layout of the property as the root of a block
+    pub fn layout(&self, rule: LayoutRule) -> UniformLayout {
+        self.layout_at(0, rule)
+    }
+
+    /// Returns the padded size of the property
+    pub fn size(&self, rule: LayoutRule) -> u64 {
+        self.layout(rule).size
+    }
+
+    /// Creates a struct of named members, the member offsets follow the layout rule
+    pub fn structure(members: Vec<(&str, UniformProperty)>, rule: LayoutRule) -> Self {
+        let mut end = 0;
+        let members = members
+            .into_iter()
+            .map(|(name, property)| {
+                let offset = round_up(end, property.align(rule));
+                end = offset + property.size(rule);
+                UniformMember::new(name, offset, property)
+            })
+            .collect();
+
+        UniformProperty::Struct(members)
+    }
+
+    /// Returns true if both properties have the same types & member offsets, names are ignored
+    pub fn same_layout(&self, other: &UniformProperty) -> bool {
+        match (self, other) {
+            (UniformProperty::Struct(lhs), UniformProperty::Struct(rhs)) => {
+                lhs.len() == rhs.len() && lhs.iter().zip(rhs.iter()).all(|(l, r)| {
+                    l.offset == r.offset && l.property.same_layout(&r.property)
+                })
+            }
+            (UniformProperty::Array(lhs, l), UniformProperty::Array(rhs, r)) => l == r && lhs.same_layout(rhs),
+            (lhs, rhs) => lhs == rhs,
+        }
+    }
+
+    /// Finds a member by its path, e.g. `lights[2].color`, and returns its offset relative to
+    /// the start of the block. A leading block or instance name as in `ubo.lights[2].color`
+    /// is skipped. Member offsets are taken from the struct, array strides follow the layout rule.
+    pub fn find(&self, path: &str, rule: LayoutRule) -> Option<UniformField> {
+        let mut segments = path.split('.').collect::<Vec<&str>>();
+        if let UniformProperty::Struct(members) = self {
+            let name = segments[0].split('[').next().unwrap_or_default();
+            if segments.len() > 1 && !members.iter().any(|m| m.name == name) {
+                segments.remove(0);
+            }
+        }
+
+        let mut offset = 0;
+        let mut property = self;
+        for segment in segments {
+            let mut parts = segment.split('[');
+            let name = parts.next()?;
+
+            let member = match property {
+                UniformProperty::Struct(members) => members.iter().find(|m| m.name == name)?,
+                _ => return None,
+            };
+            offset += member.offset;
+            property = &member.property;
+
+            for index in parts {
+                let index = index.strip_suffix(']')?.parse::<usize>().ok()?;
+                match property {
+                    UniformProperty::Array(element, length) if index < *length => {
+                        offset += index as u64 * property.layout(rule).array_stride.unwrap_or(0);
+                        property = element;
+                    }
+                    _ => return None,
+                }
+            }
+        }
+
+        Some(UniformField {
+            offset,
+            property: property.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LayoutRule, UniformField, UniformLayout, UniformProperty};
+    #[cfg(feature = "glsl")]
+    use crate::{BindingType, Renderer, Shader, ShaderLayout, ShaderStage};
+
+    fn offsets(property: &UniformProperty, rule: LayoutRule) -> Vec<u64> {
+        property.layout(rule).members.iter().map(|m| m.offset).collect()
+    }
+
+    fn lights_block(rule: LayoutRule) -> UniformProperty {
+        // struct Light { vec2 position; float intensity; };
+        // uniform Lights { float count; Light lights[2]; float time; };
+        let light = UniformProperty::structure(
+            vec![("position", UniformProperty::Vec2), ("intensity", UniformProperty::Float)],
+            rule,
+        );
+        UniformProperty::structure(
+            vec![
+                ("count", UniformProperty::Float),
+                ("lights", UniformProperty::Array(Box::new(light), 2)),
+                ("time", UniformProperty::Float),
+            ],
+            rule,
+        )
+    }
+
+    #[test]
+    fn it_lays_out_vec3_followed_by_mat4() {
+        // layout(binding = 0) uniform Uniforms { vec3 light; mat4 modelView; } ubo;
+        let block = UniformProperty::structure(
+            vec![("light", UniformProperty::Vec3), ("modelView", UniformProperty::Mat4)],
+            LayoutRule::Std140,
+        );
+
+        assert_eq!(vec![0, 16], offsets(&block, LayoutRule::Std140));
+        assert_eq!(vec![0, 16], offsets(&block, LayoutRule::Std430));
+        assert_eq!(80, block.size(LayoutRule::Std140));
+        assert_eq!(80, block.size(LayoutRule::Std430));
+    }
+
+    #[test]
+    fn it_lays_out_matrices() {
+        // uniform UniformBufferObject { mat4 modelViewProjection; mat4 modelView; mat3 normalMatrix; } ubo;
+        let block = UniformProperty::structure(
+            vec![
+                ("modelViewProjection", UniformProperty::Mat4),
+                ("modelView", UniformProperty::Mat4),
+                ("normalMatrix", UniformProperty::Mat3),
+            ],
+            LayoutRule::Std140,
+        );
+
+        let layout = block.layout(LayoutRule::Std140);
+        assert_eq!(vec![0, 64, 128], offsets(&block, LayoutRule::Std140));
+        assert_eq!(Some(16), layout.members[2].array_stride);
+        assert_eq!(48, layout.members[2].size);
+        assert_eq!(176, layout.size);
+
+        assert_eq!(Some(16), UniformProperty::Mat2.layout(LayoutRule::Std140).array_stride);
+        assert_eq!(Some(8), UniformProperty::Mat2.layout(LayoutRule::Std430).array_stride);
+    }
+
+    #[test]
+    fn it_packs_scalars_after_vectors() {
+        // uniform Locals { vec3 u_cameraPos; vec2 u_resolution; float u_time; };
+        let block = UniformProperty::structure(
+            vec![
+                ("u_cameraPos", UniformProperty::Vec3),
+                ("u_resolution", UniformProperty::Vec2),
+                ("u_time", UniformProperty::Float),
+            ],
+            LayoutRule::Std140,
+        );
+
+        assert_eq!(vec![0, 16, 24], offsets(&block, LayoutRule::Std140));
+        assert_eq!(32, block.size(LayoutRule::Std140));
+
+        let block = UniformProperty::structure(
+            vec![("position", UniformProperty::IVec3), ("index", UniformProperty::UInt)],
+            LayoutRule::Std140,
+        );
+        assert_eq!(vec![0, 12], offsets(&block, LayoutRule::Std140));
+        assert_eq!(16, block.size(LayoutRule::Std140));
+    }
+
+    #[test]
+    fn it_lays_out_arrays() {
+        let floats = UniformProperty::Array(Box::new(UniformProperty::Float), 4);
+        assert_eq!(Some(16), floats.layout(LayoutRule::Std140).array_stride);
+        assert_eq!(64, floats.size(LayoutRule::Std140));
+        assert_eq!(Some(4), floats.layout(LayoutRule::Std430).array_stride);
+        assert_eq!(16, floats.size(LayoutRule::Std430));
+
+        let vectors = UniformProperty::Array(Box::new(UniformProperty::Vec3), 2);
+        assert_eq!(Some(16), vectors.layout(LayoutRule::Std430).array_stride);
+        assert_eq!(32, vectors.size(LayoutRule::Std430));
+    }
+
+    #[test]
+    fn it_aligns_nested_structs() {
+        let block = lights_block(LayoutRule::Std140);
+        let std140 = block.layout(LayoutRule::Std140);
+        assert_eq!(vec![0, 16, 48], offsets(&block, LayoutRule::Std140));
+        assert_eq!(Some(16), std140.members[1].array_stride);
+        assert_eq!(64, std140.size);
+
+        let block = lights_block(LayoutRule::Std430);
+        let std430 = block.layout(LayoutRule::Std430);
+        assert_eq!(vec![0, 8, 40], offsets(&block, LayoutRule::Std430));
+        assert_eq!(Some(16), std430.members[1].array_stride);
+        assert_eq!(48, std430.size);
+    }
+
+    #[test]
+    fn it_finds_members_by_path() {
+        let block = lights_block(LayoutRule::Std140);
+
+        assert_eq!(
+            Some(UniformField { offset: 48, property: UniformProperty::Float }),
+            block.find("time", LayoutRule::Std140),
+        );
+        assert_eq!(
+            Some(UniformField { offset: 40, property: UniformProperty::Float }),
+            block.find("lights[1].intensity", LayoutRule::Std140),
+        );
+        assert_eq!(
+            Some(UniformField { offset: 32, property: UniformProperty::Vec2 }),
+            block.find("ubo.lights[1].position", LayoutRule::Std140),
+        );
+        assert_eq!(None, block.find("lights[2].position", LayoutRule::Std140));
+        assert_eq!(None, block.find("lights[0].color", LayoutRule::Std140));
+        assert_eq!(None, block.find("count[0]", LayoutRule::Std140));
+    }
+
+    #[test]
+    fn it_compares_layouts_without_names() {
+        let block = lights_block(LayoutRule::Std140);
+        let renamed = UniformProperty::structure(
+            vec![
+                ("num_lights", UniformProperty::Float),
+                ("lights", UniformProperty::Array(Box::new(UniformProperty::structure(
+                    vec![("pos", UniformProperty::Vec2), ("power", UniformProperty::Float)],
+                    LayoutRule::Std140,
+                )), 2)),
+                ("elapsed", UniformProperty::Float),
+            ],
+            LayoutRule::Std140,
+        );
+
+        assert!(block.same_layout(&renamed));
+        assert!(!block.same_layout(&lights_block(LayoutRule::Std430)));
+    }
+
+    /// Asserts that the reflected member offsets match the computed layout, including nested structs
+    #[cfg(feature = "glsl")]
+    fn assert_reflected_offsets(property: &UniformProperty, layout: &UniformLayout) {
+        let members = match property {
+            UniformProperty::Struct(members) => members,
+            UniformProperty::Array(element, _) => return assert_reflected_offsets(element, &element.layout(LayoutRule::Std140)),
+            _ => return,
+        };
+
+        assert_eq!(
+            members.iter().map(|m| m.offset).collect::<Vec<u64>>(),
+            layout.members.iter().map(|m| m.offset).collect::<Vec<u64>>(),
+        );
+        for (member, member_layout) in members.iter().zip(layout.members.iter()) {
+            assert_reflected_offsets(&member.property, member_layout);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "glsl")]
+    fn it_matches_offsets_of_reflected_std140_block() {
+        const VERTEX_SHADER: &str = r#"
+        #version 450
+
+        struct Light {
+            vec3 position;
+            float intensity;
+            vec4 color;
+        };
+
+        layout(set = 0, binding = 0) uniform Uniforms {
+            vec3 cameraPosition;
+            mat4 viewProjection;
+            float exposure;
+            vec2 jitter[3];
+            Light lights[2];
+            mat3 normalMatrix;
+        } ubo;
+
+        void main() {
+            vec3 position = ubo.normalMatrix * (ubo.cameraPosition + ubo.lights[1].position);
+            float intensity = ubo.exposure * ubo.lights[0].intensity * ubo.lights[1].color.a;
+            gl_Position = ubo.viewProjection * vec4(position + vec3(ubo.jitter[2], 0.0), intensity);
+        }
+        "#;
+
+        let renderer = futures::executor::block_on(Renderer::new()).unwrap();
+        let shader = Shader::compile(VERTEX_SHADER, ShaderStage::Vertex, &renderer.device).unwrap();
+        let layout = ShaderLayout::from_shader(&shader);
+
+        let property = match &layout.bind_groups[0].bindings[0].binding_type {
+            BindingType::Uniform { property, .. } => property.clone(),
+            binding_type => panic!("Unexpected binding type {:?}", binding_type),
+        };
+
+        let computed = property.layout(LayoutRule::Std140);
+        assert_eq!(vec![0, 16, 80, 96, 144, 208], offsets(&property, LayoutRule::Std140));
+        assert_eq!(256, computed.size);
+        assert_eq!(Some(16), computed.members[3].array_stride);
+        assert_eq!(Some(32), computed.members[4].array_stride);
+        assert_reflected_offsets(&property, &computed);
+    }
+}
@@ -0,0 +1,40 @@
+use crate::{BindGroupId, ComputePipelineId, Renderer, wgpu_resources::WgpuResources};
+
+pub struct ComputePass<'a> {
+    /// The reference to all resources of the Renderer
+    resources: &'a WgpuResources,
+    /// Internal reference to ComputePass
+    pub compute_pass: wgpu::ComputePass<'a>,
+}
+
+impl<'a> ComputePass<'a> {
+    /// Initializes a Compute Pass to provide useful API functions
+    pub fn new(
+        renderer: &'a Renderer,
+        compute_pass: wgpu::ComputePass<'a>,
+    ) -> Self {
+        Self {
+            resources: &renderer.resources,
+            compute_pass,
+        }
+    }
+
+    /// Sets the Compute Pipeline
+    pub fn set_pipeline(&mut self, pipeline_id: &ComputePipelineId) -> &mut Self {
+        let pipeline = self.resources.get_compute_pipeline(pipeline_id);
+        self.compute_pass.set_pipeline(pipeline);
+        self
+    }
+
+    /// Sets the bind group at the given index
+    pub fn set_bind_group(&mut self, index: u32, bind_group_id: &BindGroupId) -> &mut Self {
+        let bind_group = self.resources.get_bind_group(bind_group_id);
+        self.compute_pass.set_bind_group(index, bind_group, &[]);
+        self
+    }
+
+    /// Dispatches the given number of workgroups in x, y & z
+    pub fn dispatch(&mut self, x: u32, y: u32, z: u32) {
+        self.compute_pass.dispatch(x, y, z);
+    }
+}
@@ -1,8 +1,10 @@
 pub mod color;
+pub mod compute_pass;
 pub mod pass;
 pub mod render_pass;
 
 pub use color::Color;
+pub use compute_pass::ComputePass;
 pub use pass::*;
 pub use render_pass::RenderPass;
 
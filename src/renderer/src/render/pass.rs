@@ -1,9 +1,28 @@
 use crate::{Color, Operations, Surface, Texture, Window};
 
+/// The target a color attachment renders into
+#[derive(Debug)]
+pub enum ColorAttachment<'a> {
+    /// The current frame of a window surface
+    Surface(&'a mut Surface),
+    /// An offscreen texture, e.g. for headless rendering
+    Texture(&'a Texture),
+}
+
+impl<'a> ColorAttachment<'a> {
+    /// Returns the texture view to render into
+    pub fn view(&mut self) -> &wgpu::TextureView {
+        match self {
+            ColorAttachment::Surface(surface) => surface.texture(),
+            ColorAttachment::Texture(texture) => &texture.view,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct RenderPassColorAttachmentDescriptor<'a> {
-    /// The color attachment, e.g. Surface
-    pub attachment: &'a mut Surface,
+    /// The color attachment, e.g. Surface or Texture
+    pub attachment: ColorAttachment<'a>,
     /// The resolve target, e.g. Texture
     pub resolve_target: Option<&'a Texture>,
     /// The clear color operation
@@ -41,7 +60,7 @@ impl<'a> From<&'a mut Window> for PassDescriptor<'a> {
     fn from(window: &'a mut Window) -> Self {
         let color = Color::BLACK;
         let color_attachment = RenderPassColorAttachmentDescriptor {
-            attachment: &mut window.surface,
+            attachment: ColorAttachment::Surface(&mut window.surface),
             resolve_target: None,
             ops: Operations::new(color),
         };
@@ -57,3 +76,18 @@ impl<'a> From<&'a mut Window> for PassDescriptor<'a> {
         }
     }
 }
+
+impl<'a> From<&'a Texture> for PassDescriptor<'a> {
+    fn from(texture: &'a Texture) -> Self {
+        let color_attachment = RenderPassColorAttachmentDescriptor {
+            attachment: ColorAttachment::Texture(texture),
+            resolve_target: None,
+            ops: Operations::new(Color::BLACK),
+        };
+
+        Self {
+            color_attachments: vec![color_attachment],
+            depth_stencil_attachment: None,
+        }
+    }
+}
@@ -55,6 +55,11 @@ impl<'a> RenderPass<'a> {
         self
     }
 
+    /// Draws primitives from the active vertex buffers
+    pub fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        self.render_pass.draw(vertices, instances);
+    }
+
     /// Draws the content of the pipeline using indices
     pub fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
         self.render_pass.draw_indexed(indices, base_vertex, instances);
@@ -1,8 +1,10 @@
 use std::sync::Arc;
 
-use wgpu::{BufferUsage, util::DeviceExt};
+use wgpu::util::DeviceExt;
 
-use crate::{BindGroupDescriptor, BindGroupDescriptorId, BindGroupId, IndexBuffer, Indices, PassDescriptor, PipelineDescriptor, RenderPass, RenderPipelineId, Shader, ShaderStage, Surface, Texture, TextureDescriptor, TextureFormat, VertexBuffer, WgpuVertexBufferDescriptor, wgpu_resources::WgpuResources};
+use crate::{BindGroupBuilder, BindGroupDescriptor, BindGroupEntry, BindGroupError, BindGroupId, BindingResource, Buffer, BufferDescriptor, BufferUsage, ComputePass, ComputePipelineDescriptor, ComputePipelineId, IndexBuffer, Indices, PassDescriptor, PipelineDescriptor, PipelineLayout, RenderPass, RenderPipelineId, RendererError, RendererOptions, SamplerDescriptor, SamplerId, Shader, ShaderCache, ShaderDefs, ShaderError, ShaderIncludes, ShaderSource, ShaderStage, ShaderVariants, Surface, Texture, TextureDescriptor, TextureFormat, Uniform, UniformBuffer, UniformError, VertexBuffer, validate_uniform, padded_bytes_per_row, unpad_rows, WgpuVertexBufferDescriptor, renderer_options::limits_supported, wgpu_resources::WgpuResources};
+#[cfg(feature = "glsl")]
+use crate::{PipelineBuilder, ShaderCompileOptions, ShaderVariantKey};
 
 pub struct RenderPassHandle {}
 
@@ -44,46 +46,95 @@ impl CommandEncoder {
 pub struct Renderer {
     /// The WGPU instance, used to create Adapters or Surfaces
     pub instance: wgpu::Instance,
+    /// The selected adapter, the physical graphics device
+    pub adapter: wgpu::Adapter,
     /// The link / connection to the graphics device, useful to create objects
     pub device: Arc<wgpu::Device>,
+    /// The command queue of the device, used to submit recorded command buffers
+    pub queue: wgpu::Queue,
     /// The encoder to begin / finish the render pass
     pub command_encoder: CommandEncoder,
+    /// Resolves `#include` directives of all shaders created by the renderer
+    pub shader_includes: ShaderIncludes,
+    /// Cache of all compiled shader variants
+    pub shader_variants: ShaderVariants,
+    /// Optional on-disk cache of compiled shaders, shared between runs
+    pub shader_cache: Option<ShaderCache>,
+    /// Collects compiler warnings into created shaders instead of suppressing them
+    pub shader_warnings: bool,
     /// The list of all WGPU specific resources, only visible to crate
     pub(crate) resources: WgpuResources,
 }
 
 impl Renderer {
-    pub async fn new() -> Self {
-        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+    /// Creates a new Renderer with default options
+    pub async fn new() -> Result<Self, RendererError> {
+        Self::with_options(&RendererOptions::default()).await
+    }
+
+    /// Creates a new Renderer, requests adapter & device as configured in the given options
+    pub async fn with_options(options: &RendererOptions) -> Result<Self, RendererError> {
+        let instance = wgpu::Instance::new(options.backends);
 
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
+                power_preference: options.power_preference,
                 compatible_surface: None,
             })
             .await
-            .expect("Unable to find GPU!");
+            .ok_or(RendererError::AdapterNotFound)?;
 
-        let trace_path = Some(std::path::Path::new("wgpu_trace"));
+        if !adapter.features().contains(options.features) {
+            return Err(RendererError::UnsupportedFeatures(options.features - adapter.features()));
+        }
+        if !limits_supported(&options.limits, &adapter.limits()) {
+            return Err(RendererError::UnsupportedLimits(options.limits.clone()));
+        }
 
-        let (device, _queue) = adapter
+        let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
-                    limits: wgpu::Limits::default(),
-                    shader_validation: true,
+                    features: options.features,
+                    limits: options.limits.clone(),
+                    shader_validation: options.shader_validation,
                 },
-                trace_path
+                options.trace_path.as_deref(),
             )
             .await
-            .expect("Failed to request device.");
+            .map_err(|e| RendererError::RequestDeviceFailed(e.to_string()))?;
 
-        Self {
+        Ok(Self {
             instance,
+            adapter,
             device: Arc::new(device),
+            queue,
             command_encoder: CommandEncoder::default(),
+            shader_includes: ShaderIncludes::default(),
+            shader_variants: ShaderVariants::default(),
+            shader_cache: options.shader_cache.clone(),
+            shader_warnings: options.shader_warnings,
             resources: WgpuResources::default(),
-        }
+        })
+    }
+
+    /// Returns information about the selected adapter, e.g. name & backend
+    pub fn adapter_info(&self) -> wgpu::AdapterInfo {
+        self.adapter.get_info()
+    }
+
+    /// Returns the best limits the adapter supports
+    pub fn adapter_limits(&self) -> wgpu::Limits {
+        self.adapter.limits()
+    }
+
+    /// Returns the limits the device was created with
+    pub fn limits(&self) -> wgpu::Limits {
+        self.device.limits()
+    }
+
+    /// Returns the features the device was created with
+    pub fn features(&self) -> wgpu::Features {
+        self.device.features()
     }
 
     /// Creates a new bind group layout
@@ -97,7 +148,7 @@ impl Renderer {
                 .map(|binding| {
                     wgpu::BindGroupLayoutEntry {
                         binding: binding.index,
-                        visibility: binding.shader_stage.into(),
+                        visibility: binding.shader_stages.into(),
                         ty: (&binding.binding_type).into(),
                         count: None,
                     }
@@ -114,28 +165,54 @@ impl Renderer {
         }
     }
 
-    /// Creates a new Bind Group with some data
+    /// Creates a new Bind Group from the resources attached to the builder.
+    ///
+    /// All resources are validated against the reflected bind group descriptor first.
     pub fn create_bind_group(
         &mut self,
-        bind_group_descriptor_id: &BindGroupDescriptorId,
-    ) -> BindGroupId {
-        // fetch bind group layout
+        builder: &BindGroupBuilder,
+    ) -> Result<BindGroupId, BindGroupError> {
+        let bindings = builder.resolve()?;
+
+        self.create_bind_group_layout(builder.descriptor);
         let bind_group_layout = self.resources
-            .get_bind_group_layout(bind_group_descriptor_id)
+            .get_bind_group_layout(&builder.descriptor.id)
             .expect("Bind Group Layout not found");
 
-        BindGroupId::new()
-    }
+        let entries = bindings
+            .iter()
+            .map(|(index, resource)| {
+                let resource = match resource {
+                    BindingResource::Buffer(buffer) => {
+                        wgpu::BindingResource::Buffer(self.resources.get_buffer(buffer).slice(..))
+                    }
+                    BindingResource::Texture(texture) => wgpu::BindingResource::TextureView(&texture.view),
+                    BindingResource::Sampler(sampler) => {
+                        wgpu::BindingResource::Sampler(self.resources.get_sampler(sampler))
+                    }
+                };
 
-    /// This creates and stores a new wgpu::RenderPipeline, the function returns an ID
-    /// to reference it later
-    pub fn create_pipeline(
-        &mut self,
-        pipeline_descriptor: &PipelineDescriptor,
-    ) -> RenderPipelineId {
-        let layout = pipeline_descriptor.get_layout().unwrap();
-        dbg!(&layout.bind_groups);
+                wgpu::BindGroupEntry {
+                    binding: *index,
+                    resource,
+                }
+            })
+            .collect::<Vec<wgpu::BindGroupEntry>>();
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: bind_group_layout,
+            entries: entries.as_slice(),
+        });
+
+        let bind_group_id = BindGroupId::new();
+        self.resources.bind_groups.insert(bind_group_id, bind_group);
+
+        Ok(bind_group_id)
+    }
 
+    /// Creates the wgpu pipeline layout including all bind group layouts
+    fn create_pipeline_layout(&mut self, layout: &PipelineLayout) -> wgpu::PipelineLayout {
         layout.bind_groups
             .iter()
             .for_each(|bind_group| self.create_bind_group_layout(bind_group));
@@ -145,21 +222,107 @@ impl Renderer {
             .map(|descriptor| self.resources.get_bind_group_layout(&descriptor.id).unwrap())
             .collect::<Vec<&wgpu::BindGroupLayout>>();
 
-        let pipeline_layout =
-            self.device
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("Pipeline layout"),
-                    bind_group_layouts: bind_group_layouts_ref.as_slice(),
-                    push_constant_ranges: &[],
-                });
+        self.device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Pipeline layout"),
+                bind_group_layouts: bind_group_layouts_ref.as_slice(),
+                push_constant_ranges: &[],
+            })
+    }
+
+    /// This creates and stores a new wgpu::RenderPipeline, the function returns an ID
+    /// to reference it later.
+    ///
+    /// Pipelines are cached, an identical descriptor returns the ID of the existing pipeline.
+    /// Fails if the vertex buffers do not provide all inputs of the vertex shader.
+    pub fn create_pipeline(
+        &mut self,
+        pipeline_descriptor: &PipelineDescriptor,
+    ) -> Result<RenderPipelineId, ShaderError> {
+        let key = pipeline_descriptor.cache_key();
+        if let Some(pipeline_id) = self.resources.render_pipeline_keys.get(&key) {
+            return Ok(*pipeline_id);
+        }
+
+        pipeline_descriptor.validate()?;
+        let pipeline_id = RenderPipelineId::new();
+        let pipeline = self.build_pipeline(pipeline_descriptor);
+        self.resources.render_pipelines.insert(pipeline_id, pipeline);
+        self.resources.render_pipeline_keys.insert(key, pipeline_id);
+
+        Ok(pipeline_id)
+    }
+
+    /// Rebuilds the render pipeline from the given descriptor, e.g. after a shader changed.
+    ///
+    /// The pipeline id stays valid and refers to the new pipeline afterwards.
+    /// Returns false and builds nothing if there is no pipeline with the given id, e.g. it was evicted.
+    /// Fails if the vertex buffers do not provide all inputs of the vertex shader, the previous pipeline is kept.
+    pub fn update_pipeline(
+        &mut self,
+        pipeline_id: &RenderPipelineId,
+        pipeline_descriptor: &PipelineDescriptor,
+    ) -> Result<bool, ShaderError> {
+        if !self.resources.render_pipelines.contains_key(pipeline_id) {
+            return Ok(false);
+        }
+
+        pipeline_descriptor.validate()?;
+        Ok(self.rebuild_pipeline(pipeline_id, pipeline_descriptor))
+    }
+
+    /// Rebuilds the render pipeline from an already validated descriptor, returns false if there is
+    /// no pipeline with the given id
+    pub(crate) fn rebuild_pipeline(
+        &mut self,
+        pipeline_id: &RenderPipelineId,
+        pipeline_descriptor: &PipelineDescriptor,
+    ) -> bool {
+        if !self.resources.render_pipelines.contains_key(pipeline_id) {
+            return false;
+        }
+
+        let pipeline = self.build_pipeline(pipeline_descriptor);
+        self.resources.render_pipelines.insert(*pipeline_id, pipeline);
+
+        // the previous descriptor no longer matches the pipeline
+        self.resources.render_pipeline_keys.retain(|_, id| id != pipeline_id);
+        self.resources.render_pipeline_keys
+            .entry(pipeline_descriptor.cache_key())
+            .or_insert(*pipeline_id);
+        true
+    }
+
+    /// Removes the render pipeline, the pipeline id is no longer valid afterwards.
+    ///
+    /// Returns false if there is no pipeline with the given id.
+    pub fn evict_pipeline(&mut self, pipeline_id: &RenderPipelineId) -> bool {
+        self.resources.render_pipeline_keys.retain(|_, id| id != pipeline_id);
+        self.resources.render_pipelines.remove(pipeline_id).is_some()
+    }
+
+    /// Removes all render pipelines, all pipeline ids are no longer valid afterwards
+    pub fn evict_all_pipelines(&mut self) {
+        self.resources.render_pipeline_keys.clear();
+        self.resources.render_pipelines.clear();
+    }
+
+    /// Returns the number of render pipelines
+    pub fn pipeline_count(&self) -> usize {
+        self.resources.render_pipelines.len()
+    }
+
+    /// Creates the wgpu::RenderPipeline from the given descriptor
+    fn build_pipeline(&mut self, pipeline_descriptor: &PipelineDescriptor) -> wgpu::RenderPipeline {
+        let pipeline_layout = self.create_pipeline_layout(pipeline_descriptor.layout());
 
         // set up shaders
         let vertex_stage = wgpu::ProgrammableStageDescriptor {
             module: &pipeline_descriptor.vertex_shader.module,
-            entry_point: "main",
+            entry_point: pipeline_descriptor.vertex_entry_point(),
         };
 
-        let vertex_buffer_descriptors = layout.vertex_buffer_descriptors
+        let vertex_buffer_descriptors = pipeline_descriptor.vertex_buffer_descriptors()
             .iter()
             .map(|v| v.into())
             .collect::<Vec<WgpuVertexBufferDescriptor>>();
@@ -174,7 +337,7 @@ impl Renderer {
 
         let fragment_stage = wgpu::ProgrammableStageDescriptor {
             module: &pipeline_descriptor.fragment_shader.module,
-            entry_point: "main",
+            entry_point: pipeline_descriptor.fragment_entry_point(),
         };
 
         let rasterization_state = pipeline_descriptor.rasterization_state
@@ -206,9 +369,30 @@ impl Renderer {
             alpha_to_coverage_enabled: pipeline_descriptor.alpha_to_coverage_enabled,
         };
 
-        let pipeline_id = RenderPipelineId::new();
-        let pipeline = self.device.create_render_pipeline(&render_pipeline_descriptor);
-        self.resources.render_pipelines.insert(pipeline_id, pipeline);
+        self.device.create_render_pipeline(&render_pipeline_descriptor)
+    }
+
+    /// This creates and stores a new wgpu::ComputePipeline, the function returns an ID
+    /// to reference it later
+    pub fn create_compute_pipeline(
+        &mut self,
+        pipeline_descriptor: &ComputePipelineDescriptor,
+    ) -> ComputePipelineId {
+        let pipeline_layout = self.create_pipeline_layout(&pipeline_descriptor.layout);
+
+        let compute_stage = wgpu::ProgrammableStageDescriptor {
+            module: &pipeline_descriptor.compute_shader.module,
+            entry_point: &pipeline_descriptor.entry_point,
+        };
+
+        let pipeline = self.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: pipeline_descriptor.label.as_ref().map(|label| label.as_str()),
+            layout: Some(&pipeline_layout),
+            compute_stage,
+        });
+
+        let pipeline_id = ComputePipelineId::new();
+        self.resources.compute_pipelines.insert(pipeline_id, pipeline);
 
         pipeline_id
     }
@@ -219,7 +403,7 @@ impl Renderer {
             &wgpu::util::BufferInitDescriptor {
                 label: Some("Vertex Buffer"),
                 contents: data,
-                usage: BufferUsage::VERTEX,
+                usage: wgpu::BufferUsage::VERTEX,
             }
         );
 
@@ -232,6 +416,91 @@ impl Renderer {
         vertex_buffer_id
     }
 
+    /// Creates a new buffer without initial content
+    pub fn create_buffer(&mut self, descriptor: &BufferDescriptor) -> Buffer {
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: descriptor.label.as_ref().map(|label| label.as_str()),
+            size: descriptor.size,
+            usage: descriptor.usage.into(),
+            mapped_at_creation: false,
+        });
+
+        let buffer_id = Buffer::new(descriptor.size, descriptor.usage);
+        self.resources.buffers.insert(buffer_id, buffer);
+
+        buffer_id
+    }
+
+    /// Creates a new buffer initialized with the given data
+    pub fn create_buffer_with_data(&mut self, data: &[u8], usage: BufferUsage) -> Buffer {
+        let buffer = self.device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: data,
+                usage: usage.into(),
+            }
+        );
+
+        let buffer_id = Buffer::new(data.len() as u64, usage);
+        self.resources.buffers.insert(buffer_id, buffer);
+
+        buffer_id
+    }
+
+    /// Writes the data into the buffer at the given offset
+    pub fn write_buffer(&self, buffer: &Buffer, offset: u64, data: &[u8]) {
+        let buffer = self.resources.get_buffer(buffer);
+        self.queue.write_buffer(buffer, offset, data);
+    }
+
+    /// Creates a new uniform buffer initialized with the given value.
+    ///
+    /// The type is validated against the reflected uniform block of the binding first.
+    pub fn create_uniform_buffer<T: Uniform>(
+        &mut self,
+        binding: &BindGroupEntry,
+        value: &T,
+    ) -> Result<UniformBuffer<T>, UniformError> {
+        validate_uniform::<T>(binding)?;
+
+        let buffer = self.create_buffer_with_data(
+            bytemuck::bytes_of(value),
+            BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+        );
+
+        Ok(UniformBuffer::new(buffer, &binding.name))
+    }
+
+    /// Updates the content of the uniform buffer
+    pub fn update_uniform_buffer<T: Uniform>(&self, uniform_buffer: &UniformBuffer<T>, value: &T) {
+        self.write_buffer(&uniform_buffer.buffer, 0, bytemuck::bytes_of(value));
+    }
+
+    /// Updates a single member of the uniform block by its path, e.g. `ubo.lights[2].color`.
+    ///
+    /// The binding must be the one the uniform buffer was created for.
+    pub fn update_uniform_field<T: Uniform, U: Uniform>(
+        &self,
+        uniform_buffer: &UniformBuffer<U>,
+        binding: &BindGroupEntry,
+        path: &str,
+        value: &T,
+    ) -> Result<(), UniformError> {
+        let field = uniform_buffer.field::<T>(binding, path)?;
+        self.write_buffer(&uniform_buffer.buffer, field.offset, bytemuck::bytes_of(value));
+        Ok(())
+    }
+
+    /// Creates a new texture sampler
+    pub fn create_sampler(&mut self, descriptor: &SamplerDescriptor) -> SamplerId {
+        let sampler = self.device.create_sampler(&descriptor.into());
+
+        let sampler_id = SamplerId::new(descriptor.compare.is_some());
+        self.resources.samplers.insert(sampler_id, sampler);
+
+        sampler_id
+    }
+
     /// Creates a new index buffer
     pub fn create_index_buffer(&mut self, indices: &Indices) -> IndexBuffer {
         let buffer = self.device.create_buffer_init(
@@ -272,16 +541,239 @@ impl Renderer {
             );
             run_pass(&mut render_pass);
         }
+        self.command_encoder.set(encoder);
+    }
+
+    /// Creates a new compute pass, recorded into the same encoder as render passes
+    pub fn begin_compute_pass(
+        &mut self,
+        run_pass: &mut dyn Fn(&mut ComputePass),
+    ) {
+        if !self.command_encoder.is_some() {
+            self.command_encoder.create(&self.device);
+        }
+        let mut encoder = self.command_encoder.take().unwrap();
+        {
+            let wgpu_compute_pass = encoder.begin_compute_pass();
+            let mut compute_pass = ComputePass::new(
+                self,
+                wgpu_compute_pass,
+            );
+            run_pass(&mut compute_pass);
+        }
+        self.command_encoder.set(encoder);
+    }
+
+    /// Begins a new frame, all render passes until `end_frame` are recorded into the same encoder
+    pub fn begin_frame(&mut self) {
+        if !self.command_encoder.is_some() {
+            self.command_encoder.create(&self.device);
+        }
+    }
+
+    /// Finishes the current command encoder and submits the recorded commands to the queue
+    pub fn submit(&mut self) {
+        if let Some(encoder) = self.command_encoder.take() {
+            self.queue.submit(Some(encoder.finish()));
+        }
+    }
+
+    /// Ends the current frame, submits all recorded commands and presents the surface frame
+    pub fn end_frame(&mut self, surface: &mut Surface) {
+        self.submit();
+        surface.present();
+    }
+
+    /// Copies the content of a 2d texture back into an RGBA image.
+    ///
+    /// All render passes recorded so far are submitted before the texture is read.
+    pub fn read_texture(&mut self, texture: &Texture) -> Result<image::RgbaImage, RendererError> {
+        let swizzle = match texture.texture_format() {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+            format => return Err(RendererError::UnsupportedTextureFormat(format)),
+        };
+
+        let width = texture.width();
+        let height = texture.height();
+        let unpadded_bytes_per_row = width * 4;
+        let padded_bytes_per_row = padded_bytes_per_row(width, 4);
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        self.begin_frame();
+        let mut encoder = self.command_encoder.take().unwrap();
+        encoder.copy_texture_to_buffer(
+            wgpu::TextureCopyView {
+                texture: &texture.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            wgpu::BufferCopyView {
+                buffer: &buffer,
+                layout: wgpu::TextureDataLayout {
+                    offset: 0,
+                    bytes_per_row: padded_bytes_per_row,
+                    rows_per_image: height,
+                },
+            },
+            wgpu::Extent3d { width, height, depth: 1 },
+        );
+        self.command_encoder.set(encoder);
+        self.submit();
+
+        let buffer_slice = buffer.slice(..);
+        let mapping = buffer_slice.map_async(wgpu::MapMode::Read);
+        self.device.poll(wgpu::Maintain::Wait);
+        futures::executor::block_on(mapping).map_err(|_| RendererError::BufferMapFailed)?;
+
+        let mut data = {
+            let mapped = buffer_slice.get_mapped_range();
+            unpad_rows(&mapped, padded_bytes_per_row, unpadded_bytes_per_row)
+        };
+        buffer.unmap();
+
+        if swizzle {
+            data.chunks_exact_mut(4).for_each(|pixel| pixel.swap(0, 2));
+        }
+
+        Ok(image::RgbaImage::from_raw(width, height, data).unwrap())
     }
 
-    /// Creates a new shader
+    /// Copies the content of the buffer back into host memory, e.g. the results of a compute pass.
+    ///
+    /// The buffer requires the COPY_SRC usage, all passes recorded so far are submitted first.
+    pub fn read_buffer(&mut self, buffer: &Buffer) -> Result<Vec<u8>, RendererError> {
+        let staging_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: buffer.size,
+            usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        self.begin_frame();
+        let mut encoder = self.command_encoder.take().unwrap();
+        encoder.copy_buffer_to_buffer(self.resources.get_buffer(buffer), 0, &staging_buffer, 0, buffer.size);
+        self.command_encoder.set(encoder);
+        self.submit();
+
+        let buffer_slice = staging_buffer.slice(..);
+        let mapping = buffer_slice.map_async(wgpu::MapMode::Read);
+        self.device.poll(wgpu::Maintain::Wait);
+        futures::executor::block_on(mapping).map_err(|_| RendererError::BufferMapFailed)?;
+
+        let data = buffer_slice.get_mapped_range().to_vec();
+        staging_buffer.unmap();
+
+        Ok(data)
+    }
+
+    /// Creates a new shader, compiler errors are returned as diagnostics
+    #[cfg(feature = "glsl")]
     pub fn create_shader(
         &self,
         source: &str,
         stage: ShaderStage,
-    ) -> Shader {
-        // TODO use better error handling, return useful Result
-        Shader::compile(source, stage, &self.device).expect("Failed to compile shader")
+    ) -> Result<Shader, ShaderError> {
+        self.compile_shader(source, &format!("{}_shader.glsl", stage), stage, &ShaderDefs::default())
+    }
+
+    /// Creates the variant of the shader compiled with the given defines.
+    ///
+    /// Each variant is compiled once, subsequent calls return the cached variant
+    /// until one of its included files changes.
+    #[cfg(feature = "glsl")]
+    pub fn create_shader_variant(
+        &mut self,
+        source: &str,
+        stage: ShaderStage,
+        defs: &ShaderDefs,
+    ) -> Result<Shader, ShaderError> {
+        let key = ShaderVariantKey::new(source, stage, defs);
+        if let Some(shader) = self.shader_variants.get(&key, &self.shader_includes) {
+            return Ok(shader.clone());
+        }
+
+        let shader = self.compile_shader(source, &format!("{}_shader.glsl", stage), stage, defs)?;
+        self.shader_variants.insert(key, shader.clone());
+
+        Ok(shader)
+    }
+
+    /// Creates a pipeline descriptor from the permutation of vertex & fragment shader
+    /// selected by the given defines
+    #[cfg(feature = "glsl")]
+    pub fn create_pipeline_descriptor(
+        &mut self,
+        vertex_source: &str,
+        fragment_source: &str,
+        defs: &ShaderDefs,
+    ) -> Result<PipelineDescriptor, ShaderError> {
+        let vertex_shader = self.create_shader_variant(vertex_source, ShaderStage::Vertex, defs)?;
+        let fragment_shader = self.create_shader_variant(fragment_source, ShaderStage::Fragment, defs)?;
+
+        PipelineBuilder::new(vertex_shader, fragment_shader).build()
+    }
+
+    /// Creates a shader from either GLSL source or precompiled SPIR-V binary
+    pub fn create_shader_from_source(
+        &self,
+        source: &ShaderSource,
+        stage: ShaderStage,
+    ) -> Result<Shader, ShaderError> {
+        match source {
+            #[cfg(feature = "glsl")]
+            ShaderSource::Glsl(source) => self.compile_shader(source, &format!("{}_shader.glsl", stage), stage, &ShaderDefs::default()),
+            ShaderSource::SpirV(spirv) => Shader::from_spirv(spirv.clone(), stage, &self.device),
+        }
+    }
+
+    /// Loads and compiles a shader from file, includes are resolved relative to the file
+    /// and by the shader includes of the renderer. Files with `.spv` extension are loaded as SPIR-V binary.
+    pub fn create_shader_from_file(
+        &self,
+        file_path: &std::path::PathBuf,
+        stage: ShaderStage,
+    ) -> Result<Shader, ShaderError> {
+        self.create_shader_variant_from_file(file_path, stage, &ShaderDefs::default())
+    }
+
+    /// Loads and compiles the variant of a shader file with the given defines,
+    /// SPIR-V binaries are already compiled and can only be loaded without defines.
+    pub fn create_shader_variant_from_file(
+        &self,
+        file_path: &std::path::PathBuf,
+        stage: ShaderStage,
+        defs: &ShaderDefs,
+    ) -> Result<Shader, ShaderError> {
+        match ShaderSource::from_file(file_path)? {
+            #[cfg(feature = "glsl")]
+            ShaderSource::Glsl(source) => self.compile_shader(&source, &format!("{}", file_path.display()), stage, defs),
+            ShaderSource::SpirV(spirv) if defs.is_empty() => Shader::from_spirv(spirv, stage, &self.device),
+            ShaderSource::SpirV(_) => Err(ShaderError::CompileError(
+                format!("'{}' is a SPIR-V binary, defines cannot be applied", file_path.display()),
+            )),
+        }
+    }
+
+    /// Compiles the shader with the includes & cache of the renderer
+    #[cfg(feature = "glsl")]
+    fn compile_shader(
+        &self,
+        source: &str,
+        file_name: &str,
+        stage: ShaderStage,
+        defs: &ShaderDefs,
+    ) -> Result<Shader, ShaderError> {
+        let options = ShaderCompileOptions::new(&self.shader_includes, defs)
+            .with_cache(self.shader_cache.as_ref())
+            .with_warnings(self.shader_warnings);
+        Shader::compile_with_options(source, file_name, stage, &options, &self.device)
     }
 
     /// Creates surface in context of renderer
@@ -323,12 +815,9 @@ fn create_render_pass<'a>(
         .iter_mut()
         .map(|descriptor| {
             wgpu::RenderPassColorAttachmentDescriptor {
-                attachment: descriptor.attachment.texture(),
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                    store: true,
-                },
+                attachment: descriptor.attachment.view(),
+                resolve_target: descriptor.resolve_target.map(|texture| &texture.view),
+                ops: (&descriptor.ops).into(),
             }
         })
         .collect::<Vec<wgpu::RenderPassColorAttachmentDescriptor>>();
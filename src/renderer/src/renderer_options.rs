@@ -0,0 +1,152 @@
+use std::{fmt, path::PathBuf};
+
+use crate::{ShaderCache, TextureFormat};
+
+#[derive(Debug)]
+pub enum RendererError {
+    /// No adapter found that matches the requested backends & power preference
+    AdapterNotFound,
+    /// The adapter does not support all requested features
+    UnsupportedFeatures(wgpu::Features),
+    /// The requested limits exceed the limits of the adapter
+    UnsupportedLimits(wgpu::Limits),
+    /// Failed to request the device from the adapter
+    RequestDeviceFailed(String),
+    /// The texture format is not supported for the requested operation
+    UnsupportedTextureFormat(TextureFormat),
+    /// Failed to map a buffer into host memory
+    BufferMapFailed,
+}
+
+impl fmt::Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            RendererError::AdapterNotFound => String::from("Unable to find a suitable GPU adapter"),
+            RendererError::UnsupportedFeatures(features) => format!("Adapter does not support features: {:?}", features),
+            RendererError::UnsupportedLimits(limits) => format!("Adapter does not support limits: {:?}", limits),
+            RendererError::RequestDeviceFailed(error) => format!("Failed to request device: {}", error),
+            RendererError::UnsupportedTextureFormat(format) => format!("Unsupported texture format {:?}", format),
+            RendererError::BufferMapFailed => String::from("Failed to map buffer"),
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::error::Error for RendererError {}
+
+/// Description of how the Renderer selects the adapter and requests the device
+#[derive(Debug, Clone)]
+pub struct RendererOptions {
+    /// The graphics backends to consider when requesting an adapter
+    pub backends: wgpu::BackendBit,
+    /// Power preference of the adapter, e.g. discrete vs integrated GPU
+    pub power_preference: wgpu::PowerPreference,
+    /// Optional directory to write an API trace into
+    pub trace_path: Option<PathBuf>,
+    /// Features the device is required to support
+    pub features: wgpu::Features,
+    /// Limits the device is required to support
+    pub limits: wgpu::Limits,
+    /// Enables validation of shader modules
+    pub shader_validation: bool,
+    /// Optional on-disk cache of compiled shaders
+    pub shader_cache: Option<ShaderCache>,
+    /// Collects shader compiler warnings instead of suppressing them
+    pub shader_warnings: bool,
+}
+
+impl Default for RendererOptions {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::BackendBit::PRIMARY,
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            trace_path: None,
+            features: wgpu::Features::empty(),
+            limits: wgpu::Limits::default(),
+            shader_validation: true,
+            shader_cache: None,
+            shader_warnings: false,
+        }
+    }
+}
+
+impl RendererOptions {
+    /// Sets the backends to request an adapter from
+    pub fn with_backends(mut self, backends: wgpu::BackendBit) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    /// Sets the power preference of the adapter
+    pub fn with_power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    /// Sets the directory to write the API trace into
+    pub fn with_trace_path<P: Into<PathBuf>>(mut self, trace_path: P) -> Self {
+        self.trace_path = Some(trace_path.into());
+        self
+    }
+
+    /// Sets the required features of the device
+    pub fn with_features(mut self, features: wgpu::Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Sets the required limits of the device
+    pub fn with_limits(mut self, limits: wgpu::Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Enables or disables shader validation
+    pub fn with_shader_validation(mut self, shader_validation: bool) -> Self {
+        self.shader_validation = shader_validation;
+        self
+    }
+
+    /// Sets the cache to store compiled shaders in
+    pub fn with_shader_cache(mut self, shader_cache: ShaderCache) -> Self {
+        self.shader_cache = Some(shader_cache);
+        self
+    }
+
+    /// Enables or disables collecting shader compiler warnings
+    pub fn with_shader_warnings(mut self, shader_warnings: bool) -> Self {
+        self.shader_warnings = shader_warnings;
+        self
+    }
+}
+
+/// Returns true if all requested limits are within the given supported limits
+pub(crate) fn limits_supported(requested: &wgpu::Limits, supported: &wgpu::Limits) -> bool {
+    requested.max_bind_groups <= supported.max_bind_groups
+        && requested.max_dynamic_uniform_buffers_per_pipeline_layout <= supported.max_dynamic_uniform_buffers_per_pipeline_layout
+        && requested.max_dynamic_storage_buffers_per_pipeline_layout <= supported.max_dynamic_storage_buffers_per_pipeline_layout
+        && requested.max_sampled_textures_per_shader_stage <= supported.max_sampled_textures_per_shader_stage
+        && requested.max_samplers_per_shader_stage <= supported.max_samplers_per_shader_stage
+        && requested.max_storage_buffers_per_shader_stage <= supported.max_storage_buffers_per_shader_stage
+        && requested.max_storage_textures_per_shader_stage <= supported.max_storage_textures_per_shader_stage
+        && requested.max_uniform_buffers_per_shader_stage <= supported.max_uniform_buffers_per_shader_stage
+        && requested.max_uniform_buffer_binding_size <= supported.max_uniform_buffer_binding_size
+        && requested.max_push_constant_size <= supported.max_push_constant_size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::limits_supported;
+
+    #[test]
+    fn it_checks_requested_limits() {
+        let supported = wgpu::Limits::default();
+        assert!(limits_supported(&wgpu::Limits::default(), &supported));
+
+        let requested = wgpu::Limits {
+            max_bind_groups: 8,
+            ..Default::default()
+        };
+        assert!(!limits_supported(&requested, &supported));
+    }
+}
@@ -1,6 +1,16 @@
 pub mod shader;
+pub mod shader_cache;
+pub mod shader_defs;
+pub mod shader_diagnostics;
+pub mod shader_includes;
 pub mod shader_reflect;
+pub mod shader_watcher;
 pub mod spirv;
 
 pub use shader::*;
+pub use shader_cache::*;
+pub use shader_defs::*;
+pub use shader_diagnostics::*;
+pub use shader_includes::*;
 pub use shader_reflect::*;
+pub use shader_watcher::*;
@@ -1,9 +1,13 @@
-use std::{borrow::Cow, fmt, fs::File, io::Read, path};
+use std::{borrow::Cow, collections::hash_map::DefaultHasher, fmt, fs::File, hash::{Hash, Hasher}, io::Read, path, sync::Arc};
+#[cfg(feature = "glsl")]
+use std::cell::RefCell;
 use fmt::Display;
 use wgpu::ShaderModuleSource;
 use spirv_reflect::types::ReflectShaderStageFlags;
 
-use crate::ShaderLayout;
+use crate::{shader_reflect::reflect, spirv::{spirv_from_bytes, validate_spirv}, IncludeDependency, ShaderDefs, ShaderDiagnostics, ShaderLayout, VaryingMismatch, VertexAttributeDescriptor};
+#[cfg(feature = "glsl")]
+use crate::{ShaderCache, ShaderIncludes};
 
 type ShaderResult = Result<Shader, ShaderError>;
 
@@ -12,7 +16,9 @@ pub enum CompilerError {
     /// The compiler could not be loaded
     CompilerNotLoaded,
     /// The compiler did not compile the shader correctly
-    CompilationFailed(String),
+    CompilationFailed(ShaderDiagnostics),
+    /// The compiler failed for other reasons than errors in the source
+    InternalError(String),
     /// Cannot access the Compiler
     AccessBlocked,
 }
@@ -22,36 +28,81 @@ impl Display for CompilerError {
         let s = match self {
             CompilerError::AccessBlocked => String::from("Access to Compiler is blocked"),
             CompilerError::CompilerNotLoaded => String::from("Failed to load compiler"),
-            CompilerError::CompilationFailed(error) => format!("Failed to compile shader: {}", error),
+            CompilerError::CompilationFailed(diagnostics) => format!("Failed to compile shader:\n{}", diagnostics),
+            CompilerError::InternalError(error) => format!("Internal compiler error: {}", error),
         };
         write!(f, "{}", s)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ShaderError {
     /// The source kind of the shader is not supported
+    #[cfg(feature = "glsl")]
     UnsupportedShaderKind(shaderc::ShaderKind),
     /// Wraps any CompilerError values
     CompileError(String),
+    /// The shader source contains errors
+    CompilationFailed(ShaderDiagnostics),
     /// Failed to open file
     OpenFileFailed(String),
     /// Failed to read from source file
     ReadFromFileFailed(String),
+    /// The SPIR-V binary is not a valid module
+    InvalidSpirV(String),
+    /// Failed to watch shader files for changes
+    WatchFailed(String),
+    /// The outputs of the vertex shader do not match the inputs of the fragment shader
+    InterfaceMismatch(Vec<VaryingMismatch>),
+    /// The shader stages were compiled with different values for the same define
+    ConflictingDefines(String),
+    /// The file is a GLSL source, but GLSL compilation is disabled, contains the file name
+    GlslUnsupported(String),
+    /// The shader is not of the stage the pipeline requires, contains expected & found stage
+    WrongStage(ShaderStage, ShaderStage),
+    /// A binding differs between shader stages, contains the set & binding index
+    InconsistentBinding(u32, u32),
+    /// The vertex buffers do not provide these inputs of the vertex shader
+    MissingVertexAttributes(Vec<VertexAttributeDescriptor>),
 }
 
 impl Display for ShaderError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
+            #[cfg(feature = "glsl")]
             ShaderError::UnsupportedShaderKind(kind) => format!("Unsupported shader kind '{:?}' found", kind),
             ShaderError::CompileError(error) => format!("Failed to compile shader: {}", error),
+            ShaderError::CompilationFailed(diagnostics) => format!("Failed to compile shader:\n{}", diagnostics),
             ShaderError::OpenFileFailed(error) => format!("Failed to open file: {}", error),
             ShaderError::ReadFromFileFailed(error) => format!("Failed to read from file: {}", error),
+            ShaderError::InvalidSpirV(error) => format!("Invalid SPIR-V binary: {}", error),
+            ShaderError::WatchFailed(error) => format!("Failed to watch shader files: {}", error),
+            ShaderError::InterfaceMismatch(mismatches) => format!(
+                "Shader stages do not match:\n{}",
+                mismatches.iter().map(|m| format!("  {}", m)).collect::<Vec<String>>().join("\n"),
+            ),
+            ShaderError::ConflictingDefines(name) => format!("Shader stages use different values for define '{}'", name),
+            ShaderError::WrongStage(expected, found) => format!("Expected a {} shader, found a {} shader", expected, found),
+            ShaderError::InconsistentBinding(set, binding) => format!("Binding {} in bind group {} is not consistent across shader stages", binding, set),
+            ShaderError::MissingVertexAttributes(inputs) => format!(
+                "Vertex buffers do not provide the vertex shader inputs: {}",
+                inputs.iter().map(|input| format!("{} (location {})", input.name, input.location)).collect::<Vec<String>>().join(", "),
+            ),
+            ShaderError::GlslUnsupported(file) => format!("'{}' is not a SPIR-V binary, GLSL sources require the `glsl` feature", file),
         };
         write!(f, "{}", s)
     }
 }
 
+impl From<CompilerError> for ShaderError {
+    fn from(error: CompilerError) -> Self {
+        match error {
+            CompilerError::CompilationFailed(diagnostics) => ShaderError::CompilationFailed(diagnostics),
+            error => ShaderError::CompileError(error.to_string()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ShaderStage {
     /// Type for vertex shader stage
@@ -62,6 +113,32 @@ pub enum ShaderStage {
     Compute,
 }
 
+bitflags::bitflags! {
+    /// The set of shader stages a binding is visible in
+    #[repr(transparent)]
+    pub struct ShaderStages: u32 {
+        const VERTEX = 1;
+        const FRAGMENT = 2;
+        const COMPUTE = 4;
+    }
+}
+
+impl From<ShaderStage> for ShaderStages {
+    fn from(stage: ShaderStage) -> Self {
+        match stage {
+            ShaderStage::Vertex => ShaderStages::VERTEX,
+            ShaderStage::Fragment => ShaderStages::FRAGMENT,
+            ShaderStage::Compute => ShaderStages::COMPUTE,
+        }
+    }
+}
+
+impl From<ShaderStages> for wgpu::ShaderStage {
+    fn from(stages: ShaderStages) -> Self {
+        Self::from_bits(stages.bits()).unwrap()
+    }
+}
+
 impl From<ReflectShaderStageFlags> for ShaderStage {
     fn from(flags: ReflectShaderStageFlags) -> Self {
         match flags {
@@ -73,6 +150,7 @@ impl From<ReflectShaderStageFlags> for ShaderStage {
     }
 }
 
+#[cfg(feature = "glsl")]
 impl From<ShaderStage> for shaderc::ShaderKind {
     fn from(stage: ShaderStage) -> Self {
         match stage {
@@ -104,83 +182,511 @@ impl fmt::Display for ShaderStage {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ShaderSource {
+    /// GLSL source, compiled into SPIR-V at runtime
+    #[cfg(feature = "glsl")]
     Glsl(String),
+    /// Precompiled SPIR-V binary
+    SpirV(Vec<u32>),
+}
+
+impl ShaderSource {
+    /// Creates a SPIR-V source from the binary, validates word alignment & magic number
+    pub fn spirv_from_bytes(data: &[u8]) -> Result<Self, ShaderError> {
+        Ok(ShaderSource::SpirV(spirv_from_bytes(data)?))
+    }
+
+    /// Loads the source from file, files with `.spv` extension are loaded as SPIR-V binary.
+    /// All other files are GLSL sources, they require the `glsl` feature.
+    pub fn from_file(file_path: &path::Path) -> Result<Self, ShaderError> {
+        match file_path.extension().and_then(|extension| extension.to_str()) {
+            Some("spv") => {
+                let display = file_path.display();
+                let mut data = Vec::new();
+                let mut file = File::open(file_path).map_err(|_| ShaderError::OpenFileFailed(format!("{}", display)))?;
+                file.read_to_end(&mut data).map_err(|_| ShaderError::ReadFromFileFailed(format!("{}", display)))?;
+                ShaderSource::spirv_from_bytes(&data)
+            }
+            #[cfg(feature = "glsl")]
+            _ => Ok(ShaderSource::Glsl(read_source(file_path)?)),
+            #[cfg(not(feature = "glsl"))]
+            _ => Err(ShaderError::GlslUnsupported(format!("{}", file_path.display()))),
+        }
+    }
 }
 
+#[derive(Debug, Clone)]
 pub struct Shader {
     /// Shader stage
     pub stage: ShaderStage,
+    /// The defines the shader variant was compiled with
+    pub defs: ShaderDefs,
+    /// The compiler warnings, only collected when enabled in the compile options
+    pub warnings: ShaderDiagnostics,
+    /// All files & snippets included by the shader source
+    pub dependencies: Vec<IncludeDependency>,
     /// The compiled shader source in binary format
-    source: shaderc::CompilationArtifact,
+    spirv: Arc<Vec<u32>>,
+    /// Hash of stage & binary, identifies shaders by content
+    content_hash: u64,
+    /// The layout, reflected once when the shader is created
+    layout: Arc<ShaderLayout>,
     /// The shader module associated with the shader
-    pub module: wgpu::ShaderModule,
+    pub module: Arc<wgpu::ShaderModule>,
+}
+
+/// Identifies a shader by content, cheap to clone, hash & compare
+#[derive(Debug, Clone)]
+pub struct ShaderKey {
+    stage: ShaderStage,
+    spirv: Arc<Vec<u32>>,
+    content_hash: u64,
+}
+
+impl Hash for ShaderKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.content_hash.hash(state);
+    }
+}
+
+impl PartialEq for ShaderKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.content_hash == other.content_hash
+            && self.stage == other.stage
+            && (Arc::ptr_eq(&self.spirv, &other.spirv) || self.spirv == other.spirv)
+    }
 }
 
-fn compile_shader(source_text: &str, stage: ShaderStage) -> Result<shaderc::CompilationArtifact, CompilerError> {
+impl Eq for ShaderKey {}
+
+/// Describes the compiler options, part of the shader cache key, update when options change
+#[cfg(feature = "glsl")]
+pub(crate) const COMPILER_OPTIONS: &str = "shaderc-0.7;vulkan1.0;spirv1.0;performance;debug-info";
+
+/// Everything besides the source a GLSL shader is compiled with
+#[cfg(feature = "glsl")]
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderCompileOptions<'a> {
+    /// Resolves `#include` directives
+    pub includes: &'a ShaderIncludes,
+    /// The preprocessor defines of the shader variant
+    pub defs: &'a ShaderDefs,
+    /// Optional on-disk cache to load the compiled binary from & store it in
+    pub cache: Option<&'a ShaderCache>,
+    /// Collects compiler warnings instead of suppressing them, cached binaries are not used then
+    pub warnings: bool,
+}
+
+#[cfg(feature = "glsl")]
+impl<'a> ShaderCompileOptions<'a> {
+    pub fn new(includes: &'a ShaderIncludes, defs: &'a ShaderDefs) -> Self {
+        Self {
+            includes,
+            defs,
+            cache: None,
+            warnings: false,
+        }
+    }
+
+    /// Sets the cache to load the compiled binary from & store it in
+    pub fn with_cache(mut self, cache: Option<&'a ShaderCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Enables or disables collecting compiler warnings
+    pub fn with_warnings(mut self, warnings: bool) -> Self {
+        self.warnings = warnings;
+        self
+    }
+}
+
+/// The result of compiling a GLSL shader, or of loading a SPIR-V binary
+#[derive(Debug)]
+pub(crate) struct CompiledShader {
+    /// The compiled shader in binary format
+    pub spirv: Vec<u32>,
+    /// All files & snippets included by the source
+    pub dependencies: Vec<IncludeDependency>,
+    /// The compiler warnings, if enabled
+    pub warnings: ShaderDiagnostics,
+}
+
+/// Parses the compiler output, attaches the sources of the shader & all included files
+#[cfg(feature = "glsl")]
+fn parse_diagnostics(output: &str, source_text: &str, file_name: &str, included: &[(String, String)]) -> ShaderDiagnostics {
+    included.iter().fold(
+        ShaderDiagnostics::parse(output).with_source(file_name, source_text),
+        |diagnostics, (name, content)| diagnostics.with_source(name, content),
+    )
+}
+
+/// Compiles the GLSL source into SPIR-V, the file name is used to resolve relative includes
+/// and in error messages.
+#[cfg(feature = "glsl")]
+pub(crate) fn compile_shader(
+    source_text: &str,
+    file_name: &str,
+    stage: ShaderStage,
+    compile_options: &ShaderCompileOptions,
+) -> Result<CompiledShader, CompilerError> {
+    let included = RefCell::new(Vec::new());
+
     let mut compiler = shaderc::Compiler::new().ok_or(CompilerError::CompilerNotLoaded)?;
     let mut options = shaderc::CompileOptions::new().unwrap();
+    options.set_include_callback(|requested, include_type, requesting, depth| {
+        let include = compile_options.includes.resolve(requested, include_type, requesting, depth)?;
+        included.borrow_mut().push((include.resolved_name.clone(), include.content.clone()));
+        Ok(include)
+    });
     options.add_macro_definition("main", Some("main"));
+    for (name, value) in compile_options.defs.iter() {
+        options.add_macro_definition(name, value);
+    }
     options.set_target_env(shaderc::TargetEnv::Vulkan, shaderc::EnvVersion::Vulkan1_0 as u32);
     options.set_auto_bind_uniforms(false);
     options.set_optimization_level(shaderc::OptimizationLevel::Performance);
     options.set_source_language(shaderc::SourceLanguage::GLSL);
     options.set_target_spirv(shaderc::SpirvVersion::V1_0);
-    options.set_suppress_warnings();
+    if !compile_options.warnings {
+        options.set_suppress_warnings();
+    }
     // options.set_warnings_as_errors();
     options.set_generate_debug_info();
 
-    let artifact = compiler.compile_into_spirv(
+    let result = compiler.compile_into_spirv(
         source_text,
         stage.into(),
-        &format!("{}_shader.glsl", stage.to_string()),
+        file_name,
         "main",
         Some(&options)
-    ).map_err(|e| CompilerError::CompilationFailed(e.to_string()))?;
-    Ok(artifact)
+    );
+    drop(options);
+    let included = included.into_inner();
+
+    let artifact = match result {
+        Ok(artifact) => artifact,
+        Err(shaderc::Error::CompilationError(_, output)) => {
+            return Err(CompilerError::CompilationFailed(parse_diagnostics(&output, source_text, file_name, &included)));
+        }
+        Err(error) => return Err(CompilerError::InternalError(error.to_string())),
+    };
+
+    let warnings = if artifact.get_num_warnings() > 0 {
+        parse_diagnostics(&artifact.get_warning_messages(), source_text, file_name, &included)
+    } else {
+        ShaderDiagnostics::default()
+    };
+
+    Ok(CompiledShader {
+        spirv: artifact.as_binary().to_vec(),
+        dependencies: included.iter().map(|(name, content)| IncludeDependency::new(name, content)).collect(),
+        warnings,
+    })
+}
+
+/// Compiles the GLSL source into SPIR-V, the binary is loaded from & stored in the cache if given
+#[cfg(feature = "glsl")]
+pub(crate) fn compile_spirv(
+    source_text: &str,
+    file_name: &str,
+    stage: ShaderStage,
+    options: &ShaderCompileOptions,
+) -> Result<CompiledShader, ShaderError> {
+    let key = ShaderCache::key(source_text, file_name, stage, options.defs, options.includes, COMPILER_OPTIONS);
+    if !options.warnings {
+        if let Some((spirv, dependencies)) = options.cache.and_then(|cache| cache.load(&key, options.includes)) {
+            return Ok(CompiledShader {
+                spirv,
+                dependencies,
+                warnings: ShaderDiagnostics::default(),
+            });
+        }
+    }
+
+    let compiled = compile_shader(source_text, file_name, stage, options)?;
+
+    if let Some(cache) = options.cache {
+        // the cache is only an optimization, failing to write it does not fail the compilation
+        let _ = cache.store(&key, &compiled.spirv, &compiled.dependencies);
+    }
+    Ok(compiled)
+}
+
+/// Reads the shader source from file
+#[cfg(feature = "glsl")]
+fn read_source(file_path: &path::Path) -> Result<String, ShaderError> {
+    let display = file_path.display();
+
+    let mut source = String::new();
+    let mut file = File::open(file_path).map_err(|_| ShaderError::OpenFileFailed(format!("{}", display)))?;
+    file.read_to_string(&mut source).map_err(|_| ShaderError::ReadFromFileFailed(format!("{}", display)))?;
+    Ok(source)
 }
 
 impl Shader {
     /// Initializes a new shader
+    #[cfg(feature = "glsl")]
     pub fn compile(source: &str, stage: ShaderStage, device: &wgpu::Device) -> ShaderResult {
-        let source = compile_shader(source, stage)
-            .map_err(|e| ShaderError::CompileError(e.to_string()))?;
+        let file_name = format!("{}_shader.glsl", stage.to_string());
+        Shader::compile_with_includes(source, &file_name, stage, &ShaderIncludes::default(), device)
+    }
+
+    /// Initializes a new shader, `#include` directives are resolved by the given includes
+    #[cfg(feature = "glsl")]
+    pub fn compile_with_includes(
+        source: &str,
+        file_name: &str,
+        stage: ShaderStage,
+        includes: &ShaderIncludes,
+        device: &wgpu::Device,
+    ) -> ShaderResult {
+        Shader::compile_variant(source, file_name, stage, includes, &ShaderDefs::default(), device)
+    }
+
+    /// Initializes a new shader variant, compiled with the given preprocessor defines
+    #[cfg(feature = "glsl")]
+    pub fn compile_variant(
+        source: &str,
+        file_name: &str,
+        stage: ShaderStage,
+        includes: &ShaderIncludes,
+        defs: &ShaderDefs,
+        device: &wgpu::Device,
+    ) -> ShaderResult {
+        Shader::compile_with_options(source, file_name, stage, &ShaderCompileOptions::new(includes, defs), device)
+    }
+
+    /// Initializes a new shader with the given compile options, e.g. to use a cache or collect warnings
+    #[cfg(feature = "glsl")]
+    pub fn compile_with_options(
+        source: &str,
+        file_name: &str,
+        stage: ShaderStage,
+        options: &ShaderCompileOptions,
+        device: &wgpu::Device,
+    ) -> ShaderResult {
+        let compiled = compile_spirv(source, file_name, stage, options)?;
+        Ok(Shader::create(compiled, stage, options.defs.clone(), device))
+    }
+
+    /// Initializes a new shader from a precompiled SPIR-V binary
+    pub fn from_spirv(spirv: Vec<u32>, stage: ShaderStage, device: &wgpu::Device) -> ShaderResult {
+        validate_spirv(&spirv)?;
+        let compiled = CompiledShader {
+            spirv,
+            dependencies: Vec::new(),
+            warnings: ShaderDiagnostics::default(),
+        };
+        Ok(Shader::create(compiled, stage, ShaderDefs::default(), device))
+    }
+
+    /// Initializes a new shader from either GLSL source or SPIR-V binary
+    pub fn from_source(source: &ShaderSource, stage: ShaderStage, device: &wgpu::Device) -> ShaderResult {
+        match source {
+            #[cfg(feature = "glsl")]
+            ShaderSource::Glsl(source) => Shader::compile(source, stage, device),
+            ShaderSource::SpirV(spirv) => Shader::from_spirv(spirv.clone(), stage, device),
+        }
+    }
 
-        let module_source = ShaderModuleSource::SpirV(Cow::from(source.as_binary()));
+    /// Creates the shader module from the SPIR-V binary
+    fn create(compiled: CompiledShader, stage: ShaderStage, defs: ShaderDefs, device: &wgpu::Device) -> Self {
+        let module_source = ShaderModuleSource::SpirV(Cow::from(compiled.spirv.as_slice()));
         let module = device.create_shader_module(module_source);
 
-        Ok(Self {
+        let mut hasher = DefaultHasher::new();
+        stage.hash(&mut hasher);
+        compiled.spirv.hash(&mut hasher);
+        let layout = reflect(bytemuck::cast_slice(compiled.spirv.as_slice()));
+
+        Self {
             stage,
-            source,
-            module,
-        })
+            defs,
+            warnings: compiled.warnings,
+            dependencies: compiled.dependencies,
+            spirv: Arc::new(compiled.spirv),
+            content_hash: hasher.finish(),
+            layout: Arc::new(layout),
+            module: Arc::new(module),
+        }
     }
 
-    /// Loads and compiles the shader from file
+    /// Loads and compiles the shader from file, `.spv` files are loaded as SPIR-V binary
     pub fn from_file(file_path: &path::PathBuf, stage: ShaderStage, device: &wgpu::Device) -> ShaderResult {
-        let path = std::path::Path::new(file_path);
-        let display = path.display();
-
-        let mut source = String::new();
-        let mut file = File::open(&path).map_err(|_| ShaderError::OpenFileFailed(format!("{}", display)))?;
-        file.read_to_string(&mut source).map_err(|_| ShaderError::ReadFromFileFailed(format!("{}", display)))?;
+        match ShaderSource::from_file(file_path)? {
+            #[cfg(feature = "glsl")]
+            ShaderSource::Glsl(source) => {
+                let file_name = format!("{}", file_path.display());
+                Shader::compile_with_includes(&source, &file_name, stage, &ShaderIncludes::default(), device)
+            }
+            ShaderSource::SpirV(spirv) => Shader::from_spirv(spirv, stage, device),
+        }
+    }
 
-        Shader::compile(&source, stage, device)
+    /// Loads and compiles the shader from file, includes are resolved relative to the file first.
+    /// Files with `.spv` extension are loaded as SPIR-V binary.
+    #[cfg(feature = "glsl")]
+    pub fn from_file_with_includes(
+        file_path: &path::PathBuf,
+        stage: ShaderStage,
+        includes: &ShaderIncludes,
+        device: &wgpu::Device,
+    ) -> ShaderResult {
+        match ShaderSource::from_file(file_path)? {
+            ShaderSource::Glsl(source) => {
+                let file_name = format!("{}", file_path.display());
+                Shader::compile_with_includes(&source, &file_name, stage, includes, device)
+            }
+            ShaderSource::SpirV(spirv) => Shader::from_spirv(spirv, stage, device),
+        }
     }
 
     /// Returns the shader as vec of u8.
     pub fn as_bytes(&self) -> &[u8] {
-        self.source.as_binary_u8()
+        bytemuck::cast_slice(self.spirv.as_slice())
     }
 
     /// Returns the shader as vec of u32.
     pub fn as_binary(&self) -> &[u32] {
-        self.source.as_binary()
+        &self.spirv
+    }
+
+    /// Returns the Shader Layout, reflected when the shader was created
+    pub fn layout(&self) -> &ShaderLayout {
+        &self.layout
     }
 
-    /// Returns the Shader Layout
-    pub fn layout(&self) -> ShaderLayout {
-        ShaderLayout::from_shader(self)
+    /// Returns the key identifying the shader by content
+    pub fn key(&self) -> ShaderKey {
+        ShaderKey {
+            stage: self.stage,
+            spirv: Arc::clone(&self.spirv),
+            content_hash: self.content_hash,
+        }
+    }
+}
+
+impl Hash for Shader {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.content_hash.hash(state);
+    }
+}
+
+/// Shaders are equal by content, shaders compiled from the same source are interchangeable
+impl PartialEq for Shader {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Shader {}
+
+#[cfg(all(test, feature = "glsl"))]
+mod tests {
+    use crate::{Severity, ShaderCompileOptions, ShaderDefs, ShaderIncludes, ShaderStage};
+
+    use super::{CompilerError, compile_shader};
+
+    const FRAGMENT_SHADER: &str = r#"
+    #version 450
+    #include "noise/simplex.glsl"
+
+    layout(location = 0) out vec4 outColor;
+
+    void main() {
+        outColor = vec4(vec3(snoise(gl_FragCoord.xy)), 1.0);
+    }
+    "#;
+
+    #[test]
+    fn it_compiles_shaders_with_includes() {
+        let includes = ShaderIncludes::new()
+            .with_source("noise/simplex.glsl", "#include \"common.glsl\"\nfloat snoise(vec2 v) { return hash(v); }\n")
+            .with_source("noise/common.glsl", "float hash(vec2 v) { return fract(sin(dot(v, vec2(12.9898, 78.233))) * 43758.5453); }\n");
+
+        let compiled = compile_shader(FRAGMENT_SHADER, "fragment.glsl", ShaderStage::Fragment, &ShaderCompileOptions::new(&includes, &ShaderDefs::new())).unwrap();
+        assert_eq!(
+            vec!["noise/simplex.glsl", "noise/common.glsl"],
+            compiled.dependencies.iter().map(|d| d.name.as_str()).collect::<Vec<&str>>(),
+        );
+    }
+
+    #[test]
+    fn it_reports_errors_in_included_files() {
+        let includes = ShaderIncludes::new()
+            .with_source("noise/simplex.glsl", "float snoise(vec2 v) {\n    return undefined_hash(v);\n}\n");
+
+        let error = compile_shader(FRAGMENT_SHADER, "fragment.glsl", ShaderStage::Fragment, &ShaderCompileOptions::new(&includes, &ShaderDefs::new()))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("noise/simplex.glsl:2"), "{}", error);
+        assert!(error.contains("2 |     return undefined_hash(v);"), "{}", error);
+
+        let error = compile_shader(FRAGMENT_SHADER, "fragment.glsl", ShaderStage::Fragment, &ShaderCompileOptions::new(&ShaderIncludes::new(), &ShaderDefs::new()))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("noise/simplex.glsl"), "{}", error);
+    }
+
+    #[test]
+    fn it_compiles_shader_variants_with_defines() {
+        const SHADER: &str = r#"
+        #version 450
+
+        layout(location = 0) out vec4 outColor;
+
+        void main() {
+        #ifdef USE_SHADOWS
+            outColor = vec4(float(MAX_LIGHTS));
+        #else
+            outColor = vec4(1.0);
+        #endif
+        }
+        "#;
+
+        let includes = ShaderIncludes::new();
+        let shadows = ShaderDefs::new().with_def("USE_SHADOWS").with_value("MAX_LIGHTS", 8);
+
+        assert!(compile_shader(SHADER, "fragment.glsl", ShaderStage::Fragment, &ShaderCompileOptions::new(&includes, &ShaderDefs::new())).is_ok());
+        assert!(compile_shader(SHADER, "fragment.glsl", ShaderStage::Fragment, &ShaderCompileOptions::new(&includes, &shadows)).is_ok());
+
+        let missing_value = ShaderDefs::new().with_def("USE_SHADOWS");
+        assert!(compile_shader(SHADER, "fragment.glsl", ShaderStage::Fragment, &ShaderCompileOptions::new(&includes, &missing_value)).is_err());
+    }
+
+    #[test]
+    fn it_parses_compiler_errors_into_diagnostics() {
+        const SHADER: &str = "#version 450\n\nlayout(location = 0) out vec4 outColor;\n\nvoid main() {\n    outColor = undefined_color;\n}\n";
+
+        let includes = ShaderIncludes::new();
+        let defs = ShaderDefs::new();
+        match compile_shader(SHADER, "fragment.glsl", ShaderStage::Fragment, &ShaderCompileOptions::new(&includes, &defs)) {
+            Err(CompilerError::CompilationFailed(diagnostics)) => {
+                let error = diagnostics.errors().next().unwrap();
+                assert_eq!("fragment.glsl", error.file);
+                assert_eq!(Some(6), error.line);
+                assert!(error.message.contains("undefined_color"), "{}", error.message);
+            }
+            _ => panic!("Expected compilation to fail"),
+        }
+    }
+
+    #[test]
+    fn it_collects_warnings_when_enabled() {
+        const SHADER: &str = "#version 450\n#extension GL_UNKNOWN_extension : enable\n\nlayout(location = 0) out vec4 outColor;\n\nvoid main() {\n    outColor = vec4(1.0);\n}\n";
+
+        let includes = ShaderIncludes::new();
+        let defs = ShaderDefs::new();
+        let options = ShaderCompileOptions::new(&includes, &defs);
+
+        let compiled = compile_shader(SHADER, "fragment.glsl", ShaderStage::Fragment, &options).unwrap();
+        assert!(compiled.warnings.is_empty());
+
+        let compiled = compile_shader(SHADER, "fragment.glsl", ShaderStage::Fragment, &options.with_warnings(true)).unwrap();
+        let warning = compiled.warnings.warnings().next().unwrap();
+        assert_eq!(Severity::Warning, warning.severity);
+        assert_eq!(Some(2), warning.line);
     }
 }
@@ -0,0 +1,364 @@
+use std::{fs, io, path::{Path, PathBuf}};
+
+use crate::{spirv::spirv_from_bytes, ShaderDefs, ShaderIncludes, ShaderStage};
+
+/// Offset basis of the 64 bit FNV-1a hash
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+/// Prime of the 64 bit FNV-1a hash
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Returns the 64 bit FNV-1a hash of the data, unlike `DefaultHasher` it is stable across builds
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ *byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// Appends a named, length prefixed field to the key inputs
+fn push_field(inputs: &mut String, name: &str, value: &str) {
+    inputs.push_str(&format!("{} {}\n{}\n", name, value.len(), value));
+}
+
+/// Appends the length prefixed section to the cache entry
+fn push_section(entry: &mut Vec<u8>, data: &[u8]) {
+    entry.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    entry.extend_from_slice(data);
+}
+
+/// Splits the length prefixed section off the start of the cache entry
+fn split_section(entry: &[u8]) -> Option<(&[u8], &[u8])> {
+    if entry.len() < 8 {
+        return None;
+    }
+    let (length, rest) = entry.split_at(8);
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(length);
+    let length = u64::from_le_bytes(bytes) as usize;
+    if rest.len() < length {
+        return None;
+    }
+    Some(rest.split_at(length))
+}
+
+/// A file or in-memory snippet that was included while compiling a shader
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeDependency {
+    /// The resolved name of the include, e.g. the path of the file
+    pub name: String,
+    /// FNV-1a hash of the included content
+    pub hash: u64,
+}
+
+impl IncludeDependency {
+    pub fn new(name: &str, content: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            hash: fnv1a(content.as_bytes()),
+        }
+    }
+
+    /// Returns true if the include still resolves to the same content
+    pub fn is_current(&self, includes: &ShaderIncludes) -> bool {
+        includes
+            .load(&self.name)
+            .map_or(false, |content| self.hash == fnv1a(content.as_bytes()))
+    }
+}
+
+/// Identifies an entry of the shader cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderCacheKey {
+    /// All inputs the shader is compiled from, stored with the entry & compared when it is loaded
+    inputs: String,
+    /// FNV-1a hash of the inputs, names the files of the entry
+    digest: u64,
+}
+
+impl ShaderCacheKey {
+    /// Returns the hash of all inputs
+    pub fn digest(&self) -> u64 {
+        self.digest
+    }
+}
+
+/// A persistent on-disk cache of compiled SPIR-V binaries.
+///
+/// Each entry is keyed by source, file name, stage, defines, include configuration & compiler options.
+/// The key is stored with the binary and compared on load, so colliding hashes never return
+/// the wrong binary. The files & snippets included by a shader are stored as well, an entry is
+/// only used when none of them changed. Key, includes & binary are written as a single file,
+/// an interrupted write never leaves an entry that mixes them up.
+///
+/// Example:
+/// let cache = ShaderCache::in_app_dir()?;
+/// let options = RendererOptions::default().with_shader_cache(cache);
+///
+#[derive(Debug, Clone)]
+pub struct ShaderCache {
+    /// The directory all cached binaries are stored in
+    directory: PathBuf,
+    /// When disabled, no binaries are loaded or stored
+    enabled: bool,
+}
+
+impl ShaderCache {
+    /// Creates a new cache that stores binaries in the given directory
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        Self {
+            directory: directory.into(),
+            enabled: true,
+        }
+    }
+
+    /// Creates a new cache in the `shader_cache` folder of the application directory
+    pub fn in_app_dir() -> Result<Self, io::Error> {
+        Ok(Self::new(utils::app_dir()?.join("shader_cache")))
+    }
+
+    /// Returns the directory of the cache
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns true if the cache is used
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables the cache, the stored binaries are kept
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns the key of the shader compiled with the given includes & compiler options.
+    ///
+    /// Search paths & names of registered snippets are part of the key, both change how includes resolve.
+    pub fn key(
+        source: &str,
+        file_name: &str,
+        stage: ShaderStage,
+        defs: &ShaderDefs,
+        includes: &ShaderIncludes,
+        compiler_options: &str,
+    ) -> ShaderCacheKey {
+        let mut inputs = String::new();
+        push_field(&mut inputs, "options", compiler_options);
+        push_field(&mut inputs, "stage", &stage.to_string());
+        push_field(&mut inputs, "file", file_name);
+        for (name, value) in defs.iter() {
+            push_field(&mut inputs, "define", name);
+            if let Some(value) = value {
+                push_field(&mut inputs, "value", value);
+            }
+        }
+        for search_path in includes.search_paths.iter() {
+            push_field(&mut inputs, "search_path", &search_path.to_string_lossy());
+        }
+        let mut snippets = includes.sources.keys().collect::<Vec<&String>>();
+        snippets.sort();
+        for name in snippets {
+            push_field(&mut inputs, "snippet", name);
+        }
+        push_field(&mut inputs, "source", source);
+
+        ShaderCacheKey {
+            digest: fnv1a(inputs.as_bytes()),
+            inputs,
+        }
+    }
+
+    /// Loads the cached binary & its includes, returns None if there is no entry for the key
+    /// or any include changed
+    pub fn load(&self, key: &ShaderCacheKey, includes: &ShaderIncludes) -> Option<(Vec<u32>, Vec<IncludeDependency>)> {
+        if !self.enabled {
+            return None;
+        }
+
+        let entry = fs::read(self.path(key, "entry")).ok()?;
+        let (inputs, entry) = split_section(&entry)?;
+        let (dependencies_text, entry) = split_section(entry)?;
+        let (data, rest) = split_section(entry)?;
+        if !rest.is_empty() {
+            return None;
+        }
+
+        // a different key with the same hash
+        if inputs != key.inputs.as_bytes() {
+            return None;
+        }
+
+        let mut dependencies = Vec::new();
+        for line in std::str::from_utf8(dependencies_text).ok()?.lines() {
+            let mut parts = line.splitn(2, ' ');
+            let hash = u64::from_str_radix(parts.next()?, 16).ok()?;
+            let dependency = IncludeDependency { name: parts.next()?.to_string(), hash };
+            if !dependency.is_current(includes) {
+                return None;
+            }
+            dependencies.push(dependency);
+        }
+
+        spirv_from_bytes(data).ok().map(|spirv| (spirv, dependencies))
+    }
+
+    /// Stores the compiled binary together with the list of its includes
+    pub fn store(&self, key: &ShaderCacheKey, spirv: &[u32], dependencies: &[IncludeDependency]) -> Result<(), io::Error> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let dependencies = dependencies
+            .iter()
+            .map(|dependency| format!("{:016x} {}\n", dependency.hash, dependency.name))
+            .collect::<String>();
+
+        let mut entry = Vec::new();
+        push_section(&mut entry, key.inputs.as_bytes());
+        push_section(&mut entry, dependencies.as_bytes());
+        push_section(&mut entry, bytemuck::cast_slice(spirv));
+
+        // the entry is renamed once it is complete, loads never see a partially written entry
+        fs::create_dir_all(&self.directory)?;
+        let temp_path = self.path(key, "tmp");
+        fs::write(&temp_path, entry)?;
+        fs::rename(temp_path, self.path(key, "entry"))
+    }
+
+    /// Removes all cached binaries
+    pub fn clear(&self) -> Result<(), io::Error> {
+        if !self.directory.is_dir() {
+            return Ok(());
+        }
+
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            let extension = path.extension().and_then(|e| e.to_str());
+            if let Some("entry") | Some("tmp") = extension {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the path of the cache file with the given extension
+    fn path(&self, key: &ShaderCacheKey, extension: &str) -> PathBuf {
+        self.directory.join(format!("{:016x}.{}", key.digest, extension))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{IncludeDependency, ShaderCache, ShaderCacheKey, ShaderDefs, ShaderIncludes, ShaderStage};
+
+    use super::fnv1a;
+
+    const SPIRV: [u32; 6] = [0x0723_0203, 0x0001_0000, 0, 8, 0, 0x0002_0011];
+
+    fn cache(name: &str) -> ShaderCache {
+        let cache = ShaderCache::new(std::env::temp_dir().join(name));
+        cache.clear().unwrap();
+        cache
+    }
+
+    fn key(source: &str) -> ShaderCacheKey {
+        ShaderCache::key(source, "main.glsl", ShaderStage::Vertex, &ShaderDefs::new(), &ShaderIncludes::new(), "O1")
+    }
+
+    #[test]
+    fn it_hashes_with_fnv1a() {
+        assert_eq!(0xcbf2_9ce4_8422_2325, fnv1a(b""));
+        assert_eq!(0xaf63_dc4c_8601_ec8c, fnv1a(b"a"));
+        assert_eq!(0x8594_4171_f739_67e8, fnv1a(b"foobar"));
+    }
+
+    #[test]
+    fn it_keys_entries_by_source_stage_defines_includes_and_options() {
+        let defs = ShaderDefs::new().with_def("USE_SHADOWS");
+        let includes = ShaderIncludes::new().with_search_path("shaders");
+        let key = ShaderCache::key("void main() {}", "main.glsl", ShaderStage::Vertex, &defs, &includes, "O1");
+
+        assert_eq!(key, ShaderCache::key("void main() {}", "main.glsl", ShaderStage::Vertex, &defs, &includes, "O1"));
+        assert_ne!(key, ShaderCache::key("void main() { }", "main.glsl", ShaderStage::Vertex, &defs, &includes, "O1"));
+        assert_ne!(key, ShaderCache::key("void main() {}", "main.glsl", ShaderStage::Fragment, &defs, &includes, "O1"));
+        assert_ne!(key, ShaderCache::key("void main() {}", "main.glsl", ShaderStage::Vertex, &ShaderDefs::new(), &includes, "O1"));
+        assert_ne!(key, ShaderCache::key("void main() {}", "main.glsl", ShaderStage::Vertex, &defs, &includes, "O2"));
+
+        let other_path = ShaderIncludes::new().with_search_path("other_shaders");
+        assert_ne!(key, ShaderCache::key("void main() {}", "main.glsl", ShaderStage::Vertex, &defs, &other_path, "O1"));
+        let snippet = includes.clone().with_source("common.glsl", "");
+        assert_ne!(key, ShaderCache::key("void main() {}", "main.glsl", ShaderStage::Vertex, &defs, &snippet, "O1"));
+    }
+
+    #[test]
+    fn it_stores_and_loads_binaries() {
+        let mut cache = cache("renderer_shader_cache_store");
+        let includes = ShaderIncludes::new();
+        let key = key("void main() {}");
+
+        assert_eq!(None, cache.load(&key, &includes));
+        cache.store(&key, &SPIRV, &[]).unwrap();
+        assert_eq!(Some((SPIRV.to_vec(), Vec::new())), cache.load(&key, &includes));
+
+        cache.set_enabled(false);
+        assert_eq!(None, cache.load(&key, &includes));
+
+        cache.set_enabled(true);
+        cache.clear().unwrap();
+        assert_eq!(None, cache.load(&key, &includes));
+    }
+
+    #[test]
+    fn it_ignores_entries_of_colliding_keys() {
+        let cache = cache("renderer_shader_cache_collision");
+        let includes = ShaderIncludes::new();
+        let key = key("void main() {}");
+        cache.store(&key, &SPIRV, &[]).unwrap();
+
+        let colliding = ShaderCacheKey {
+            inputs: String::from("void main() { discard; }"),
+            digest: key.digest(),
+        };
+        assert_eq!(None, cache.load(&colliding, &includes));
+        assert!(cache.load(&key, &includes).is_some());
+    }
+
+    #[test]
+    fn it_invalidates_entries_when_includes_change() {
+        let cache = cache("renderer_shader_cache_includes");
+        let include_path = cache.directory().join("lighting.glsl");
+        fs::create_dir_all(cache.directory()).unwrap();
+        fs::write(&include_path, "vec3 phong();").unwrap();
+
+        let includes = ShaderIncludes::new().with_source("noise.glsl", "float snoise(vec2 v);");
+        let key = key("#include \"noise.glsl\"");
+        let dependencies = vec![
+            IncludeDependency::new("noise.glsl", "float snoise(vec2 v);"),
+            IncludeDependency::new(&include_path.to_string_lossy(), "vec3 phong();"),
+        ];
+        cache.store(&key, &SPIRV, &dependencies).unwrap();
+        assert_eq!(Some((SPIRV.to_vec(), dependencies)), cache.load(&key, &includes));
+
+        fs::write(&include_path, "vec3 blinn_phong();").unwrap();
+        assert_eq!(None, cache.load(&key, &includes));
+
+        fs::write(&include_path, "vec3 phong();").unwrap();
+        let changed = ShaderIncludes::new().with_source("noise.glsl", "float snoise(vec3 v);");
+        assert_eq!(None, cache.load(&key, &changed));
+    }
+
+    #[test]
+    fn it_ignores_truncated_entries() {
+        let cache = cache("renderer_shader_cache_truncated");
+        let includes = ShaderIncludes::new();
+        let key = key("void main() {}");
+        cache.store(&key, &SPIRV, &[]).unwrap();
+
+        let path = cache.directory().join(format!("{:016x}.entry", key.digest()));
+        let entry = fs::read(&path).unwrap();
+        fs::write(&path, &entry[..entry.len() - 4]).unwrap();
+        assert_eq!(None, cache.load(&key, &includes));
+
+        fs::write(&path, &entry[..12]).unwrap();
+        assert_eq!(None, cache.load(&key, &includes));
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{Shader, ShaderError, ShaderIncludes, ShaderStage};
+
+/// A set of preprocessor defines a shader is compiled with, e.g. `USE_SHADOWS` or `MAX_LIGHTS=8`.
+///
+/// Each distinct set of defines compiles a separate variant (permutation) of the same source.
+///
+/// Example:
+/// let defs = ShaderDefs::new()
+///     .with_def("USE_SHADOWS")
+///     .with_value("MAX_LIGHTS", "8");
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ShaderDefs {
+    /// The defines sorted by name, with an optional value
+    defs: BTreeMap<String, Option<String>>,
+}
+
+impl ShaderDefs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a define without value, e.g. `#define USE_SHADOWS`
+    pub fn with_def(mut self, name: &str) -> Self {
+        self.defs.insert(name.to_string(), None);
+        self
+    }
+
+    /// Adds a define with value, e.g. `#define MAX_LIGHTS 8`
+    pub fn with_value<V: ToString>(mut self, name: &str, value: V) -> Self {
+        self.defs.insert(name.to_string(), Some(value.to_string()));
+        self
+    }
+
+    /// Returns true if the define is set
+    pub fn contains(&self, name: &str) -> bool {
+        self.defs.contains_key(name)
+    }
+
+    /// Returns true if no defines are set
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
+
+    /// Iterates over all defines in order of their names
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.defs.iter().map(|(name, value)| (name.as_str(), value.as_deref()))
+    }
+
+    /// Returns the defines of both sets, fails if a define is set with different values
+    pub fn merge(&self, other: &ShaderDefs) -> Result<ShaderDefs, ShaderError> {
+        let mut defs = self.defs.clone();
+        for (name, value) in other.defs.iter() {
+            match defs.get(name) {
+                Some(existing) if existing != value => return Err(ShaderError::ConflictingDefines(name.clone())),
+                Some(_) => (),
+                None => {
+                    defs.insert(name.clone(), value.clone());
+                }
+            }
+        }
+        Ok(ShaderDefs { defs })
+    }
+}
+
+/// Identifies a compiled variant of a shader source, holds all inputs the variant is compiled from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShaderVariantKey {
+    /// The shader source
+    pub source: String,
+    /// The shader stage the source is compiled for
+    pub stage: ShaderStage,
+    /// The defines the source is compiled with
+    pub defs: ShaderDefs,
+}
+
+impl ShaderVariantKey {
+    pub fn new(source: &str, stage: ShaderStage, defs: &ShaderDefs) -> Self {
+        Self {
+            source: source.to_string(),
+            stage,
+            defs: defs.clone(),
+        }
+    }
+}
+
+/// Caches all compiled shader variants, so each permutation is only compiled once.
+///
+/// A variant is only returned while all of its included files are unchanged.
+#[derive(Default)]
+pub struct ShaderVariants {
+    variants: HashMap<ShaderVariantKey, Shader>,
+}
+
+impl ShaderVariants {
+    /// Returns the compiled variant, if it exists and none of its includes changed
+    pub fn get(&self, key: &ShaderVariantKey, includes: &ShaderIncludes) -> Option<&Shader> {
+        self.variants
+            .get(key)
+            .filter(|shader| shader.dependencies.iter().all(|dependency| dependency.is_current(includes)))
+    }
+
+    /// Stores the compiled variant, replaces an outdated variant of the same key
+    pub fn insert(&mut self, key: ShaderVariantKey, shader: Shader) {
+        self.variants.insert(key, shader);
+    }
+
+    /// Returns the number of cached variants
+    pub fn len(&self) -> usize {
+        self.variants.len()
+    }
+
+    /// Returns true if no variant is cached
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty()
+    }
+
+    /// Removes all cached variants
+    pub fn clear(&mut self) {
+        self.variants.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ShaderDefs, ShaderError, ShaderStage, ShaderVariantKey};
+
+    #[test]
+    fn it_orders_defines_by_name() {
+        let defs = ShaderDefs::new()
+            .with_value("MAX_LIGHTS", 8)
+            .with_def("USE_SHADOWS")
+            .with_def("ALPHA_TEST");
+
+        assert!(defs.contains("USE_SHADOWS"));
+        assert_eq!(
+            vec![("ALPHA_TEST", None), ("MAX_LIGHTS", Some("8")), ("USE_SHADOWS", None)],
+            defs.iter().collect::<Vec<(&str, Option<&str>)>>(),
+        );
+    }
+
+    #[test]
+    fn it_merges_defines_of_both_stages() {
+        let vertex = ShaderDefs::new().with_def("USE_SKINNING").with_value("MAX_LIGHTS", 8);
+        let fragment = ShaderDefs::new().with_def("USE_SHADOWS").with_value("MAX_LIGHTS", 8);
+
+        let defs = vertex.merge(&fragment).unwrap();
+        assert_eq!(
+            vec![("MAX_LIGHTS", Some("8")), ("USE_SHADOWS", None), ("USE_SKINNING", None)],
+            defs.iter().collect::<Vec<(&str, Option<&str>)>>(),
+        );
+
+        let fragment = ShaderDefs::new().with_value("MAX_LIGHTS", 4);
+        assert!(matches!(vertex.merge(&fragment), Err(ShaderError::ConflictingDefines(name)) if name == "MAX_LIGHTS"));
+    }
+
+    #[test]
+    fn it_keys_variants_by_source_stage_and_defines() {
+        let shadows = ShaderDefs::new().with_def("USE_SHADOWS").with_value("MAX_LIGHTS", 8);
+        let key = ShaderVariantKey::new("void main() {}", ShaderStage::Fragment, &shadows);
+
+        let same_defs = ShaderDefs::new().with_value("MAX_LIGHTS", "8").with_def("USE_SHADOWS");
+        assert_eq!(key, ShaderVariantKey::new("void main() {}", ShaderStage::Fragment, &same_defs));
+
+        assert_ne!(key, ShaderVariantKey::new("void main() {}", ShaderStage::Vertex, &shadows));
+        assert_ne!(key, ShaderVariantKey::new("void main() { }", ShaderStage::Fragment, &shadows));
+        assert_ne!(key, ShaderVariantKey::new("void main() {}", ShaderStage::Fragment, &ShaderDefs::new()));
+    }
+
+    #[cfg(feature = "glsl")]
+    #[test]
+    fn it_recompiles_variants_when_includes_change() {
+        const SHADER: &str = "#version 450\n#include \"color.glsl\"\n\nlayout(location = 0) out vec4 outColor;\n\nvoid main() {\n    outColor = COLOR;\n}\n";
+
+        let mut renderer = futures::executor::block_on(crate::Renderer::new()).unwrap();
+        renderer.shader_includes.add_source("color.glsl", "#define COLOR vec4(1.0)");
+
+        let defs = ShaderDefs::new();
+        let shader = renderer.create_shader_variant(SHADER, ShaderStage::Fragment, &defs).unwrap();
+        assert_eq!(shader, renderer.create_shader_variant(SHADER, ShaderStage::Fragment, &defs).unwrap());
+        assert_eq!(1, renderer.shader_variants.len());
+
+        renderer.shader_includes.add_source("color.glsl", "#define COLOR vec4(0.5)");
+        let changed = renderer.create_shader_variant(SHADER, ShaderStage::Fragment, &defs).unwrap();
+        assert_ne!(shader, changed);
+        assert_eq!(1, renderer.shader_variants.len());
+    }
+}
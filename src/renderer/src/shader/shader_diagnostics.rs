@@ -0,0 +1,285 @@
+use std::{collections::HashMap, fmt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The shader failed to compile
+    Error,
+    /// The shader compiled, but contains suspicious code
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A single message of the shader compiler, e.g. `fragment.glsl:14: error: 'foo' : undeclared identifier`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file or include name the message refers to, empty if unknown
+    pub file: String,
+    /// The line in the file, starting at 1
+    pub line: Option<u32>,
+    /// The column in the line, starting at 1
+    pub column: Option<u32>,
+    /// Severity of the message
+    pub severity: Severity,
+    /// The message without location
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Parses a single line of compiler output, returns None if the line is not a diagnostic
+    pub fn parse(line: &str) -> Option<Self> {
+        let markers = [("error: ", Severity::Error), ("warning: ", Severity::Warning)];
+
+        for (marker, severity) in markers.iter() {
+            // messages without location start with the severity
+            if let Some(message) = line.strip_prefix(marker) {
+                return Some(Self::new("", None, None, *severity, message));
+            }
+
+            if let Some(index) = line.find(&format!(": {}", marker)) {
+                let message = &line[index + marker.len() + 2..];
+                let (file, numbers) = split_location(&line[..index]);
+                return Some(Self::new(file, numbers.first().copied(), numbers.get(1).copied(), *severity, message));
+            }
+        }
+        None
+    }
+
+    pub fn new(file: &str, line: Option<u32>, column: Option<u32>, severity: Severity, message: &str) -> Self {
+        Self {
+            file: file.to_string(),
+            line,
+            column,
+            severity,
+            message: message.trim().to_string(),
+        }
+    }
+
+    /// Returns the location as `file:line:column`, leaving out unknown parts
+    pub fn location(&self) -> String {
+        let mut location = self.file.clone();
+        if let Some(line) = self.line {
+            location += &format!(":{}", line);
+            if let Some(column) = self.column {
+                location += &format!(":{}", column);
+            }
+        }
+        location
+    }
+
+    /// Formats the diagnostic, shows the offending line of the given source with a caret
+    pub fn pretty_print(&self, source: Option<&str>) -> String {
+        let mut output = format!("{}: {}\n", self.severity, self.message);
+        if self.file.is_empty() {
+            return output;
+        }
+
+        let line_number = match self.line {
+            Some(line_number) if line_number > 0 => line_number,
+            _ => {
+                output += &format!("  --> {}\n", self.location());
+                return output;
+            }
+        };
+
+        let gutter = " ".repeat(line_number.to_string().len());
+        output += &format!("{}--> {}\n", gutter, self.location());
+
+        let text = source.and_then(|source| source.lines().nth(line_number as usize - 1));
+        if let Some(text) = text {
+            let (start, end) = self.highlight(text);
+            let padding = display_width(&text[..start]);
+            let length = display_width(&text[start..end]).max(1);
+            output += &format!("{} |\n", gutter);
+            output += &format!("{} | {}\n", line_number, text.replace('\t', &" ".repeat(TAB_WIDTH)));
+            output += &format!("{} | {}{}\n", gutter, " ".repeat(padding), "^".repeat(length));
+        }
+        output
+    }
+
+    /// Returns the byte range of the highlighted part of the line.
+    ///
+    /// Uses the column if known, otherwise the first quoted token of the message found in the line,
+    /// e.g. `'undefined_hash' : no matching overloaded function found`, or the whole line.
+    fn highlight(&self, text: &str) -> (usize, usize) {
+        if let Some(column) = self.column {
+            // the column is a byte offset, it is moved to the start of the character it points into
+            let offset = (column as usize).saturating_sub(1);
+            let (start, character) = text
+                .char_indices()
+                .take_while(|(index, _)| *index <= offset)
+                .last()
+                .unwrap_or((text.len(), ' '));
+            return (start, (start + character.len_utf8()).min(text.len()));
+        }
+
+        let token = self.message.split('\'').nth(1).filter(|token| !token.trim().is_empty());
+        if let Some((start, token)) = token.and_then(|token| text.find(token).map(|start| (start, token))) {
+            return (start, start + token.len());
+        }
+
+        let start = text.len() - text.trim_start().len();
+        (start, text.trim_end().len().max(start))
+    }
+}
+
+/// Number of columns a tab is expanded to in printed source lines
+const TAB_WIDTH: usize = 4;
+
+/// Returns the number of columns the text takes up when printed, tabs are expanded
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c == '\t' { TAB_WIDTH } else { 1 }).sum()
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.file.is_empty() {
+            write!(f, "{}: {}", self.severity, self.message)
+        } else {
+            write!(f, "{}: {}: {}", self.location(), self.severity, self.message)
+        }
+    }
+}
+
+/// Splits the location into file name & trailing line / column numbers, e.g. `C:\shaders\main.glsl:14:5`
+fn split_location(location: &str) -> (&str, Vec<u32>) {
+    let mut file = location;
+    let mut numbers = Vec::new();
+
+    while numbers.len() < 2 {
+        match file.rfind(':') {
+            Some(index) => match file[index + 1..].parse::<u32>() {
+                Ok(number) => {
+                    numbers.insert(0, number);
+                    file = &file[..index];
+                }
+                Err(_) => break,
+            },
+            None => break,
+        }
+    }
+    (file, numbers)
+}
+
+/// All diagnostics of a compiled shader, together with the sources they refer to.
+///
+/// The `Display` output shows each diagnostic with the offending source line.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderDiagnostics {
+    /// The list of parsed diagnostics, in order of the compiler output
+    pub diagnostics: Vec<Diagnostic>,
+    /// Sources of the shader & its includes, keyed by file name
+    sources: HashMap<String, String>,
+}
+
+impl ShaderDiagnostics {
+    /// Parses the compiler output, lines that are not diagnostics are skipped
+    pub fn parse(output: &str) -> Self {
+        Self {
+            diagnostics: output.lines().filter_map(Diagnostic::parse).collect(),
+            sources: HashMap::new(),
+        }
+    }
+
+    /// Adds the source of the given file, used to print the offending lines
+    pub fn with_source(mut self, file_name: &str, source: &str) -> Self {
+        self.sources.insert(file_name.to_string(), source.to_string());
+        self
+    }
+
+    /// Returns all errors
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|d| d.severity == Severity::Error)
+    }
+
+    /// Returns all warnings
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|d| d.severity == Severity::Warning)
+    }
+
+    /// Returns true if there are no diagnostics
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Formats all diagnostics, each with the offending source line
+    pub fn pretty_print(&self) -> String {
+        self.diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.pretty_print(self.sources.get(&diagnostic.file).map(String::as_str)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl fmt::Display for ShaderDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pretty_print())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Diagnostic, Severity, ShaderDiagnostics};
+
+    #[test]
+    fn it_parses_compiler_output() {
+        let output = "fragment.glsl:14: error: 'undefined_hash' : no matching overloaded function found\n\
+                      C:\\shaders\\noise.glsl:3:7: warning: '' : extra tokens\n\
+                      error: Linking fragment stage: Missing entry point\n\
+                      2 errors generated.\n";
+
+        let diagnostics = ShaderDiagnostics::parse(output);
+        assert_eq!(
+            vec![
+                Diagnostic::new("fragment.glsl", Some(14), None, Severity::Error, "'undefined_hash' : no matching overloaded function found"),
+                Diagnostic::new("C:\\shaders\\noise.glsl", Some(3), Some(7), Severity::Warning, "'' : extra tokens"),
+                Diagnostic::new("", None, None, Severity::Error, "Linking fragment stage: Missing entry point"),
+            ],
+            diagnostics.diagnostics,
+        );
+        assert_eq!(2, diagnostics.errors().count());
+        assert_eq!(1, diagnostics.warnings().count());
+    }
+
+    #[test]
+    fn it_prints_the_offending_line() {
+        let source = "#version 450\n\nvoid main() {\n    float v = undefined_hash(1.0);\n}\n";
+        let diagnostics = ShaderDiagnostics::parse("main.glsl:4: error: 'undefined_hash' : no matching overloaded function found")
+            .with_source("main.glsl", source);
+
+        let expected = "error: 'undefined_hash' : no matching overloaded function found\n \
+                         --> main.glsl:4\n  \
+                          |\n\
+                        4 |     float v = undefined_hash(1.0);\n  \
+                          |               ^^^^^^^^^^^^^^\n";
+        assert_eq!(expected, diagnostics.to_string());
+
+        let diagnostic = Diagnostic::new("main.glsl", Some(4), Some(5), Severity::Warning, "unused variable");
+        assert_eq!(Some("  |     ^"), diagnostic.pretty_print(Some(source)).lines().last());
+    }
+
+    #[test]
+    fn it_places_the_caret_after_non_ascii_text_and_tabs() {
+        let source = "void main() {\n\tfloat größe = undefined_hash(1.0); // Größe\n}\n";
+        let diagnostics = ShaderDiagnostics::parse("main.glsl:2: error: 'undefined_hash' : no matching overloaded function found")
+            .with_source("main.glsl", source);
+
+        let printed = diagnostics.to_string();
+        let lines = printed.lines().collect::<Vec<&str>>();
+        assert_eq!("2 |     float größe = undefined_hash(1.0); // Größe", lines[3]);
+        assert_eq!("  |                   ^^^^^^^^^^^^^^", lines[4]);
+
+        // the column is a byte offset, 'u' starts at byte 17 after the tab & two 2-byte characters
+        let diagnostic = Diagnostic::new("main.glsl", Some(2), Some(18), Severity::Error, "unknown identifier");
+        assert_eq!(Some("  |                   ^"), diagnostic.pretty_print(Some(source)).lines().last());
+    }
+}
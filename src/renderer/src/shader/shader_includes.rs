@@ -0,0 +1,179 @@
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+
+#[cfg(feature = "glsl")]
+use shaderc::{IncludeCallbackResult, IncludeType, ResolvedInclude};
+
+/// Maximum depth of nested includes, guards against recursive includes
+#[cfg(feature = "glsl")]
+const MAX_INCLUDE_DEPTH: usize = 32;
+
+/// A virtual file system to resolve `#include` directives in GLSL shaders.
+///
+/// An include like `#include "noise/simplex.glsl"` is looked up relative to the including file,
+/// in the in-memory registry of snippets and in all search paths, in that order.
+/// Includes with angle brackets, e.g. `#include <lighting.glsl>`, skip the relative lookup.
+///
+/// Example:
+/// let includes = ShaderIncludes::new()
+///     .with_search_path("assets/shaders")
+///     .with_source("common/constants.glsl", "const float PI = 3.14159265;");
+///
+#[derive(Debug, Clone, Default)]
+pub struct ShaderIncludes {
+    /// Directories to search for included files
+    pub search_paths: Vec<PathBuf>,
+    /// In-memory snippets, keyed by their include name, e.g. `noise/simplex.glsl`
+    pub sources: HashMap<String, String>,
+}
+
+impl ShaderIncludes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a directory to search included files in
+    pub fn with_search_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.add_search_path(path);
+        self
+    }
+
+    /// Registers an in-memory snippet under the given include name
+    pub fn with_source(mut self, name: &str, source: &str) -> Self {
+        self.add_source(name, source);
+        self
+    }
+
+    /// Adds a directory to search included files in
+    pub fn add_search_path<P: Into<PathBuf>>(&mut self, path: P) {
+        self.search_paths.push(path.into());
+    }
+
+    /// Registers an in-memory snippet under the given include name
+    pub fn add_source(&mut self, name: &str, source: &str) {
+        self.sources.insert(name.to_string(), source.to_string());
+    }
+
+    /// Resolves the requested include, used as the include callback of the shader compiler.
+    ///
+    /// The resolved name is either the name of the snippet or the path of the file,
+    /// compiler errors inside included code refer to it.
+    #[cfg(feature = "glsl")]
+    pub fn resolve(
+        &self,
+        requested: &str,
+        include_type: IncludeType,
+        requesting: &str,
+        depth: usize,
+    ) -> IncludeCallbackResult {
+        if depth > MAX_INCLUDE_DEPTH {
+            return Err(format!("Include depth of {} exceeded, including '{}'", MAX_INCLUDE_DEPTH, requested));
+        }
+
+        if let IncludeType::Relative = include_type {
+            let relative = match Path::new(requesting).parent() {
+                Some(parent) => parent.join(requested),
+                None => PathBuf::from(requested),
+            };
+            let name = relative.to_string_lossy().replace('\\', "/");
+
+            if let Some(include) = self.resolve_source(&name) {
+                return Ok(include);
+            }
+            if let Some(include) = resolve_file(&relative) {
+                return Ok(include);
+            }
+        }
+
+        if let Some(include) = self.resolve_source(requested) {
+            return Ok(include);
+        }
+
+        self.search_paths
+            .iter()
+            .find_map(|search_path| resolve_file(&search_path.join(requested)))
+            .ok_or_else(|| format!("Cannot find include '{}' requested by '{}'", requested, requesting))
+    }
+
+    /// Returns the current content of a previously resolved include, either snippet or file
+    pub fn load(&self, resolved_name: &str) -> Option<String> {
+        match self.sources.get(resolved_name) {
+            Some(content) => Some(content.clone()),
+            None => read_file(Path::new(resolved_name)),
+        }
+    }
+
+    /// Returns the in-memory snippet with the given name
+    #[cfg(feature = "glsl")]
+    fn resolve_source(&self, name: &str) -> Option<ResolvedInclude> {
+        self.sources.get(name).map(|content| ResolvedInclude {
+            resolved_name: name.to_string(),
+            content: content.clone(),
+        })
+    }
+}
+
+/// Reads the file at the given path, if it exists
+fn read_file(path: &Path) -> Option<String> {
+    if !path.is_file() {
+        return None;
+    }
+
+    fs::read_to_string(path).ok()
+}
+
+/// Resolves the file at the given path, if it exists
+#[cfg(feature = "glsl")]
+fn resolve_file(path: &Path) -> Option<ResolvedInclude> {
+    read_file(path).map(|content| ResolvedInclude {
+        resolved_name: path.to_string_lossy().to_string(),
+        content,
+    })
+}
+
+#[cfg(all(test, feature = "glsl"))]
+mod tests {
+    use std::fs;
+
+    use shaderc::IncludeType;
+
+    use crate::ShaderIncludes;
+
+    #[test]
+    fn it_resolves_registered_sources() {
+        let includes = ShaderIncludes::new()
+            .with_source("noise/simplex.glsl", "float snoise(vec2 v);")
+            .with_source("noise/common.glsl", "vec3 permute(vec3 x);");
+
+        let include = includes.resolve("noise/simplex.glsl", IncludeType::Relative, "vertex_shader.glsl", 1).unwrap();
+        assert_eq!("noise/simplex.glsl", include.resolved_name);
+        assert_eq!("float snoise(vec2 v);", include.content);
+
+        // relative to the including snippet
+        let include = includes.resolve("common.glsl", IncludeType::Relative, "noise/simplex.glsl", 2).unwrap();
+        assert_eq!("noise/common.glsl", include.resolved_name);
+
+        assert!(includes.resolve("common.glsl", IncludeType::Standard, "noise/simplex.glsl", 2).is_err());
+        assert!(includes.resolve("noise/simplex.glsl", IncludeType::Standard, "main.glsl", 33).is_err());
+    }
+
+    #[test]
+    fn it_resolves_files_in_search_paths() {
+        let dir = std::env::temp_dir().join("renderer_shader_includes");
+        fs::create_dir_all(dir.join("lighting")).unwrap();
+        fs::write(dir.join("lighting/phong.glsl"), "vec3 phong();").unwrap();
+
+        let includes = ShaderIncludes::new().with_search_path(&dir);
+
+        let include = includes.resolve("lighting/phong.glsl", IncludeType::Standard, "main.glsl", 1).unwrap();
+        assert_eq!("vec3 phong();", include.content);
+        assert_eq!(dir.join("lighting/phong.glsl").to_string_lossy(), include.resolved_name);
+
+        let requesting = dir.join("lighting/main.glsl");
+        let include = ShaderIncludes::new()
+            .resolve("phong.glsl", IncludeType::Relative, &requesting.to_string_lossy(), 1)
+            .unwrap();
+        assert_eq!("vec3 phong();", include.content);
+
+        assert!(includes.resolve("lighting/missing.glsl", IncludeType::Relative, "main.glsl", 1).is_err());
+    }
+}
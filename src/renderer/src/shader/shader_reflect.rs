@@ -1,6 +1,6 @@
-use spirv_reflect::{ShaderModule, types::ReflectDescriptorBinding, types::ReflectDescriptorSet, types::{ReflectDescriptorType, ReflectInterfaceVariable, ReflectTypeDescription, ReflectTypeFlags}};
+use spirv_reflect::{ShaderModule, types::ReflectDescriptorBinding, types::ReflectDescriptorSet, types::{ReflectBlockVariable, ReflectDecorationFlags, ReflectDescriptorType, ReflectInterfaceVariable, ReflectTypeDescription, ReflectTypeFlags}};
 
-use crate::{BindGroupDescriptor, BindGroupEntry, BindingType, InputStepMode, Shader, ShaderStage, TextureComponentType, TextureViewDimension, UniformProperty, VertexAttributeDescriptor, VertexBufferDescriptor, VertexFormat};
+use crate::{spirv::{SpirvDecorations, parse_local_size}, BindGroupDescriptor, BindGroupEntry, BindingType, InputStepMode, Shader, ShaderStage, TextureComponentType, TextureViewDimension, UniformMember, UniformProperty, VertexAttributeDescriptor, VertexBufferDescriptor, VertexFormat};
 
 #[derive(Debug)]
 enum NumberType {
@@ -12,57 +12,64 @@ enum NumberType {
     UInt(u32, u32),
 }
 
-impl From<&ReflectTypeDescription> for NumberType {
-    fn from(description: &ReflectTypeDescription) -> Self {
+impl NumberType {
+    /// Returns the number type of a scalar, vector or matrix type, `None` for other types
+    fn reflect(description: &ReflectTypeDescription) -> Option<Self> {
         let numeric = &description.traits.numeric;
         let width = numeric.scalar.width;
         let components = numeric.vector.component_count;
 
         if description.type_flags.contains(ReflectTypeFlags::INT) {
             match numeric.scalar.signedness {
-                0 => NumberType::UInt(components, width),
-                1 => NumberType::Int(components, width),
+                0 => Some(NumberType::UInt(components, width)),
+                1 => Some(NumberType::Int(components, width)),
                 _ => panic!("Unexpected numeric signedness value {:?}", numeric.scalar),
             }
         } else if description.type_flags.contains(ReflectTypeFlags::FLOAT) {
-            NumberType::Float(components, width)
+            Some(NumberType::Float(components, width))
         } else {
-            panic!("Unexpected number type found {:?}", description.type_flags);
+            None
         }
     }
 }
 
-impl From<&ReflectTypeDescription> for VertexFormat {
+impl From<&ReflectTypeDescription> for NumberType {
     fn from(description: &ReflectTypeDescription) -> Self {
-        let number: NumberType = description.into();
-        match number {
-            NumberType::UInt(2, 8) => VertexFormat::Uchar2,
-            NumberType::UInt(4, 8) => VertexFormat::Uchar4,
-            NumberType::Int(2, 8) => VertexFormat::Char2,
-            NumberType::Int(4, 8) => VertexFormat::Char4,
-            NumberType::Float(2, 16) => VertexFormat::Half2,
-            NumberType::Float(4, 16) => VertexFormat::Half4,
-            NumberType::UInt(2, 16) => VertexFormat::Ushort2,
-            NumberType::UInt(4, 16) => VertexFormat::Ushort4,
-            NumberType::Int(2, 16) => VertexFormat::Short2,
-            NumberType::Int(4, 16) => VertexFormat::Short4,
-            NumberType::Float(0, 32) => VertexFormat::Float,
-            NumberType::Float(2, 32) => VertexFormat::Float2,
-            NumberType::Float(3, 32) => VertexFormat::Float3,
-            NumberType::Float(4, 32) => VertexFormat::Float4,
-            NumberType::Int(0, 32) => VertexFormat::Int,
-            NumberType::Int(2, 32) => VertexFormat::Int2,
-            NumberType::Int(3, 32) => VertexFormat::Int3,
-            NumberType::Int(4, 32) => VertexFormat::Int4,
-            NumberType::UInt(0, 32) => VertexFormat::Uint,
-            NumberType::UInt(2, 32) => VertexFormat::Uint2,
-            NumberType::UInt(3, 32) => VertexFormat::Uint3,
-            NumberType::UInt(4, 32) => VertexFormat::Uint4,
-            _ => panic!("Unexpected vertex format found: {:?}", number)
-        }
+        NumberType::reflect(description)
+            .unwrap_or_else(|| panic!("Unexpected number type found {:?}", description.type_flags))
     }
 }
 
+/// Returns the vertex format of the type, `None` if there is no matching format, e.g. for doubles
+fn vertex_format(description: &ReflectTypeDescription) -> Option<VertexFormat> {
+    let format = match NumberType::reflect(description)? {
+        NumberType::UInt(2, 8) => VertexFormat::Uchar2,
+        NumberType::UInt(4, 8) => VertexFormat::Uchar4,
+        NumberType::Int(2, 8) => VertexFormat::Char2,
+        NumberType::Int(4, 8) => VertexFormat::Char4,
+        NumberType::Float(2, 16) => VertexFormat::Half2,
+        NumberType::Float(4, 16) => VertexFormat::Half4,
+        NumberType::UInt(2, 16) => VertexFormat::Ushort2,
+        NumberType::UInt(4, 16) => VertexFormat::Ushort4,
+        NumberType::Int(2, 16) => VertexFormat::Short2,
+        NumberType::Int(4, 16) => VertexFormat::Short4,
+        NumberType::Float(0, 32) => VertexFormat::Float,
+        NumberType::Float(2, 32) => VertexFormat::Float2,
+        NumberType::Float(3, 32) => VertexFormat::Float3,
+        NumberType::Float(4, 32) => VertexFormat::Float4,
+        NumberType::Int(0, 32) => VertexFormat::Int,
+        NumberType::Int(2, 32) => VertexFormat::Int2,
+        NumberType::Int(3, 32) => VertexFormat::Int3,
+        NumberType::Int(4, 32) => VertexFormat::Int4,
+        NumberType::UInt(0, 32) => VertexFormat::Uint,
+        NumberType::UInt(2, 32) => VertexFormat::Uint2,
+        NumberType::UInt(3, 32) => VertexFormat::Uint3,
+        NumberType::UInt(4, 32) => VertexFormat::Uint4,
+        _ => return None,
+    };
+    Some(format)
+}
+
 impl From<&ReflectTypeDescription> for UniformProperty {
     fn from(description: &ReflectTypeDescription) -> Self {
         let flags = &description.type_flags;
@@ -73,6 +80,7 @@ impl From<&ReflectTypeDescription> for UniformProperty {
             let columns = traits.numeric.matrix.column_count;
             let rows = traits.numeric.matrix.row_count;
             match (number_type, columns, rows) {
+                (NumberType::Float(_, _), 2, 2) => UniformProperty::Mat2,
                 (NumberType::Float(_, _), 3, 3) => UniformProperty::Mat3,
                 (NumberType::Float(_, _), 4, 4) => UniformProperty::Mat4,
                 (number_type, columns, rows) => panic!(
@@ -84,10 +92,17 @@ impl From<&ReflectTypeDescription> for UniformProperty {
             let components = traits.numeric.vector.component_count;
             match (number_type, components) {
                 (NumberType::UInt(_, _), 0) => UniformProperty::UInt,
+                (NumberType::UInt(_, _), 2) => UniformProperty::UVec2,
+                (NumberType::UInt(_, _), 3) => UniformProperty::UVec3,
+                (NumberType::UInt(_, _), 4) => UniformProperty::UVec4,
                 (NumberType::Int(_, _), 0) => UniformProperty::Int,
+                (NumberType::Int(_, _), 2) => UniformProperty::IVec2,
+                (NumberType::Int(_, _), 3) => UniformProperty::IVec3,
+                (NumberType::Int(_, _), 4) => UniformProperty::IVec4,
                 (NumberType::Float(_, _), 0) => UniformProperty::Float,
                 (NumberType::Float(_, _), 2) => UniformProperty::Vec2,
                 (NumberType::Float(_, _), 3) => UniformProperty::Vec3,
+                (NumberType::Float(_, _), 4) => UniformProperty::Vec4,
                 (number_type, components) => panic!(
                     "Unexpected uniform property format {:?} {}",
                     number_type, components
@@ -97,6 +112,19 @@ impl From<&ReflectTypeDescription> for UniformProperty {
     }
 }
 
+impl From<&ReflectTypeDescription> for TextureComponentType {
+    fn from(description: &ReflectTypeDescription) -> Self {
+        if description.type_flags.contains(ReflectTypeFlags::INT) {
+            match description.traits.numeric.scalar.signedness {
+                0 => TextureComponentType::UInt,
+                _ => TextureComponentType::Int,
+            }
+        } else {
+            TextureComponentType::Float
+        }
+    }
+}
+
 impl From<spirv_reflect::types::ReflectDimension> for TextureViewDimension {
     fn from(dim: spirv_reflect::types::ReflectDimension) -> Self {
         match dim {
@@ -112,7 +140,12 @@ impl From<spirv_reflect::types::ReflectDimension> for TextureViewDimension {
 impl From<&ReflectTypeDescription> for TextureViewDimension {
     fn from(description: &ReflectTypeDescription) -> Self {
         if description.type_flags.contains(ReflectTypeFlags::EXTERNAL_IMAGE) {
-            description.traits.image.dim.into()
+            let image = &description.traits.image;
+            match (image.dim.into(), image.arrayed) {
+                (TextureViewDimension::D2, 1) => TextureViewDimension::D2Array,
+                (TextureViewDimension::Cube, 1) => TextureViewDimension::CubeArray,
+                (dimension, _) => dimension,
+            }
         } else {
             panic!("Resource type {} is not an sampler / texture")
         }
@@ -125,10 +158,16 @@ impl From<&ReflectTypeDescription> for TextureViewDimension {
 pub struct ShaderLayout {
     /// Name of the entry point
     pub entry_point: String,
+    /// The stage of the shader
+    pub stage: ShaderStage,
     /// The list of bind groups
     pub bind_groups: Vec<BindGroupDescriptor>,
-    /// The list of vertex buffer descriptors
+    /// The list of vertex buffer descriptors, one per input variable
     pub vertex_buffer_descriptors: Vec<VertexBufferDescriptor>,
+    /// The output variables passed on to the next stage, sorted by location
+    pub outputs: Vec<VertexAttributeDescriptor>,
+    /// The workgroup size of a compute shader
+    pub local_size: Option<[u32; 3]>,
 }
 
 impl ShaderLayout {
@@ -136,6 +175,14 @@ impl ShaderLayout {
     pub fn from_shader(shader: &Shader) -> ShaderLayout {
         reflect(shader.as_bytes())
     }
+
+    /// Returns the input variables of the shader, sorted by location
+    pub fn inputs(&self) -> Vec<&VertexAttributeDescriptor> {
+        self.vertex_buffer_descriptors
+            .iter()
+            .flat_map(|descriptor| descriptor.attributes.iter())
+            .collect()
+    }
 }
 
 /// Reflect the given shader
@@ -145,15 +192,26 @@ pub(crate) fn reflect(spv_data: &[u8]) -> ShaderLayout {
             let entry_point = module.get_entry_point_name();
             let shader_stage: ShaderStage = module.get_shader_stage().into();
 
-            let bind_groups: Vec<BindGroupDescriptor> = reflect_bind_groups(&module, shader_stage);
-            let vertex_buffer_descriptors: Vec<VertexBufferDescriptor> = reflect_input_variables(&module);
+            let decorations = SpirvDecorations::parse(spv_data);
+            let bind_groups: Vec<BindGroupDescriptor> = reflect_bind_groups(&module, shader_stage, &decorations);
+            let vertex_buffer_descriptors: Vec<VertexBufferDescriptor> = match shader_stage {
+                ShaderStage::Compute => Vec::new(),
+                _ => reflect_input_variables(&module),
+            };
+            let outputs = match shader_stage {
+                ShaderStage::Compute => Vec::new(),
+                _ => reflect_output_variables(&module),
+            };
 
             reflect_push_constant_blocks(&module);
 
             ShaderLayout {
                 entry_point,
+                stage: shader_stage,
                 bind_groups,
                 vertex_buffer_descriptors,
+                outputs,
+                local_size: parse_local_size(spv_data),
             }
         }
         Err(err) => panic!("Failed to reflect shader layout: {:?}", err),
@@ -161,21 +219,26 @@ pub(crate) fn reflect(spv_data: &[u8]) -> ShaderLayout {
 }
 
 /// Returns the list of bind groups in the shader
-pub(crate) fn reflect_bind_groups(shader_module: &ShaderModule, shader_stage: ShaderStage) -> Vec<BindGroupDescriptor> {
+pub(crate) fn reflect_bind_groups(
+    shader_module: &ShaderModule,
+    shader_stage: ShaderStage,
+    decorations: &SpirvDecorations,
+) -> Vec<BindGroupDescriptor> {
     let descriptor_sets = shader_module.enumerate_descriptor_sets(None).unwrap();
     descriptor_sets
         .iter()
-        .map(|descriptor_set| reflect_bind_group(descriptor_set, shader_stage))
+        .map(|descriptor_set| reflect_bind_group(descriptor_set, shader_stage, decorations))
         .collect()
 }
 
 fn reflect_bind_group(
     descriptor_set: &ReflectDescriptorSet,
     shader_stage: ShaderStage,
+    decorations: &SpirvDecorations,
 ) -> BindGroupDescriptor {
     let bindings = descriptor_set.bindings
         .iter()
-        .map(|descriptor_binding| reflect_binding(descriptor_binding, shader_stage))
+        .map(|descriptor_binding| reflect_binding(descriptor_binding, shader_stage, decorations))
         .collect();
 
     BindGroupDescriptor::new(descriptor_set.set, bindings)
@@ -184,6 +247,7 @@ fn reflect_bind_group(
 pub(crate) fn reflect_binding(
     binding: &ReflectDescriptorBinding,
     shader_stage: ShaderStage,
+    decorations: &SpirvDecorations,
 ) -> BindGroupEntry {
     let type_description = binding.type_description.as_ref().unwrap();
 
@@ -192,14 +256,36 @@ pub(crate) fn reflect_binding(
             &type_description.type_name,
             BindingType::Uniform {
                 dynamic: false,
-                property: reflect_uniform(type_description),
+                property: reflect_uniform(&binding.block),
+            }
+        ),
+        ReflectDescriptorType::StorageBuffer => (
+            &type_description.type_name,
+            BindingType::StorageBuffer {
+                dynamic: false,
+                readonly: is_readonly(&binding.block) || decorations.non_writable.contains(&binding.spirv_id),
+                property: reflect_uniform(&binding.block),
             }
         ),
-        ReflectDescriptorType::CombinedImageSampler => (
+        ReflectDescriptorType::CombinedImageSampler | ReflectDescriptorType::SampledImage => (
             &binding.name,
             BindingType::SampledTexture {
                 dimension: type_description.into(),
-                component_type: TextureComponentType::Float,
+                component_type: type_description.into(),
+            }
+        ),
+        ReflectDescriptorType::Sampler => (
+            &binding.name,
+            BindingType::Sampler {
+                comparison: decorations.comparison_samplers.contains(&binding.spirv_id),
+            }
+        ),
+        ReflectDescriptorType::StorageImage => (
+            &binding.name,
+            BindingType::StorageTexture {
+                dimension: type_description.into(),
+                format: binding.image.image_format.into(),
+                readonly: decorations.non_writable.contains(&binding.spirv_id),
             }
         ),
         _ => panic!("Unsupported binding type {:?}", binding.descriptor_type),
@@ -209,57 +295,128 @@ pub(crate) fn reflect_binding(
         name: name.to_string(),
         index: binding.binding,
         binding_type,
-        shader_stage,
+        shader_stages: shader_stage.into(),
     }
 }
 
 pub(crate) fn reflect_input_variables(shader_module: &ShaderModule) -> Vec<VertexBufferDescriptor> {
     let variables = shader_module.enumerate_input_variables(None).unwrap();
-    let mut vertex_attributes = variables.iter()
-        .map(|variable| reflect_vertex_attribute(&variable))
-        .collect::<Vec<VertexAttributeDescriptor>>();
-
-    vertex_attributes.sort_by_key(|va| va.location);
-    vertex_attributes.drain(..)
-        .into_iter()
+    reflect_interface_variables(&variables)
+        .drain(..)
         .map(|attr| VertexBufferDescriptor::from_attribute(attr, InputStepMode::Vertex))
         .collect()
 }
 
+/// Returns the list of output variables, built-in variables like `gl_Position` are skipped
+pub(crate) fn reflect_output_variables(shader_module: &ShaderModule) -> Vec<VertexAttributeDescriptor> {
+    let variables = shader_module.enumerate_output_variables(None).unwrap();
+    reflect_interface_variables(&variables)
+}
+
+/// Returns the non built-in variables sorted by location.
+///
+/// Members of interface blocks are flattened into consecutive locations, variables without
+/// a matching vertex format, e.g. doubles, are skipped.
+fn reflect_interface_variables(variables: &[ReflectInterfaceVariable]) -> Vec<VertexAttributeDescriptor> {
+    let mut attributes = Vec::new();
+    for variable in variables.iter().filter(|variable| !is_built_in(variable)) {
+        flatten_interface_variable(variable, variable.location, &mut attributes);
+    }
+
+    attributes.sort_by_key(|attribute| attribute.location);
+    attributes
+}
+
+/// Adds the variable or the members of a block starting at the given location,
+/// returns the number of locations the variable occupies
+fn flatten_interface_variable(
+    variable: &ReflectInterfaceVariable,
+    location: u32,
+    attributes: &mut Vec<VertexAttributeDescriptor>,
+) -> u32 {
+    let description = variable.type_description.as_ref().unwrap();
+
+    if description.type_flags.contains(ReflectTypeFlags::STRUCT) {
+        return variable.members
+            .iter()
+            .fold(0, |used, member| used + flatten_interface_variable(member, location + used, attributes));
+    }
+
+    if let Some(format) = vertex_format(description) {
+        attributes.push(VertexAttributeDescriptor {
+            name: variable.name.clone(),
+            location,
+            offset: 0,
+            format,
+        });
+    }
+
+    location_count(description)
+}
+
+/// Returns the number of locations a variable of the given type occupies
+fn location_count(description: &ReflectTypeDescription) -> u32 {
+    let numeric = &description.traits.numeric;
+    let columns = if description.type_flags.contains(ReflectTypeFlags::MATRIX) {
+        numeric.matrix.column_count.max(1)
+    } else {
+        1
+    };
+    // dvec3 & dvec4 take two locations each
+    let slots = if numeric.scalar.width == 64 && numeric.vector.component_count > 2 { 2 } else { 1 };
+
+    description.traits.array.dims
+        .iter()
+        .fold(columns * slots, |count, length| count * (*length).max(1))
+}
+
+/// Returns true if the variable is a built-in, e.g. `gl_Position` or `gl_VertexIndex`
+fn is_built_in(variable: &ReflectInterfaceVariable) -> bool {
+    variable.decoration_flags.contains(ReflectDecorationFlags::BUILT_IN)
+}
+
 pub(crate) fn reflect_push_constant_blocks(shader_module: &ShaderModule) {
     let _block_variables = shader_module.enumerate_push_constant_blocks(None).unwrap();
     // println!("----PUSH CONSTANT BLOCKS: {:?}", block_variables);
 }
 
-pub(crate) fn reflect_vertex_attribute(variable: &ReflectInterfaceVariable) -> VertexAttributeDescriptor {
-    VertexAttributeDescriptor {
-        name: variable.name.clone(),
-        location: variable.location,
-        offset: 0,
-        format: variable.type_description.as_ref().unwrap().into(),
-    }
+/// Returns true if the buffer block or all of its members are declared readonly
+fn is_readonly(block: &ReflectBlockVariable) -> bool {
+    block.decoration_flags.contains(ReflectDecorationFlags::NON_WRITABLE)
+        || (!block.members.is_empty() && block.members
+            .iter()
+            .all(|member| member.decoration_flags.contains(ReflectDecorationFlags::NON_WRITABLE)))
 }
 
-fn reflect_uniform(type_description: &ReflectTypeDescription) -> UniformProperty {
-    if type_description.type_flags.contains(ReflectTypeFlags::STRUCT) {
-        let uniforms = type_description.members
+/// Reflects the block variable into a tree of named members, arrays & nested structs
+fn reflect_uniform(block: &ReflectBlockVariable) -> UniformProperty {
+    let type_description = block.type_description.as_ref().unwrap();
+
+    let property = if type_description.type_flags.contains(ReflectTypeFlags::STRUCT) {
+        let members = block.members
             .iter()
-            .map(|description| reflect_uniform(description))
+            .map(|member| UniformMember::new(&member.name, member.offset as u64, reflect_uniform(member)))
             .collect();
 
-        UniformProperty::Struct(uniforms)
+        UniformProperty::Struct(members)
     } else {
         type_description.into()
-    }
+    };
+
+    // multi dimensional arrays are nested, the outermost dimension comes first
+    type_description.traits.array.dims
+        .iter()
+        .rev()
+        .fold(property, |element, length| UniformProperty::Array(Box::new(element), *length as usize))
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "glsl"))]
 mod tests {
-    use crate::{BindGroupDescriptor, BindGroupEntry, BindingType, InputStepMode, Renderer, Shader, ShaderLayout, ShaderStage, TextureComponentType, TextureViewDimension, UniformProperty, VertexAttributeDescriptor, VertexBufferDescriptor, VertexFormat};
+    use crate::{BindGroupDescriptor, BindGroupEntry, BindingType, InputStepMode, LayoutRule, Renderer, Shader, ShaderLayout, ShaderSource, ShaderStage, TextureComponentType, TextureFormat, TextureViewDimension, UniformMember, UniformProperty, VertexAttributeDescriptor, VertexBufferDescriptor, VertexFormat};
 
-    fn shader_layout(source: &str) -> ShaderLayout {
-        let renderer = futures::executor::block_on(Renderer::new());
-        let shader = Shader::compile(source, ShaderStage::Vertex, &renderer.device).unwrap();
+    fn shader_layout(source: &str, shader_stage: ShaderStage) -> ShaderLayout {
+        let renderer = futures::executor::block_on(Renderer::new()).unwrap();
+        let shader = Shader::compile(source, shader_stage, &renderer.device).unwrap();
         ShaderLayout::from_shader(&shader)
     }
 
@@ -285,7 +442,7 @@ mod tests {
         }
         "#;
 
-        let layout = shader_layout(&VERTEX_SHADER);
+        let layout = shader_layout(&VERTEX_SHADER, ShaderStage::Vertex);
         assert_eq!(layout.entry_point, "main");
         assert_eq!(
             vec![
@@ -298,11 +455,11 @@ mod tests {
                             binding_type: BindingType::Uniform {
                                 dynamic: false,
                                 property: UniformProperty::Struct(vec![
-                                    UniformProperty::Vec3,
-                                    UniformProperty::Mat4,
+                                    UniformMember::new("light", 0, UniformProperty::Vec3),
+                                    UniformMember::new("modelView", 16, UniformProperty::Mat4),
                                 ]),
                             },
-                            shader_stage: ShaderStage::Vertex,
+                            shader_stages: ShaderStage::Vertex.into(),
                         },
                     ],
                 ),
@@ -316,7 +473,7 @@ mod tests {
                                 dimension: TextureViewDimension::D2,
                                 component_type: TextureComponentType::Float,
                             },
-                            shader_stage: ShaderStage::Vertex,
+                            shader_stages: ShaderStage::Vertex.into(),
                         },
                     ],
                 ),
@@ -348,9 +505,21 @@ mod tests {
         }
         "#;
 
-        let layout = shader_layout(&VERTEX_SHADER);
+        let layout = shader_layout(&VERTEX_SHADER, ShaderStage::Vertex);
 
         assert_eq!(layout.entry_point, "main");
+        assert_eq!(layout.stage, ShaderStage::Vertex);
+        assert_eq!(
+            vec![
+                VertexAttributeDescriptor {
+                    name: "vertex".into(),
+                    offset: 0,
+                    location: 0,
+                    format: VertexFormat::Float3,
+                },
+            ],
+            layout.outputs,
+        );
         assert_eq!(
             vec![
                 BindGroupDescriptor::new(
@@ -362,11 +531,11 @@ mod tests {
                             binding_type: BindingType::Uniform {
                                 dynamic: false,
                                 property: UniformProperty::Struct(vec![
-                                    UniformProperty::Mat4,
-                                    UniformProperty::Mat4,
+                                    UniformMember::new("modelViewProjection", 0, UniformProperty::Mat4),
+                                    UniformMember::new("modelView", 64, UniformProperty::Mat4),
                                 ]),
                             },
-                            shader_stage: ShaderStage::Vertex,
+                            shader_stages: ShaderStage::Vertex.into(),
                         }
                     ],
                 ),
@@ -406,4 +575,281 @@ mod tests {
             layout.vertex_buffer_descriptors,
         );
     }
+
+    #[test]
+    fn test_reflect_output_blocks() {
+        const VERTEX_SHADER: &str = r#"
+        #version 450
+
+        layout(location = 0) in vec3 i_position;
+
+        layout(location = 0) flat out double depth;
+        layout(location = 1) out VertexData {
+            vec2 uv;
+            vec3 normal;
+        } o;
+
+        void main() {
+            depth = double(i_position.z);
+            o.uv = i_position.xy;
+            o.normal = i_position;
+            gl_Position = vec4(i_position, 1.0);
+        }
+        "#;
+
+        let layout = shader_layout(&VERTEX_SHADER, ShaderStage::Vertex);
+
+        // members of the block are flattened, the double has no vertex format & is skipped
+        assert_eq!(
+            vec![
+                VertexAttributeDescriptor {
+                    name: "uv".into(),
+                    offset: 0,
+                    location: 1,
+                    format: VertexFormat::Float2,
+                },
+                VertexAttributeDescriptor {
+                    name: "normal".into(),
+                    offset: 0,
+                    location: 2,
+                    format: VertexFormat::Float3,
+                },
+            ],
+            layout.outputs,
+        );
+    }
+
+    #[test]
+    fn test_reflect_nested_uniforms() {
+        const VERTEX_SHADER: &str = r#"
+        #version 450
+
+        struct Light {
+            vec4 color;
+            ivec2 tile;
+            uvec4 flags;
+        };
+
+        layout(binding = 0) uniform Lights {
+            mat2 rotation;
+            Light lights[4];
+            float weights[2][3];
+        } ubo;
+
+        void main() {
+            vec2 position = ubo.rotation * vec2(ubo.lights[2].tile + ivec2(ubo.lights[1].flags.xy));
+            gl_Position = ubo.lights[3].color * ubo.weights[1][2] * position.x;
+        }
+        "#;
+
+        let layout = shader_layout(&VERTEX_SHADER, ShaderStage::Vertex);
+        let light = UniformProperty::structure(
+            vec![
+                ("color", UniformProperty::Vec4),
+                ("tile", UniformProperty::IVec2),
+                ("flags", UniformProperty::UVec4),
+            ],
+            LayoutRule::Std140,
+        );
+        let weights = UniformProperty::Array(
+            Box::new(UniformProperty::Array(Box::new(UniformProperty::Float), 3)),
+            2,
+        );
+
+        let property = match &layout.bind_groups[0].bindings[0].binding_type {
+            BindingType::Uniform { property, .. } => property.clone(),
+            binding_type => panic!("Unexpected binding type {:?}", binding_type),
+        };
+        assert_eq!(
+            UniformProperty::Struct(vec![
+                UniformMember::new("rotation", 0, UniformProperty::Mat2),
+                UniformMember::new("lights", 32, UniformProperty::Array(Box::new(light), 4)),
+                UniformMember::new("weights", 224, weights),
+            ]),
+            property,
+        );
+        assert_eq!(Some(96), property.find("ubo.lights[1].tile", LayoutRule::Std140).map(|f| f.offset));
+        assert_eq!(Some(304), property.find("ubo.weights[1][2]", LayoutRule::Std140).map(|f| f.offset));
+    }
+
+    #[test]
+    fn test_reflect_storage_buffers() {
+        const COMPUTE_SHADER: &str = r#"
+        #version 450
+
+        layout(local_size_x = 64) in;
+
+        layout(set = 0, binding = 0) readonly buffer Input {
+            vec4 positions[];
+        } src;
+
+        layout(set = 0, binding = 1) buffer Output {
+            uint count;
+            vec4 velocities[];
+        } dst;
+
+        void main() {
+            uint index = gl_GlobalInvocationID.x;
+            dst.velocities[index] = src.positions[index];
+            dst.count = index;
+        }
+        "#;
+
+        let layout = shader_layout(&COMPUTE_SHADER, ShaderStage::Compute);
+        assert_eq!(Some([64, 1, 1]), layout.local_size);
+        assert_eq!(
+            vec![
+                BindGroupEntry {
+                    index: 0,
+                    name: "Input".into(),
+                    binding_type: BindingType::StorageBuffer {
+                        dynamic: false,
+                        readonly: true,
+                        property: UniformProperty::Struct(vec![
+                            UniformMember::new("positions", 0, UniformProperty::Array(Box::new(UniformProperty::Vec4), 0)),
+                        ]),
+                    },
+                    shader_stages: ShaderStage::Compute.into(),
+                },
+                BindGroupEntry {
+                    index: 1,
+                    name: "Output".into(),
+                    binding_type: BindingType::StorageBuffer {
+                        dynamic: false,
+                        readonly: false,
+                        property: UniformProperty::Struct(vec![
+                            UniformMember::new("count", 0, UniformProperty::UInt),
+                            UniformMember::new("velocities", 16, UniformProperty::Array(Box::new(UniformProperty::Vec4), 0)),
+                        ]),
+                    },
+                    shader_stages: ShaderStage::Compute.into(),
+                },
+            ],
+            layout.bind_groups[0].bindings,
+        );
+    }
+
+    #[test]
+    fn test_reflect_separate_textures_and_samplers() {
+        const FRAGMENT_SHADER: &str = r#"
+        #version 450
+
+        layout(location = 0) in vec2 v_uv;
+        layout(location = 0) out vec4 outColor;
+
+        layout(set = 0, binding = 0) uniform texture2D t_color;
+        layout(set = 0, binding = 1) uniform sampler s_color;
+        layout(set = 0, binding = 2) uniform texture2D t_shadow;
+        layout(set = 0, binding = 3) uniform samplerShadow s_shadow;
+        layout(set = 0, binding = 4) uniform utexture2DArray t_ids;
+
+        void main() {
+            float shadow = texture(sampler2DShadow(t_shadow, s_shadow), vec3(v_uv, 0.5));
+            uint id = texelFetch(t_ids, ivec3(0), 0).r;
+            outColor = texture(sampler2D(t_color, s_color), v_uv) * shadow * float(id);
+        }
+        "#;
+
+        let layout = shader_layout(&FRAGMENT_SHADER, ShaderStage::Fragment);
+        let binding_types = layout.bind_groups[0].bindings
+            .iter()
+            .map(|binding| (binding.name.as_str(), binding.binding_type.clone()))
+            .collect::<Vec<(&str, BindingType)>>();
+
+        let texture_2d = BindingType::SampledTexture {
+            dimension: TextureViewDimension::D2,
+            component_type: TextureComponentType::Float,
+        };
+        assert_eq!(
+            vec![
+                ("t_color", texture_2d.clone()),
+                ("s_color", BindingType::Sampler { comparison: false }),
+                ("t_shadow", texture_2d),
+                ("s_shadow", BindingType::Sampler { comparison: true }),
+                ("t_ids", BindingType::SampledTexture {
+                    dimension: TextureViewDimension::D2Array,
+                    component_type: TextureComponentType::UInt,
+                }),
+            ],
+            binding_types,
+        );
+    }
+
+    #[test]
+    fn test_reflect_storage_textures() {
+        const COMPUTE_SHADER: &str = r#"
+        #version 450
+
+        layout(local_size_x = 8, local_size_y = 8) in;
+
+        layout(set = 0, binding = 0, rgba8) uniform readonly image2D inputImage;
+        layout(set = 0, binding = 1, r32f) uniform writeonly image3D noiseField;
+
+        void main() {
+            ivec2 position = ivec2(gl_GlobalInvocationID.xy);
+            vec4 color = imageLoad(inputImage, position);
+            imageStore(noiseField, ivec3(position, 0), vec4(color.r));
+        }
+        "#;
+
+        let layout = shader_layout(&COMPUTE_SHADER, ShaderStage::Compute);
+        let binding_types = layout.bind_groups[0].bindings
+            .iter()
+            .map(|binding| binding.binding_type.clone())
+            .collect::<Vec<BindingType>>();
+
+        assert_eq!(
+            vec![
+                BindingType::StorageTexture {
+                    dimension: TextureViewDimension::D2,
+                    format: TextureFormat::Rgba8Unorm,
+                    readonly: true,
+                },
+                BindingType::StorageTexture {
+                    dimension: TextureViewDimension::D3,
+                    format: TextureFormat::R32Float,
+                    readonly: false,
+                },
+            ],
+            binding_types,
+        );
+    }
+
+    #[test]
+    fn test_reflect_precompiled_spirv() {
+        const VERTEX_SHADER: &str = r#"
+        #version 450
+
+        layout(location = 0) in vec3 i_position;
+        layout(location = 1) in vec2 i_uv;
+        layout(location = 0) out vec2 o_uv;
+
+        layout(set = 0, binding = 0) uniform Camera {
+            mat4 viewProjection;
+        };
+
+        void main() {
+            o_uv = i_uv;
+            gl_Position = viewProjection * vec4(i_position, 1.0);
+        }
+        "#;
+
+        let renderer = futures::executor::block_on(Renderer::new()).unwrap();
+        let compiled = Shader::compile(VERTEX_SHADER, ShaderStage::Vertex, &renderer.device).unwrap();
+
+        let source = ShaderSource::spirv_from_bytes(compiled.as_bytes()).unwrap();
+        let loaded = Shader::from_source(&source, ShaderStage::Vertex, &renderer.device).unwrap();
+        assert_eq!(compiled.as_binary(), loaded.as_binary());
+
+        let expected = ShaderLayout::from_shader(&compiled);
+        let layout = ShaderLayout::from_shader(&loaded);
+        assert_eq!(expected.entry_point, layout.entry_point);
+        assert_eq!(expected.vertex_buffer_descriptors, layout.vertex_buffer_descriptors);
+        assert_eq!(
+            expected.bind_groups.iter().map(|group| (group.index, group.bindings.clone())).collect::<Vec<_>>(),
+            layout.bind_groups.iter().map(|group| (group.index, group.bindings.clone())).collect::<Vec<_>>(),
+        );
+
+        assert!(Shader::from_spirv(vec![0x0203_0723, 0, 0, 0, 0], ShaderStage::Vertex, &renderer.device).is_err());
+    }
 }
@@ -0,0 +1,461 @@
+use std::{collections::{HashMap, HashSet}, fs, path::{Path, PathBuf}, sync::{Arc, mpsc::{channel, Receiver}}, time::Duration};
+
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{IncludeDependency, PipelineDescriptor, RenderPipelineId, Renderer, Shader, ShaderDefs, ShaderError, ShaderStage};
+
+/// Delay to wait for further events of a file, editors often write files in multiple steps
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(125);
+
+/// The result of reloading a changed shader file
+#[derive(Debug)]
+pub enum ShaderReload {
+    /// The shader was recompiled, all pipelines using it were rebuilt
+    Reloaded(PathBuf),
+    /// The shader failed to compile or does not fit the other shaders of a pipeline,
+    /// the previous shader & pipelines are kept
+    Failed(PathBuf, ShaderError),
+}
+
+/// A shader loaded from file, together with all files it depends on
+struct WatchedShader {
+    /// Path of the shader file
+    path: PathBuf,
+    /// The stage the shader is compiled for
+    stage: ShaderStage,
+    /// The shader file & all included files
+    files: HashSet<PathBuf>,
+    /// The current version of the shader
+    shader: Shader,
+}
+
+/// A recompiled shader, replaces the watched shader at the index once all pipelines are validated
+struct ReloadedShader {
+    /// Index of the watched shader
+    index: usize,
+    /// The new version of the shader
+    shader: Shader,
+    /// The shader file & all files included by the new version
+    files: HashSet<PathBuf>,
+}
+
+/// Watches shader files & their includes, recompiles shaders on change and rebuilds
+/// all render pipelines using them.
+///
+/// Pipelines keep their `RenderPipelineId`, when a shader fails to compile the previous
+/// shader & pipelines stay in use.
+///
+/// Example:
+/// let mut watcher = ShaderWatcher::new()?;
+/// let vertex_shader = watcher.load_shader(&renderer, "shaders/vertex.glsl", ShaderStage::Vertex)?;
+/// let fragment_shader = watcher.load_shader(&renderer, "shaders/fragment.glsl", ShaderStage::Fragment)?;
+/// let pipeline = watcher.create_pipeline(&mut renderer, PipelineDescriptor::new(vertex_shader, fragment_shader)?)?;
+///
+/// // once per frame
+/// for reload in watcher.update(&mut renderer) { ... }
+///
+pub struct ShaderWatcher {
+    /// The file system watcher
+    watcher: RecommendedWatcher,
+    /// Receives the file system events
+    receiver: Receiver<DebouncedEvent>,
+    /// All watched directories, files are replaced by some editors, therefore their directories are watched
+    directories: HashSet<PathBuf>,
+    /// All shaders loaded from file
+    shaders: Vec<WatchedShader>,
+    /// All pipelines to rebuild when one of their shaders changes
+    pipelines: HashMap<RenderPipelineId, PipelineDescriptor>,
+}
+
+impl ShaderWatcher {
+    pub fn new() -> Result<Self, ShaderError> {
+        let (sender, receiver) = channel();
+        let watcher = RecommendedWatcher::new(sender, DEBOUNCE_DELAY).map_err(|e| ShaderError::WatchFailed(e.to_string()))?;
+
+        Ok(Self {
+            watcher,
+            receiver,
+            directories: HashSet::new(),
+            shaders: Vec::new(),
+            pipelines: HashMap::new(),
+        })
+    }
+
+    /// Loads & compiles the shader from file, watches the file & all its included files
+    pub fn load_shader<P: AsRef<Path>>(
+        &mut self,
+        renderer: &Renderer,
+        path: P,
+        stage: ShaderStage,
+    ) -> Result<Shader, ShaderError> {
+        self.load_shader_variant(renderer, path, stage, &ShaderDefs::default())
+    }
+
+    /// Loads & compiles the shader variant with the given defines, the defines are kept on reload
+    pub fn load_shader_variant<P: AsRef<Path>>(
+        &mut self,
+        renderer: &Renderer,
+        path: P,
+        stage: ShaderStage,
+        defs: &ShaderDefs,
+    ) -> Result<Shader, ShaderError> {
+        let path = canonical_path(path.as_ref());
+        let shader = renderer.create_shader_variant_from_file(&path, stage, defs)?;
+
+        let files = shader_files(&path, &shader.dependencies);
+        self.watch_directories(&files)?;
+        self.shaders.push(WatchedShader {
+            path,
+            stage,
+            files,
+            shader: shader.clone(),
+        });
+
+        Ok(shader)
+    }
+
+    /// Creates the render pipeline, it is rebuilt whenever one of its shaders changes
+    pub fn create_pipeline(
+        &mut self,
+        renderer: &mut Renderer,
+        pipeline_descriptor: PipelineDescriptor,
+    ) -> Result<RenderPipelineId, ShaderError> {
+        let pipeline_id = renderer.create_pipeline(&pipeline_descriptor)?;
+        self.pipelines.insert(pipeline_id, pipeline_descriptor);
+        Ok(pipeline_id)
+    }
+
+    /// Removes the render pipeline from the renderer, it is no longer rebuilt afterwards.
+    ///
+    /// Returns false if there is no pipeline with the given id.
+    pub fn evict_pipeline(&mut self, renderer: &mut Renderer, pipeline_id: &RenderPipelineId) -> bool {
+        let watched = self.pipelines.remove(pipeline_id).is_some();
+        renderer.evict_pipeline(pipeline_id) || watched
+    }
+
+    /// Reloads all shaders whose files changed since the last call, call this once per frame
+    pub fn update(&mut self, renderer: &mut Renderer) -> Vec<ShaderReload> {
+        let changed = self.changed_files();
+        if changed.is_empty() {
+            return Vec::new();
+        }
+
+        let indices = (0..self.shaders.len())
+            .filter(|index| !self.shaders[*index].files.is_disjoint(&changed))
+            .collect::<Vec<usize>>();
+        self.reload(&indices, renderer)
+    }
+
+    /// Recompiles the given shaders, then rebuilds every pipeline using them once with all new shaders.
+    ///
+    /// Shaders of a pipeline whose new layout is invalid are rejected together, that pipeline
+    /// and all others using one of these shaders keep their previous shaders.
+    fn reload(&mut self, indices: &[usize], renderer: &mut Renderer) -> Vec<ShaderReload> {
+        let mut reloads = Vec::new();
+
+        // all changed shaders are compiled first, shader stages may change their interface together
+        let mut compiled = Vec::new();
+        for &index in indices {
+            let path = self.shaders[index].path.clone();
+            match self.recompile(index, renderer) {
+                Ok(reloaded) => compiled.push(reloaded),
+                Err(error) => reloads.push(ShaderReload::Failed(path, error)),
+            }
+        }
+
+        // all affected pipelines are checked before any pipeline is rebuilt
+        let descriptors = loop {
+            match self.reloaded_pipelines(&compiled) {
+                Ok(descriptors) => break descriptors,
+                Err((rejected, error)) => {
+                    compiled.retain(|reloaded| {
+                        if rejected.contains(&reloaded.index) {
+                            reloads.push(ShaderReload::Failed(self.shaders[reloaded.index].path.clone(), error.clone()));
+                            false
+                        } else {
+                            true
+                        }
+                    });
+                }
+            }
+        };
+
+        for (pipeline_id, descriptor) in descriptors {
+            // pipelines evicted from the renderer directly are no longer rebuilt
+            if renderer.rebuild_pipeline(&pipeline_id, &descriptor) {
+                self.pipelines.insert(pipeline_id, descriptor);
+            } else {
+                self.pipelines.remove(&pipeline_id);
+            }
+        }
+
+        for reloaded in compiled {
+            let watched = &mut self.shaders[reloaded.index];
+            watched.files = reloaded.files;
+            watched.shader = reloaded.shader;
+            reloads.push(ShaderReload::Reloaded(watched.path.clone()));
+        }
+        reloads
+    }
+
+    /// Recompiles the watched shader with the defines of its current version
+    fn recompile(&mut self, index: usize, renderer: &Renderer) -> Result<ReloadedShader, ShaderError> {
+        let watched = &self.shaders[index];
+        let shader = renderer.create_shader_variant_from_file(&watched.path, watched.stage, &watched.shader.defs)?;
+        let files = shader_files(&watched.path, &shader.dependencies);
+        self.watch_directories(&files)?;
+
+        Ok(ReloadedShader { index, shader, files })
+    }
+
+    /// Returns the descriptors of all pipelines using one of the recompiled shaders, with the new
+    /// version of both their shaders. Explicit entry points & all states are kept.
+    ///
+    /// On error returns the indices of the recompiled shaders of the failing pipeline.
+    fn reloaded_pipelines(
+        &self,
+        compiled: &[ReloadedShader],
+    ) -> Result<Vec<(RenderPipelineId, PipelineDescriptor)>, (Vec<usize>, ShaderError)> {
+        let mut descriptors = Vec::new();
+        for (pipeline_id, descriptor) in self.pipelines.iter() {
+            let vertex_shader = reloaded_shader(&self.shaders, compiled, descriptor.vertex_shader());
+            let fragment_shader = reloaded_shader(&self.shaders, compiled, descriptor.fragment_shader());
+            if vertex_shader.is_none() && fragment_shader.is_none() {
+                continue;
+            }
+
+            let mut reloaded = descriptor.clone();
+            let result = reloaded
+                .set_shaders(
+                    vertex_shader.unwrap_or(descriptor.vertex_shader()).clone(),
+                    fragment_shader.unwrap_or(descriptor.fragment_shader()).clone(),
+                )
+                .and_then(|_| reloaded.validate());
+
+            match result {
+                Ok(()) => descriptors.push((*pipeline_id, reloaded)),
+                Err(error) => {
+                    let rejected = compiled
+                        .iter()
+                        .filter(|reloaded| {
+                            let previous = &self.shaders[reloaded.index].shader;
+                            same_shader(previous, descriptor.vertex_shader()) || same_shader(previous, descriptor.fragment_shader())
+                        })
+                        .map(|reloaded| reloaded.index)
+                        .collect();
+                    return Err((rejected, error));
+                }
+            }
+        }
+        Ok(descriptors)
+    }
+
+    /// Watches the directories of the given files
+    fn watch_directories(&mut self, files: &HashSet<PathBuf>) -> Result<(), ShaderError> {
+        for directory in files.iter().filter_map(|file| file.parent()) {
+            if self.directories.contains(directory) {
+                continue;
+            }
+
+            self.watcher
+                .watch(directory, RecursiveMode::NonRecursive)
+                .map_err(|e| ShaderError::WatchFailed(e.to_string()))?;
+            self.directories.insert(directory.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// Returns all files that were written, created or renamed since the last call
+    fn changed_files(&self) -> HashSet<PathBuf> {
+        self.receiver
+            .try_iter()
+            .filter_map(|event| match event {
+                DebouncedEvent::Create(path) | DebouncedEvent::Write(path) | DebouncedEvent::Rename(_, path) => {
+                    Some(canonical_path(&path))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Returns the absolute path, events of the file watcher refer to absolute paths
+fn canonical_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Returns the shader file & all included files, in-memory snippets are skipped
+fn shader_files(path: &Path, dependencies: &[IncludeDependency]) -> HashSet<PathBuf> {
+    dependencies
+        .iter()
+        .map(|dependency| Path::new(&dependency.name))
+        .filter(|file| file.is_file())
+        .map(canonical_path)
+        .chain(std::iter::once(path.to_path_buf()))
+        .collect()
+}
+
+/// Returns true if both shaders share the same shader module
+fn same_shader(lhs: &Shader, rhs: &Shader) -> bool {
+    Arc::ptr_eq(&lhs.module, &rhs.module)
+}
+
+/// Returns the recompiled version of the given shader, if it was recompiled
+fn reloaded_shader<'a>(
+    shaders: &[WatchedShader],
+    compiled: &'a [ReloadedShader],
+    shader: &Shader,
+) -> Option<&'a Shader> {
+    compiled
+        .iter()
+        .find(|reloaded| same_shader(&shaders[reloaded.index].shader, shader))
+        .map(|reloaded| &reloaded.shader)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::{canonical_path, shader_files};
+    use crate::IncludeDependency;
+    #[cfg(feature = "glsl")]
+    use super::{ShaderReload, ShaderWatcher, same_shader};
+    #[cfg(feature = "glsl")]
+    use crate::{PipelineDescriptor, Renderer, ShaderDefs, ShaderStage};
+
+    /// Creates an empty directory in the temp dir, returns its absolute path
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        canonical_path(&dir)
+    }
+
+    #[test]
+    fn it_collects_shader_file_and_included_files() {
+        let dir = temp_dir("renderer_shader_watcher_files");
+        fs::create_dir_all(dir.join("lighting")).unwrap();
+        fs::write(dir.join("main.glsl"), "void main() {}").unwrap();
+        fs::write(dir.join("lighting/phong.glsl"), "vec3 phong();").unwrap();
+
+        let dependencies = vec![
+            IncludeDependency::new(&dir.join("lighting/../lighting/phong.glsl").to_string_lossy(), "vec3 phong();"),
+            IncludeDependency::new("noise/simplex.glsl", "float snoise(vec2 v);"),
+        ];
+        let files = shader_files(&dir.join("main.glsl"), &dependencies);
+
+        // includes are canonicalized, in-memory snippets are not watched
+        assert_eq!(2, files.len());
+        assert!(files.contains(&dir.join("main.glsl")));
+        assert!(files.contains(&dir.join("lighting/phong.glsl")));
+    }
+
+    #[test]
+    #[cfg(feature = "glsl")]
+    fn it_keeps_previous_pipeline_when_recompile_fails() {
+        const VERTEX_SHADER: &str = r#"
+        #version 450
+        #include "common.glsl"
+        void main() { gl_Position = vec4(ORIGIN, 1.0); }
+        "#;
+
+        let dir = temp_dir("renderer_shader_watcher_reload");
+        fs::write(dir.join("common.glsl"), "const vec3 ORIGIN = vec3(0.0);").unwrap();
+        fs::write(dir.join("vertex.glsl"), VERTEX_SHADER).unwrap();
+        fs::write(dir.join("fragment.glsl"), r#"
+        #version 450
+        layout(location = 0) out vec4 out_color;
+        void main() { out_color = vec4(1.0); }
+        "#).unwrap();
+
+        let mut renderer = futures::executor::block_on(Renderer::new()).unwrap();
+        let mut watcher = ShaderWatcher::new().unwrap();
+        let vertex_shader = watcher.load_shader(&renderer, dir.join("vertex.glsl"), ShaderStage::Vertex).unwrap();
+        let fragment_shader = watcher.load_shader(&renderer, dir.join("fragment.glsl"), ShaderStage::Fragment).unwrap();
+        assert!(watcher.shaders[0].files.contains(&dir.join("common.glsl")));
+
+        let descriptor = PipelineDescriptor::new(vertex_shader.clone(), fragment_shader).unwrap();
+        let pipeline = watcher.create_pipeline(&mut renderer, descriptor).unwrap();
+
+        fs::write(dir.join("vertex.glsl"), "#version 450\nvoid main() { gl_Position = undefined; }").unwrap();
+        assert!(matches!(watcher.reload(&[0], &mut renderer)[..], [ShaderReload::Failed(_, _)]));
+        assert!(same_shader(&vertex_shader, &watcher.shaders[0].shader));
+        assert!(same_shader(&vertex_shader, watcher.pipelines[&pipeline].vertex_shader()));
+        assert_eq!(1, renderer.pipeline_count());
+
+        // a fixed shader replaces the previous one, the pipeline keeps its id
+        fs::write(dir.join("vertex.glsl"), VERTEX_SHADER.replace("1.0", "0.5")).unwrap();
+        assert!(matches!(watcher.reload(&[0], &mut renderer)[..], [ShaderReload::Reloaded(_)]));
+        assert!(!same_shader(&vertex_shader, watcher.pipelines[&pipeline].vertex_shader()));
+        assert_eq!(1, renderer.pipeline_count());
+        assert!(renderer.evict_pipeline(&pipeline));
+    }
+
+    #[test]
+    #[cfg(feature = "glsl")]
+    fn it_reloads_both_stages_of_a_pipeline_together() {
+        let dir = temp_dir("renderer_shader_watcher_interface");
+        fs::write(dir.join("vertex.glsl"), r#"
+        #version 450
+        layout(location = 0) out vec2 o_uv;
+        void main() {
+            o_uv = vec2(SCALE);
+            gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+        }
+        "#).unwrap();
+        fs::write(dir.join("fragment.glsl"), r#"
+        #version 450
+        layout(location = 0) in vec2 uv;
+        layout(location = 0) out vec4 out_color;
+        void main() { out_color = vec4(uv, 0.0, 1.0); }
+        "#).unwrap();
+
+        let mut renderer = futures::executor::block_on(Renderer::new()).unwrap();
+        let mut watcher = ShaderWatcher::new().unwrap();
+        let defs = ShaderDefs::new().with_value("SCALE", "0.5");
+        let vertex_shader = watcher.load_shader_variant(&renderer, dir.join("vertex.glsl"), ShaderStage::Vertex, &defs).unwrap();
+        let fragment_shader = watcher.load_shader(&renderer, dir.join("fragment.glsl"), ShaderStage::Fragment).unwrap();
+        let descriptor = PipelineDescriptor::new(vertex_shader.clone(), fragment_shader.clone()).unwrap();
+        let pipeline = watcher.create_pipeline(&mut renderer, descriptor).unwrap();
+
+        // the varying changes from vec2 to vec3 in both stages, neither fits the previous other stage
+        fs::write(dir.join("vertex.glsl"), r#"
+        #version 450
+        layout(location = 0) out vec3 o_uv;
+        void main() {
+            o_uv = vec3(SCALE);
+            gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+        }
+        "#).unwrap();
+        fs::write(dir.join("fragment.glsl"), r#"
+        #version 450
+        layout(location = 0) in vec3 uv;
+        layout(location = 0) out vec4 out_color;
+        void main() { out_color = vec4(uv, 1.0); }
+        "#).unwrap();
+
+        let reloads = watcher.reload(&[0, 1], &mut renderer);
+        assert!(matches!(reloads[..], [ShaderReload::Reloaded(_), ShaderReload::Reloaded(_)]), "{:?}", reloads);
+
+        // the defines of the variant are kept
+        let descriptor = &watcher.pipelines[&pipeline];
+        assert_eq!(defs, descriptor.vertex_shader().defs);
+        assert!(!same_shader(&vertex_shader, descriptor.vertex_shader()));
+        assert!(!same_shader(&fragment_shader, descriptor.fragment_shader()));
+        assert_eq!(1, renderer.pipeline_count());
+
+        // a single stage that no longer fits the other is rejected
+        fs::write(dir.join("fragment.glsl"), r#"
+        #version 450
+        layout(location = 0) in vec4 uv;
+        layout(location = 0) out vec4 out_color;
+        void main() { out_color = uv; }
+        "#).unwrap();
+
+        let fragment_shader = watcher.shaders[1].shader.clone();
+        let reloads = watcher.reload(&[1], &mut renderer);
+        assert!(matches!(reloads[..], [ShaderReload::Failed(_, _)]), "{:?}", reloads);
+        assert!(same_shader(&fragment_shader, watcher.pipelines[&pipeline].fragment_shader()));
+        assert!(renderer.evict_pipeline(&pipeline));
+    }
+}
@@ -1,6 +1,8 @@
-use spirv_reflect::types::image::ReflectFormat;
+use std::collections::{HashMap, HashSet};
 
-use crate::{VertexFormat, WgpuFrom};
+use spirv_reflect::types::image::{ReflectFormat, ReflectImageFormat};
+
+use crate::{ShaderError, TextureFormat, VertexFormat, WgpuFrom};
 
 impl WgpuFrom<ReflectFormat> for VertexFormat {
     fn from(val: ReflectFormat) -> Self {
@@ -21,3 +23,291 @@ impl WgpuFrom<ReflectFormat> for VertexFormat {
         }
     }
 }
+
+impl From<ReflectImageFormat> for TextureFormat {
+    fn from(format: ReflectImageFormat) -> Self {
+        match format {
+            ReflectImageFormat::RGBA32_FLOAT => TextureFormat::Rgba32Float,
+            ReflectImageFormat::RGBA16_FLOAT => TextureFormat::Rgba16Float,
+            ReflectImageFormat::R32_FLOAT => TextureFormat::R32Float,
+            ReflectImageFormat::RGBA8 => TextureFormat::Rgba8Unorm,
+            ReflectImageFormat::RGBA8_SNORM => TextureFormat::Rgba8Snorm,
+            ReflectImageFormat::RG32_FLOAT => TextureFormat::Rg32Float,
+            ReflectImageFormat::RG16_FLOAT => TextureFormat::Rg16Float,
+            ReflectImageFormat::R11G11B10_FLOAT => TextureFormat::Rg11b10Float,
+            ReflectImageFormat::R16_FLOAT => TextureFormat::R16Float,
+            ReflectImageFormat::RGB10A2 => TextureFormat::Rgb10a2Unorm,
+            ReflectImageFormat::RG8 => TextureFormat::Rg8Unorm,
+            ReflectImageFormat::R8 => TextureFormat::R8Unorm,
+            ReflectImageFormat::RG8_SNORM => TextureFormat::Rg8Snorm,
+            ReflectImageFormat::R8_SNORM => TextureFormat::R8Snorm,
+            ReflectImageFormat::RGBA32_INT => TextureFormat::Rgba32Sint,
+            ReflectImageFormat::RGBA16_INT => TextureFormat::Rgba16Sint,
+            ReflectImageFormat::RGBA8_INT => TextureFormat::Rgba8Sint,
+            ReflectImageFormat::R32_INT => TextureFormat::R32Sint,
+            ReflectImageFormat::RG32_INT => TextureFormat::Rg32Sint,
+            ReflectImageFormat::RG16_INT => TextureFormat::Rg16Sint,
+            ReflectImageFormat::RG8_INT => TextureFormat::Rg8Sint,
+            ReflectImageFormat::R16_INT => TextureFormat::R16Sint,
+            ReflectImageFormat::R8_INT => TextureFormat::R8Sint,
+            ReflectImageFormat::RGBA32_UINT => TextureFormat::Rgba32Uint,
+            ReflectImageFormat::RGBA16_UINT => TextureFormat::Rgba16Uint,
+            ReflectImageFormat::RGBA8_UINT => TextureFormat::Rgba8Uint,
+            ReflectImageFormat::R32_UINT => TextureFormat::R32Uint,
+            ReflectImageFormat::RG32_UINT => TextureFormat::Rg32Uint,
+            ReflectImageFormat::RG16_UINT => TextureFormat::Rg16Uint,
+            ReflectImageFormat::RG8_UINT => TextureFormat::Rg8Uint,
+            ReflectImageFormat::R16_UINT => TextureFormat::R16Uint,
+            ReflectImageFormat::R8_UINT => TextureFormat::R8Uint,
+            format => panic!("Unsupported storage image format {:?}", format),
+        }
+    }
+}
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+const SPIRV_HEADER_WORDS: usize = 5;
+
+const OP_EXECUTION_MODE: u32 = 16;
+const OP_TYPE_IMAGE: u32 = 25;
+const OP_TYPE_SAMPLED_IMAGE: u32 = 27;
+const OP_LOAD: u32 = 61;
+const OP_DECORATE: u32 = 71;
+const OP_SAMPLED_IMAGE: u32 = 86;
+
+const DECORATION_NON_WRITABLE: u32 = 24;
+const EXECUTION_MODE_LOCAL_SIZE: u32 = 17;
+
+/// Converts the SPIR-V binary into words, returns None if the data is not a valid SPIR-V module
+fn spirv_words(spv_data: &[u8]) -> Option<Vec<u32>> {
+    let words = spv_data
+        .chunks_exact(4)
+        .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+        .collect::<Vec<u32>>();
+
+    if words.len() < SPIRV_HEADER_WORDS || words[0] != SPIRV_MAGIC {
+        return None;
+    }
+    Some(words)
+}
+
+/// Validates magic number & header of the SPIR-V module
+pub(crate) fn validate_spirv(words: &[u32]) -> Result<(), ShaderError> {
+    if words.len() < SPIRV_HEADER_WORDS {
+        return Err(ShaderError::InvalidSpirV(format!("Module has {} words, header needs {}", words.len(), SPIRV_HEADER_WORDS)));
+    }
+    if words[0] != SPIRV_MAGIC {
+        return Err(ShaderError::InvalidSpirV(format!("Invalid magic number {:#010x}", words[0])));
+    }
+    Ok(())
+}
+
+/// Converts the SPIR-V binary into words, the binary needs to be word aligned
+pub(crate) fn spirv_from_bytes(spv_data: &[u8]) -> Result<Vec<u32>, ShaderError> {
+    if spv_data.len() % 4 != 0 {
+        return Err(ShaderError::InvalidSpirV(format!("Size of {} bytes is not a multiple of 4", spv_data.len())));
+    }
+
+    let words = spv_data
+        .chunks_exact(4)
+        .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+        .collect::<Vec<u32>>();
+    validate_spirv(&words)?;
+    Ok(words)
+}
+
+/// Calls the function with opcode & operands of every instruction in the module
+fn for_each_instruction<F: FnMut(u32, &[u32])>(words: &[u32], mut f: F) {
+    let mut offset = SPIRV_HEADER_WORDS;
+    while offset < words.len() {
+        let word_count = (words[offset] >> 16) as usize;
+        let opcode = words[offset] & 0xffff;
+        if word_count == 0 || offset + word_count > words.len() {
+            break;
+        }
+
+        f(opcode, &words[offset + 1..offset + word_count]);
+        offset += word_count;
+    }
+}
+
+/// Returns the workgroup size of a compute shader as declared by `layout(local_size_x = ..) in;`
+pub(crate) fn parse_local_size(spv_data: &[u8]) -> Option<[u32; 3]> {
+    let words = spirv_words(spv_data)?;
+
+    let mut local_size = None;
+    for_each_instruction(&words, |opcode, operands| {
+        if opcode == OP_EXECUTION_MODE && operands.len() >= 5 && operands[1] == EXECUTION_MODE_LOCAL_SIZE {
+            local_size = Some([operands[2], operands[3], operands[4]]);
+        }
+    });
+    local_size
+}
+
+/// Properties of descriptor variables that are not exposed by spirv-reflect,
+/// parsed from the SPIR-V instructions directly.
+#[derive(Debug, Default)]
+pub(crate) struct SpirvDecorations {
+    /// Ids of all variables decorated as NonWritable, e.g. `readonly image2D`
+    pub non_writable: HashSet<u32>,
+    /// Ids of all sampler variables that are combined with depth images, e.g. `sampler2DShadow(t, s)`
+    pub comparison_samplers: HashSet<u32>,
+}
+
+impl SpirvDecorations {
+    pub fn parse(spv_data: &[u8]) -> Self {
+        let mut decorations = SpirvDecorations::default();
+        let words = match spirv_words(spv_data) {
+            Some(words) => words,
+            None => return decorations,
+        };
+
+        let mut depth_images = HashSet::new();
+        let mut sampled_image_types = HashMap::new();
+        let mut loads = HashMap::new();
+        let mut sampled_images = Vec::new();
+
+        for_each_instruction(&words, |opcode, operands| match opcode {
+            OP_DECORATE if operands.get(1) == Some(&DECORATION_NON_WRITABLE) => {
+                decorations.non_writable.insert(operands[0]);
+            }
+            OP_TYPE_IMAGE if operands.get(3) == Some(&1) => {
+                depth_images.insert(operands[0]);
+            }
+            OP_TYPE_SAMPLED_IMAGE if operands.len() >= 2 => {
+                sampled_image_types.insert(operands[0], operands[1]);
+            }
+            OP_LOAD if operands.len() >= 3 => {
+                loads.insert(operands[1], operands[2]);
+            }
+            OP_SAMPLED_IMAGE if operands.len() >= 4 => {
+                sampled_images.push((operands[0], operands[3]));
+            }
+            _ => (),
+        });
+
+        decorations.comparison_samplers = sampled_images
+            .iter()
+            .filter(|(result_type, _)| {
+                sampled_image_types
+                    .get(result_type)
+                    .map_or(false, |image_type| depth_images.contains(image_type))
+            })
+            .filter_map(|(_, sampler)| loads.get(sampler).copied())
+            .collect();
+
+        decorations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::{SpirvDecorations, parse_local_size, spirv_from_bytes};
+    use crate::{BindingType, ShaderSource, ShaderStage, VertexFormat, shader_reflect::reflect};
+    #[cfg(not(feature = "glsl"))]
+    use crate::ShaderError;
+
+    fn instruction(opcode: u32, operands: &[u32]) -> Vec<u32> {
+        let mut words = vec![((operands.len() as u32 + 1) << 16) | opcode];
+        words.extend_from_slice(operands);
+        words
+    }
+
+    fn module(instructions: Vec<Vec<u32>>) -> Vec<u8> {
+        let mut words = vec![0x0723_0203, 0x0001_0000, 0, 100, 0];
+        words.extend(instructions.into_iter().flatten());
+        words.iter().flat_map(|w| w.to_le_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn it_finds_non_writable_variables() {
+        let data = module(vec![
+            instruction(71, &[10, 24]),
+            instruction(71, &[11, 33, 0]),
+        ]);
+
+        let decorations = SpirvDecorations::parse(&data);
+        assert!(decorations.non_writable.contains(&10));
+        assert!(!decorations.non_writable.contains(&11));
+    }
+
+    #[test]
+    fn it_finds_comparison_samplers() {
+        let data = module(vec![
+            // %2 = OpTypeImage %float 2D depth=1, %3 = OpTypeImage %float 2D depth=0
+            instruction(25, &[2, 1, 1, 1, 0, 0, 1, 0]),
+            instruction(25, &[3, 1, 1, 0, 0, 0, 1, 0]),
+            instruction(27, &[4, 2]),
+            instruction(27, &[5, 3]),
+            // load the shadow sampler %20 & color sampler %21
+            instruction(61, &[6, 30, 20]),
+            instruction(61, &[6, 31, 21]),
+            instruction(86, &[4, 40, 50, 30]),
+            instruction(86, &[5, 41, 51, 31]),
+        ]);
+
+        let decorations = SpirvDecorations::parse(&data);
+        assert!(decorations.comparison_samplers.contains(&20));
+        assert!(!decorations.comparison_samplers.contains(&21));
+    }
+
+    #[test]
+    fn it_ignores_invalid_data() {
+        let decorations = SpirvDecorations::parse(&[1, 2, 3, 4]);
+        assert!(decorations.non_writable.is_empty());
+        assert!(decorations.comparison_samplers.is_empty());
+        assert_eq!(None, parse_local_size(&[1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn it_finds_local_size() {
+        let data = module(vec![
+            // OpExecutionMode %main OriginUpperLeft, OpExecutionMode %main LocalSize 8 4 1
+            instruction(16, &[1, 7]),
+            instruction(16, &[1, 17, 8, 4, 1]),
+        ]);
+        assert_eq!(Some([8, 4, 1]), parse_local_size(&data));
+        assert_eq!(None, parse_local_size(&module(vec![instruction(16, &[1, 7])])));
+    }
+
+    #[test]
+    fn it_validates_spirv_binaries() {
+        let data = module(vec![instruction(16, &[1, 7])]);
+        assert_eq!(8, spirv_from_bytes(&data).unwrap().len());
+
+        assert!(spirv_from_bytes(&data[..data.len() - 1]).is_err());
+        assert!(spirv_from_bytes(&data[..16]).is_err());
+        assert!(spirv_from_bytes(&[0u8; 20]).is_err());
+    }
+
+    #[test]
+    fn it_loads_precompiled_spirv_files() {
+        // compiled from assets/shaders/triangle.vert, loads without the `glsl` feature
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/shaders/triangle.vert.spv");
+        let data = fs::read(&path).unwrap();
+        assert_eq!(ShaderSource::spirv_from_bytes(&data).unwrap(), ShaderSource::from_file(&path).unwrap());
+
+        let layout = reflect(&data);
+        assert_eq!("main", layout.entry_point);
+        assert_eq!(ShaderStage::Vertex, layout.stage);
+
+        let inputs = layout.vertex_buffer_descriptors
+            .iter()
+            .flat_map(|descriptor| descriptor.attributes.iter())
+            .map(|attribute| (attribute.name.as_str(), attribute.location, attribute.format))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![("i_position", 0, VertexFormat::Float3), ("i_uv", 1, VertexFormat::Float2)], inputs);
+        assert_eq!(vec![("o_uv".to_string(), 0)], layout.outputs.iter().map(|o| (o.name.clone(), o.location)).collect::<Vec<_>>());
+
+        assert_eq!(0, layout.bind_groups[0].index);
+        assert!(matches!(layout.bind_groups[0].bindings[0].binding_type, BindingType::Uniform { .. }));
+    }
+
+    #[test]
+    #[cfg(not(feature = "glsl"))]
+    fn it_rejects_glsl_sources_without_glsl_feature() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/shaders/triangle.vert");
+        assert!(matches!(ShaderSource::from_file(&path), Err(ShaderError::GlslUnsupported(_))));
+    }
+}
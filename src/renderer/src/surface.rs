@@ -66,8 +66,8 @@ impl Surface {
         (&self.window).into()
     }
 
-    /// Remove swap chain
-    pub fn drop_swap_chain(&mut self) {
+    /// Presents the current frame, by dropping the output its content is shown on screen
+    pub fn present(&mut self) {
         self.output.take();
     }
 }
@@ -1,14 +1,16 @@
+pub mod sampler;
 pub mod sampler_descriptor;
 pub mod swapchain_descriptor;
 pub mod texture;
 pub mod texture_descriptor;
 
+pub use sampler::*;
 pub use sampler_descriptor::*;
 pub use swapchain_descriptor::*;
 pub use texture::*;
 pub use texture_descriptor::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TextureFormat {
     // 8 bit formats, unpacked
     R8Unorm,
@@ -0,0 +1,19 @@
+use uuid::Uuid;
+
+/// A handle to a texture sampler
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SamplerId {
+    /// Id of this sampler
+    pub uuid: Uuid,
+    /// True if the sampler is a comparison sampler, e.g. to sample shadow maps
+    pub comparison: bool,
+}
+
+impl SamplerId {
+    pub fn new(comparison: bool) -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            comparison,
+        }
+    }
+}
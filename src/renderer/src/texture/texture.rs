@@ -53,6 +53,20 @@ impl Texture {
     }
 }
 
+/// Returns the number of bytes per row aligned to the alignment required to copy a texture into a buffer
+pub fn padded_bytes_per_row(width: u32, bytes_per_pixel: u32) -> u32 {
+    let unpadded_bytes_per_row = width * bytes_per_pixel;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    ((unpadded_bytes_per_row + align - 1) / align) * align
+}
+
+/// Removes the padding at the end of each row, returns the tightly packed bytes
+pub fn unpad_rows(data: &[u8], padded_bytes_per_row: u32, unpadded_bytes_per_row: u32) -> Vec<u8> {
+    data.chunks_exact(padded_bytes_per_row as usize)
+        .flat_map(|row| row[..unpadded_bytes_per_row as usize].iter().copied())
+        .collect()
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum TextureAspect {
     All,
@@ -143,3 +157,24 @@ impl<'a> From<TextureViewDescriptor> for wgpu::TextureViewDescriptor<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{padded_bytes_per_row, unpad_rows};
+
+    #[test]
+    fn it_pads_bytes_per_row() {
+        assert_eq!(256, padded_bytes_per_row(1, 4));
+        assert_eq!(256, padded_bytes_per_row(64, 4));
+        assert_eq!(512, padded_bytes_per_row(65, 4));
+    }
+
+    #[test]
+    fn it_removes_row_padding() {
+        let mut data = vec![0u8; 2 * 256];
+        data[0..4].copy_from_slice(&[1, 2, 3, 4]);
+        data[256..260].copy_from_slice(&[5, 6, 7, 8]);
+
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8], unpad_rows(&data, 256, 4));
+    }
+}
@@ -65,6 +65,16 @@ pub enum TextureComponentType {
     UInt,
 }
 
+impl From<TextureComponentType> for wgpu::TextureComponentType {
+    fn from(val: TextureComponentType) -> Self {
+        match val {
+            TextureComponentType::Float => wgpu::TextureComponentType::Float,
+            TextureComponentType::Int => wgpu::TextureComponentType::Sint,
+            TextureComponentType::UInt => wgpu::TextureComponentType::Uint,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TextureDescriptor {
     /// Debug label of the texture
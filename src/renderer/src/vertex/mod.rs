@@ -6,7 +6,7 @@ pub use index_buffer::*;
 pub use vertex::*;
 pub use vertex_buffer::*;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum IndexFormat {
     Uint16,
     Uint32,
@@ -21,7 +21,7 @@ impl From<IndexFormat> for wgpu::IndexFormat {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum InputStepMode {
     /// Input data is advanced by Vertex
     Vertex,
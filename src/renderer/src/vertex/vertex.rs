@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum VertexFormat {
     Uchar2,
     Uchar4,
@@ -32,6 +32,9 @@ pub enum VertexFormat {
     Int4,
 }
 
+/// Offsets of vertex attributes are aligned to 4 bytes
+pub const VERTEX_ALIGNMENT: u64 = 4;
+
 impl VertexFormat {
     /// Returns the size in bytes of the vertex format
     pub fn size(&self) -> u64 {
@@ -68,6 +71,33 @@ impl VertexFormat {
             VertexFormat::Int4 => 4 * 4,
         }
     }
+
+    /// Returns the size in bytes including padding, that keeps the next attribute aligned
+    pub fn padded_size(&self) -> u64 {
+        self.size().div_ceil(VERTEX_ALIGNMENT) * VERTEX_ALIGNMENT
+    }
+
+    /// Returns the format of the input variable the shader reads the attribute with,
+    /// e.g. `Uchar4Norm` is read as `vec4` & `Ushort4` as `uvec4`
+    pub fn shader_format(&self) -> VertexFormat {
+        match self {
+            VertexFormat::Uchar2 | VertexFormat::Ushort2 => VertexFormat::Uint2,
+            VertexFormat::Uchar4 | VertexFormat::Ushort4 => VertexFormat::Uint4,
+            VertexFormat::Char2 | VertexFormat::Short2 => VertexFormat::Int2,
+            VertexFormat::Char4 | VertexFormat::Short4 => VertexFormat::Int4,
+            VertexFormat::Uchar2Norm
+            | VertexFormat::Char2Norm
+            | VertexFormat::Ushort2Norm
+            | VertexFormat::Short2Norm
+            | VertexFormat::Half2 => VertexFormat::Float2,
+            VertexFormat::Uchar4Norm
+            | VertexFormat::Char4Norm
+            | VertexFormat::Ushort4Norm
+            | VertexFormat::Short4Norm
+            | VertexFormat::Half4 => VertexFormat::Float4,
+            format => *format,
+        }
+    }
 }
 
 impl From<VertexFormat> for wgpu::VertexFormat {
@@ -16,7 +16,7 @@ impl VertexBuffer {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct VertexAttributeDescriptor {
     /// The attribute name
     pub name: String,
@@ -44,7 +44,7 @@ impl VertexAttributeDescriptor {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct VertexBufferDescriptor {
     /// Debug label
     pub label: String,
@@ -68,11 +68,11 @@ impl VertexBufferDescriptor {
                 format: format.clone(),
             };
 
-            offset += format.size();
-            attributes.push(descriptor.into());
+            offset += format.padded_size();
+            attributes.push(descriptor);
         }
 
-        let stride = verts.iter().map(|f| f.size()).sum();
+        let stride = verts.iter().map(|f| f.padded_size()).sum();
 
         Self {
             label: "".to_string(),
@@ -95,9 +95,9 @@ impl VertexBufferDescriptor {
         }
     }
 
-    /// Returns the size of all vertex attributes
+    /// Returns the size of all vertex attributes, including padding
     pub fn stride(&self) -> u64 {
-        self.attributes.iter().map(|desc| desc.size()).sum()
+        self.attributes.iter().map(|desc| desc.format.padded_size()).sum()
     }
 }
 
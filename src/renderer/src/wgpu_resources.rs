@@ -1,16 +1,24 @@
 use std::collections::HashMap;
 
-use crate::{BindGroupDescriptorId, BindGroupId, IndexBuffer, RenderPipelineId, VertexBuffer};
+use crate::{BindGroupDescriptorId, BindGroupId, Buffer, ComputePipelineId, IndexBuffer, PipelineKey, RenderPipelineId, SamplerId, VertexBuffer};
 
 /// Internal struct to keep all WGPU related structs
 #[derive(Debug)]
 pub(crate) struct WgpuResources {
     /// List of all Render pipelines
     pub render_pipelines: HashMap<RenderPipelineId, wgpu::RenderPipeline>,
+    /// Maps the cache key of a pipeline descriptor to the pipeline created from it
+    pub render_pipeline_keys: HashMap<PipelineKey, RenderPipelineId>,
+    /// List of all Compute pipelines
+    pub compute_pipelines: HashMap<ComputePipelineId, wgpu::ComputePipeline>,
     /// Map of all vertex buffers
     pub vertex_buffers: HashMap<VertexBuffer, wgpu::Buffer>,
     /// Map of all index buffers
     pub index_buffers: HashMap<IndexBuffer, wgpu::Buffer>,
+    /// Map of all generic buffers, e.g. uniform buffers
+    pub buffers: HashMap<Buffer, wgpu::Buffer>,
+    /// Map of all samplers
+    pub samplers: HashMap<SamplerId, wgpu::Sampler>,
     /// The list of all bind group layouts
     pub bind_group_layouts: HashMap<BindGroupDescriptorId, wgpu::BindGroupLayout>,
     /// The list of all bind groups
@@ -21,8 +29,12 @@ impl Default for WgpuResources {
     fn default() -> Self {
         Self {
             render_pipelines: HashMap::new(),
+            render_pipeline_keys: HashMap::new(),
+            compute_pipelines: HashMap::new(),
             vertex_buffers: HashMap::new(),
             index_buffers: HashMap::new(),
+            buffers: HashMap::new(),
+            samplers: HashMap::new(),
             bind_group_layouts: HashMap::new(),
             bind_groups: HashMap::new(),
         }
@@ -37,6 +49,13 @@ impl WgpuResources {
             .expect("No RenderPipeline with id found")
     }
 
+    /// Returns the compute pipeline by its id, fails otherwise
+    pub fn get_compute_pipeline(&self, pipeline_id: &ComputePipelineId) -> &wgpu::ComputePipeline {
+        self.compute_pipelines
+            .get(pipeline_id)
+            .expect("No ComputePipeline with id found")
+    }
+
     /// Returns the index buffer by id, fails otherwise
     pub fn get_index_buffer(&self, index_buffer: &IndexBuffer) -> &wgpu::Buffer {
         self.index_buffers
@@ -51,6 +70,20 @@ impl WgpuResources {
             .expect("No Vertex Buffer with id found")
     }
 
+    /// Returns the buffer by id, fails otherwise
+    pub fn get_buffer(&self, buffer: &Buffer) -> &wgpu::Buffer {
+        self.buffers
+            .get(buffer)
+            .expect("No Buffer with id found")
+    }
+
+    /// Returns the sampler by id, fails otherwise
+    pub fn get_sampler(&self, sampler: &SamplerId) -> &wgpu::Sampler {
+        self.samplers
+            .get(sampler)
+            .expect("No Sampler with id found")
+    }
+
     /// Returns the bind group layout by id
     pub fn get_bind_group_layout(
         &self,
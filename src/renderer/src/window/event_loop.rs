@@ -20,7 +20,8 @@ impl<'a> EventLoop {
     where
         T: WindowHandler + Sized + 'static
     {
-        let mut renderer = futures::executor::block_on(Renderer::new());
+        let mut renderer = futures::executor::block_on(Renderer::new())
+            .expect("Failed to create renderer");
         let mut window = Window::new(settings, &self.event_loop, &renderer);
         let mut window_handler = T::load(&window, &app.resources, &mut renderer);
 
@@ -40,8 +41,9 @@ impl<'a> EventLoop {
                 }
                 winit::event::Event::RedrawRequested(window_id) => {
                     if window.winit_window().id() == window_id {
+                        renderer.begin_frame();
                         window_handler.render(&mut window, &mut renderer);
-                        window.swap_buffers();
+                        renderer.end_frame(&mut window.surface);
                         window.winit_window().request_redraw();
                     }
                 }
@@ -58,7 +58,7 @@ impl Window {
 
     /// "Swaps" the buffer, by removing the surface output its content is rendered
     pub fn swap_buffers(&mut self) {
-        &self.surface.drop_swap_chain();
+        self.surface.present();
     }
 }
 